@@ -1,4 +1,7 @@
-use std::fs;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use covid::{
     epidemic::*,
@@ -9,6 +12,7 @@ use covid::{
     utils::*,
 };
 use csv::*;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 type Params = EpiParamsFull<AgeParam>;
@@ -34,12 +38,6 @@ pub struct Config {
     epicurve: Option<Epicurve>,
 }
 
-impl Config {
-    pub fn write_data(&self, data: &String, name: &str) {
-        fs::write(name, data).unwrap();
-    }
-}
-
 impl Default for Config {
     fn default() -> Self {
         Config {
@@ -66,7 +64,7 @@ pub struct TableRow {
     critical: Real,
 }
 
-pub fn read_params_table(path: &str, params: &mut Params) -> Result<()> {
+pub fn read_params_table(path: &Path, params: &mut Params) -> Result<()> {
     let mut reader = csv::Reader::from_path(path)?;
     let mut prob_severe: AgeDistribution10 = [0.0; 9];
     let mut prob_asymptomatic: AgeDistribution10 = [0.0; 9];
@@ -94,13 +92,26 @@ pub fn main() {
     use simple_logger::SimpleLogger;
     SimpleLogger::new().init().unwrap();
 
-    let cfg_data = fs::read_to_string("conf.toml").unwrap();
+    // A directory argument runs all conf_*.toml files in it as a batch
+    match env::args().nth(1) {
+        Some(dir) if Path::new(&dir).is_dir() => run_batch(Path::new(&dir)),
+        _ => {
+            let csv = run_config(Path::new("conf.toml"), Path::new("params.csv"));
+            fs::write("epicurve.csv", &csv).unwrap();
+            println!("{}", &csv);
+        }
+    }
+}
+
+/// Run a single configuration file and return the rendered epicurve.
+pub fn run_config(path: &Path, params_path: &Path) -> String {
+    let cfg_data = fs::read_to_string(path).unwrap();
     let mut cfg: Config = toml::from_str(&cfg_data).unwrap();
     let mut params = cfg.params.unwrap_or_default();
 
-    match read_params_table("params.csv", &mut params) {
+    match read_params_table(params_path, &mut params) {
         Ok(_) => {
-            println!("Using distributions from params.csv");
+            println!("Using distributions from {}", params_path.display());
         }
         _ => {}
     }
@@ -109,10 +120,34 @@ pub fn main() {
     if cfg.verbose {
         println!("{:#?}", cfg);
     }
-    simple_simulation(cfg);
+    return simple_simulation(cfg);
+}
+
+/// Run each conf_<name>.toml file in directory in parallel, writing the
+/// results to epicurve_<name>.csv in the same directory.
+pub fn run_batch(dir: &Path) {
+    let mut scenarios: Vec<(PathBuf, PathBuf)> = vec![];
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if let Some(name) = name
+            .strip_prefix("conf_")
+            .and_then(|s| s.strip_suffix(".toml"))
+        {
+            let output = dir.join(format!("epicurve_{}.csv", name));
+            scenarios.push((path, output));
+        }
+    }
+
+    let params_path = dir.join("params.csv");
+    scenarios.par_iter().for_each(|(path, output)| {
+        let csv = run_config(path, &params_path);
+        fs::write(output, &csv).unwrap();
+        println!("{} -> {}", path.display(), output.display());
+    });
 }
 
-pub fn simple_simulation(cfg: Config) {
+pub fn simple_simulation(cfg: Config) -> String {
     type T = SimpleAgent<SEICHAR<()>, bool>;
     let sampler = SimpleSampler::new(cfg.n_contacts, cfg.prob_infection);
     let population: Vec<T>;
@@ -143,8 +178,5 @@ pub fn simple_simulation(cfg: Config) {
     // Configure simulation
     sim.run(cfg.num_iter);
 
-    // Write output
-    let csv = sim.render_epicurve_csv(T::CSV_HEADER);
-    cfg.write_data(&csv, "epicurve.csv");
-    println!("{}", &csv);
+    return sim.render_epicurve_csv(T::CSV_HEADER);
 }
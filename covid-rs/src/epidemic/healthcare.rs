@@ -1,3 +1,65 @@
+use crate::{params::PROB_DEATH_ICU_COLLAPSE, prelude::Real};
+use serde::{Deserialize, Serialize};
+
+/// Capacity and current occupancy of the healthcare system.
+///
+/// Severe cases occupy regular beds and critical cases occupy ICUs. Each
+/// resource can be stretched by some overflow capacity (e.g., improvised
+/// beds) and, once even those are exhausted, the care of critical patients
+/// collapses and their fatality rises to prob_death_collapse.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealthcareCapacity {
+    pub num_beds: usize,
+    pub num_icus: usize,
+    pub maximum_overflow_beds: usize,
+    pub maximum_overflow_icus: usize,
+    pub occupied_beds: usize,
+    pub occupied_icus: usize,
+    pub prob_death_collapse: Real,
+}
+
+impl HealthcareCapacity {
+    /// Create empty healthcare system with the given number of beds and ICUs
+    /// and no overflow capacity.
+    pub fn new(num_beds: usize, num_icus: usize) -> Self {
+        HealthcareCapacity {
+            num_beds,
+            num_icus,
+            maximum_overflow_beds: 0,
+            maximum_overflow_icus: 0,
+            occupied_beds: 0,
+            occupied_icus: 0,
+            prob_death_collapse: PROB_DEATH_ICU_COLLAPSE,
+        }
+    }
+
+    /// Set the number of occupied beds and ICUs.
+    pub fn set_occupancy(&mut self, beds: usize, icus: usize) -> &mut Self {
+        self.occupied_beds = beds;
+        self.occupied_icus = icus;
+        return self;
+    }
+
+    /// True if all beds, including overflow beds, are occupied.
+    pub fn is_beds_collapsed(&self) -> bool {
+        self.occupied_beds >= self.num_beds + self.maximum_overflow_beds
+    }
+
+    /// True if all ICUs, including overflow ICUs, are occupied.
+    pub fn is_icus_collapsed(&self) -> bool {
+        self.occupied_icus >= self.num_icus + self.maximum_overflow_icus
+    }
+
+    /// Probability of death of critical patients, given the baseline value
+    /// under proper care.
+    pub fn prob_death(&self, base: Real) -> Real {
+        if self.is_icus_collapsed() {
+            return base.max(self.prob_death_collapse);
+        }
+        return base;
+    }
+}
+
 /*
 use std::convert::TryFrom;
 
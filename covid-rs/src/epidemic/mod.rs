@@ -1,8 +1,10 @@
 mod healthcare;
 mod model;
+mod stability;
 mod vaccines;
 mod variants;
 pub use self::healthcare::*;
 pub use self::model::*;
+pub use self::stability::*;
 pub use self::vaccines::*;
 pub use self::variants::*;
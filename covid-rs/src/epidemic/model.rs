@@ -1,6 +1,6 @@
 use crate::{
     prelude::Real,
-    sim::{HasEpiModel, Id, Population},
+    sim::{HasEpiModel, HasPosition, Id, Population},
 };
 use paste::paste;
 use rand::{prelude::SliceRandom, Rng};
@@ -22,7 +22,8 @@ pub trait EpiModel: Sized + Clone {
     /// Index of the susceptible state.
     const S: usize;
 
-    /// Index of the dead state.
+    /// Index of the dead state. Models without a Dead compartment must use
+    /// ABSENT.
     const D: usize;
 
     /// A type that represents the disease associated with the epidemiological
@@ -120,12 +121,74 @@ pub trait EpiModel: Sized + Clone {
         self.index() == Self::S
     }
 
+    /// Return the relative probability that agent is infected in a contact
+    /// with a contagious agent, compared to a fully susceptible agent.
+    ///
+    /// The default implementation is 1.0 for susceptible agents and 0.0
+    /// otherwise. Models with partial immunity (e.g., leaky vaccines) may
+    /// return intermediate values.
+    fn susceptibility(&self) -> Real {
+        if self.is_susceptible() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Return the relative probability that agent is infected in a contact
+    /// with the given contagious agent, compared to a fully susceptible agent.
+    ///
+    /// The default implementation ignores other and returns susceptibility().
+    /// Models with strain-specific immunity, such as SimpleAgent with
+    /// CrossImmunity, take the strain of other into account.
+    fn susceptibility_to(&self, _other: &Self) -> Real {
+        self.susceptibility()
+    }
+
+    /// Return the relative odds that a non-susceptible agent is infected again
+    /// in a contact with a contagious agent.
+    ///
+    /// The default implementation is 0.0, i.e., contamination confers complete
+    /// immunity. Models with waning or partial immunity return positive values
+    /// for recovered agents, which are then scaled by the
+    /// relative_susceptibility_recovered param in the simulation. Since this
+    /// param is not known to the model, can_contaminate() and samplers ignore
+    /// reinfections.
+    fn reinfection_odds(&self) -> Real {
+        0.0
+    }
+
     /// Return true if agent is able to contaminate other agents. It must return
     /// true even if the probability of contamination is very low.
     fn is_contagious(&self) -> bool {
         self.contagion_odds() > 0.0
     }
 
+    /// Return true if agent shows symptoms. Contact tracing policies may
+    /// detect agents at symptom onset.
+    ///
+    /// The default implementation considers all contagious agents to be
+    /// symptomatic. Models with asymptomatic compartments must override it.
+    fn is_symptomatic(&self) -> bool {
+        self.is_contagious()
+    }
+
+    /// Return true if agent is isolated (e.g., by contact tracing). Isolated
+    /// agents have zero contagion odds and cannot be contaminated, so samplers
+    /// skip them.
+    ///
+    /// The default implementation is false, since bare epidemic models do not
+    /// store isolation.
+    fn is_isolated(&self) -> bool {
+        false
+    }
+
+    /// Isolate or release agent and return true if agent supports isolation.
+    /// The default implementation does nothing and returns false.
+    fn isolate(&mut self, _value: bool) -> bool {
+        false
+    }
+
     /// Return true if agent is recovered from disease.
     fn is_recovered(&self) -> bool;
 
@@ -141,6 +204,60 @@ pub trait EpiModel: Sized + Clone {
     }
 }
 
+/// Index of a compartment that does not exist in a model (e.g., SIS has no
+/// Recovered or Dead states). It is never returned by EpiModel::index() and
+/// must not be used to index epicurves.
+pub const ABSENT: usize = usize::MAX;
+
+/// Check at compile time that the compartment indices declared by a model are
+/// within 0..CARDINALITY (or are ABSENT) and that S and D are distinct.
+///
+/// Additional indices (e.g., E, I, R from SEIRLike) can be listed after the
+/// type. The corresponding traits must be in scope.
+///
+/// ```compile_fail
+/// use covid::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct Bad;
+///
+/// impl EpiModel for Bad {
+///     const CARDINALITY: usize = 2;
+///     const CSV_HEADER: &'static str = "S,D";
+///     const S: usize = 0;
+///     const D: usize = 2;
+///     type Disease = ();
+///     type Clinical = ();
+///
+///     fn index(&self) -> usize { 0 }
+///     fn new_infectious_with(_: &()) -> Self { Bad }
+///     fn force_infectious(&mut self, _: bool) -> bool { false }
+///     fn contagion_odds(&self) -> Real { 0.0 }
+///     fn transfer_contamination_from(&mut self, _: &Self) -> bool { false }
+///     fn is_recovered(&self) -> bool { false }
+/// }
+///
+/// covid::assert_model_indices!(Bad);
+/// ```
+#[macro_export]
+macro_rules! assert_model_indices {
+    ($ty:ty $(: $($idx:ident),* $(,)?)?) => {
+        const _: () = {
+            use $crate::epidemic::EpiModel;
+            let n = <$ty as EpiModel>::CARDINALITY;
+            assert!(<$ty as EpiModel>::S < n, "S index out of range");
+            let absent = $crate::epidemic::ABSENT;
+            let d = <$ty as EpiModel>::D;
+            assert!(d < n || d == absent, "D index out of range");
+            assert!(<$ty as EpiModel>::S != d, "S and D must be distinct");
+            $($(
+                let idx = <$ty>::$idx;
+                assert!(idx < n || idx == absent, concat!(stringify!($idx), " index out of range"));
+            )*)?
+        };
+    };
+}
+
 /// Create methods each_<comp>, each_<comp>_mut and n_<comp> for querying agents
 /// in each compartment of an epidemiological model
 macro_rules! compartment_methods {
@@ -197,6 +314,7 @@ pub trait EpiModelPopulationExt: Population {
     compartment_methods!(infectious, for=SEIRLike);
     compartment_methods!(exposed, for=SEIRLike);
     compartment_methods!(asymptomatic, for=SEICHARLike);
+    compartment_methods!(vaccinated, for=SEIRVLike);
     compartment_methods!(severe, for=SEICHARLike);
     compartment_methods!(critical, for=SEICHARLike);
 
@@ -258,6 +376,30 @@ pub trait EpiModelPopulationExt: Population {
         return [s, e, i, r, n];
     }
 
+    /// Count the number of (Susceptible, Vaccinated, Exposed, Infectious,
+    /// Recovered, Total) individuals.
+    fn count_seirv(&self) -> [usize; 6]
+    where
+        Self::State: SEIRVLike,
+    {
+        let (mut s, mut v, mut e, mut i, mut r, mut n) = (0, 0, 0, 0, 0, 0);
+        self.each_agent(&mut |_, st: &Self::State| {
+            if st.is_vaccinated() {
+                v += 1;
+            } else if st.is_susceptible() {
+                s += 1;
+            } else if st.is_exposed() {
+                e += 1;
+            } else if st.is_infectious() {
+                i += 1;
+            } else if st.is_recovered() {
+                r += 1;
+            }
+            n += 1;
+        });
+        return [s, v, e, i, r, n];
+    }
+
     /// Count the number of (Susceptible, Exposed, Asymptomatic, Infectious, Recovered, Total)
     /// individuals.
     fn count_seair(&self) -> [usize; 6]
@@ -358,6 +500,97 @@ pub trait EpiModelPopulationExt: Population {
         return self.contaminate_at_random_from(&infectious, n, rng);
     }
 
+    /// Contaminate the n susceptible agents nearest to center that are within
+    /// the given radius. This models a localized introduction of the disease
+    /// rather than seeding cases at random across space.
+    ///
+    /// Ties in distance are broken at random. Fewer than n agents are
+    /// contaminated if there are not enough susceptibles within the radius.
+    fn contaminate_cluster<R: Rng>(
+        &mut self,
+        center: (Real, Real),
+        radius: Real,
+        n: usize,
+        rng: &mut R,
+    ) -> &mut Self
+    where
+        Self::State: EpiModel + HasPosition,
+        <Self::State as EpiModel>::Clinical: Default,
+    {
+        let mut candidates = vec![];
+        self.each_agent(&mut |id, st: &Self::State| {
+            let dist = st.distance_to(center);
+            if st.is_susceptible() && dist <= radius {
+                candidates.push((dist, id));
+            }
+        });
+        candidates.shuffle(rng);
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let infectious = Self::State::new_infectious();
+        for (_, id) in candidates.into_iter().take(n) {
+            self.map_agent_mut(id, |st| st.transfer_contamination_from(&infectious));
+        }
+        return self;
+    }
+
+    /// Force the susceptible agents with the given ids into an infectious
+    /// state with the given clinical data and return how many of them were
+    /// susceptible.
+    ///
+    /// Agents that are not susceptible, including repeated ids, are not
+    /// modified and are not counted.
+    fn contaminate_ids(
+        &mut self,
+        ids: &[Id],
+        clinical: &<Self::State as EpiModel>::Clinical,
+    ) -> usize
+    where
+        Self::State: EpiModel,
+    {
+        let infectious = Self::State::new_infectious_with(clinical);
+        let mut count = 0;
+        for &id in ids {
+            self.map_agent_mut(id, |st| {
+                if st.is_susceptible() {
+                    st.transfer_contamination_from(&infectious);
+                    st.force_infectious(false);
+                    count += 1;
+                }
+            });
+        }
+        return count;
+    }
+
+    /// Contaminate n random susceptible agents that satisfy the predicate,
+    /// e.g., |ag| ag.age() >= 60, and return the number of contaminated
+    /// agents, which is smaller than n if there are not enough candidates.
+    fn contaminate_matching<R: Rng>(
+        &mut self,
+        pred: impl Fn(&Self::State) -> bool,
+        n: usize,
+        rng: &mut R,
+    ) -> usize
+    where
+        Self::State: EpiModel,
+        <Self::State as EpiModel>::Clinical: Default,
+    {
+        let mut candidates = vec![];
+        self.each_agent(&mut |id, st: &Self::State| {
+            if st.is_susceptible() && pred(st) {
+                candidates.push(id);
+            }
+        });
+        candidates.shuffle(rng);
+        candidates.truncate(n);
+
+        let infectious = Self::State::new_infectious();
+        for &id in candidates.iter() {
+            self.map_agent_mut(id, |st| st.transfer_contamination_from(&infectious));
+        }
+        return candidates.len();
+    }
+
     /// Force all contaminated agents into an infectious state possibly even
     /// including dead elements.
     fn force_infectious(&mut self, force_dead: bool) -> &mut Self
@@ -491,6 +724,17 @@ pub trait SEIRLike: EpiModel {
     }
 }
 
+/// Extends the SEIR model with an explicit Vaccinated compartment. Vaccinated
+/// agents are still susceptible, possibly with a reduced susceptibility.
+pub trait SEIRVLike: SEIRLike {
+    const V: usize;
+    is_state!(vaccinated, index = V);
+
+    /// Move a susceptible agent to the vaccinated compartment. Return false
+    /// and leave agent unchanged if it is not in the Susceptible state.
+    fn vaccinate(&mut self, with: &Self::Clinical) -> bool;
+}
+
 /// Extends the SEIR with asymptomatic agents and a severity model in which
 /// agents may require healthcare.
 ///
@@ -550,16 +794,45 @@ where
         self.epimodel().is_susceptible()
     }
 
+    default fn susceptibility(&self) -> Real {
+        self.epimodel().susceptibility()
+    }
+
+    default fn susceptibility_to(&self, _other: &Self) -> Real {
+        self.susceptibility()
+    }
+
+    default fn reinfection_odds(&self) -> Real {
+        self.epimodel().reinfection_odds()
+    }
+
     default fn is_contagious(&self) -> bool {
         self.epimodel().is_contagious()
     }
 
+    default fn is_symptomatic(&self) -> bool {
+        self.epimodel().is_symptomatic()
+    }
+
+    default fn is_isolated(&self) -> bool {
+        self.isolated()
+    }
+
+    default fn isolate(&mut self, value: bool) -> bool {
+        self.set_isolated(value)
+    }
+
     default fn contagion_odds(&self) -> Real {
+        if self.is_isolated() {
+            return 0.0;
+        }
         self.epimodel().contagion_odds()
     }
 
     default fn can_contaminate(&self, other: &Self) -> bool {
-        self.epimodel().can_contaminate(other.epimodel())
+        !self.is_isolated()
+            && !other.is_isolated()
+            && self.epimodel().can_contaminate(other.epimodel())
     }
 
     default fn contaminated_from(&self, other: &Self) -> Option<Self> {
@@ -600,3 +873,117 @@ where
     P::State: EpiModel,
 {
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{SEIRm, SIRm, SeirAgent, SEIR},
+        prelude::Age,
+        sim::HasAge,
+        utils::seeded_rng,
+    };
+
+    #[derive(Debug, Clone, Default)]
+    struct SpatialAgent {
+        model: SIRm,
+        position: (Real, Real),
+    }
+
+    impl HasEpiModel for SpatialAgent {
+        type Model = SIRm;
+
+        fn epimodel(&self) -> &SIRm {
+            &self.model
+        }
+
+        fn epimodel_mut(&mut self) -> &mut SIRm {
+            &mut self.model
+        }
+    }
+
+    impl HasPosition for SpatialAgent {
+        fn position(&self) -> (Real, Real) {
+            self.position
+        }
+
+        fn set_position(&mut self, value: (Real, Real)) -> &mut Self {
+            self.position = value;
+            return self;
+        }
+    }
+
+    #[test]
+    fn contaminate_cluster_stays_within_radius() {
+        let mut pop: Vec<SpatialAgent> = vec![];
+        for i in 0..400 {
+            let mut ag = SpatialAgent::default();
+            ag.set_position(((i % 20) as Real, (i / 20) as Real));
+            pop.push(ag);
+        }
+        let center = (5.0, 5.0);
+        let radius = 2.5;
+        pop.contaminate_cluster(center, radius, 10, &mut seeded_rng(42u64));
+
+        assert_eq!(pop.n_contaminated(), 10);
+        pop.each_contaminated(|_, ag| assert!(ag.distance_to(center) <= radius));
+    }
+
+    #[test]
+    fn contaminate_specific_and_matching_agents() {
+        let mut pop = vec![SeirAgent::<()>::default(); 100];
+        for (i, ag) in pop.iter_mut().enumerate() {
+            ag.set_age(i as Age);
+        }
+        pop[3].set_epimodel(SEIR::Recovered(()));
+
+        assert_eq!(pop.contaminate_ids(&[1, 2, 3, 2], &()), 2);
+        assert!(pop[1].is_contagious() && pop[2].is_contagious());
+        assert!(pop[3].is_recovered());
+
+        let rng = &mut seeded_rng(42u64);
+        assert_eq!(pop.contaminate_matching(|ag| ag.age() >= 60, 10, rng), 10);
+        assert_eq!(pop.iter().filter(|ag| ag.is_contaminated()).count(), 13);
+        pop.each_agent(&mut |id, ag: &SeirAgent<()>| {
+            assert!(id <= 3 || id >= 60 || ag.is_susceptible());
+        });
+        assert_eq!(pop.contaminate_matching(|ag| ag.age() < 10, 10, rng), 7);
+    }
+
+    #[test]
+    fn weighted_sum_counts_infectious() {
+        let mut pop = vec![SEIRm::default(); 100];
+        for (i, st) in pop.iter_mut().enumerate() {
+            match i % 4 {
+                1 => *st = SEIR::Exposed(()),
+                2 => *st = SEIR::Infectious(()),
+                _ => {}
+            }
+        }
+        let n = pop.weighted_sum(|s| s.is_infectious() as i32 as Real);
+        assert_eq!(n, pop.n_infectious() as Real);
+        assert_eq!(n, 25.0);
+    }
+
+    #[test]
+    fn recovered_agents_are_reexposed_with_own_clinical_data() {
+        let src: SEIR<u8> = SEIR::Infectious(1);
+        let recovered: SEIR<u8> = SEIR::Recovered(2);
+        assert_eq!(recovered.reinfection_odds(), 1.0);
+        assert_eq!(SEIR::<u8>::Dead(2).reinfection_odds(), 0.0);
+        assert!(!src.can_contaminate(&recovered));
+        assert_eq!(recovered.contaminated_from(&src), None);
+        let mut reinfected = recovered;
+        assert!(reinfected.transfer_contamination_from(&src));
+        assert_eq!(reinfected, SEIR::Exposed(2));
+        assert_eq!(
+            SEIR::<u8>::Susceptible.contaminated_from(&src),
+            Some(SEIR::Exposed(1))
+        );
+        assert!(!SIRm::Infectious(()).can_contaminate(&SIRm::Recovered(())));
+    }
+}
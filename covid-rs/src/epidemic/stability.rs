@@ -0,0 +1,69 @@
+//! Linear stability analysis of the mean-field SEIR dynamics.
+//!
+//! The ODE for the fractions of each compartment is
+//!
+//!   ds/dt = -β s i
+//!   de/dt = β s i - σ e
+//!   di/dt = σ e - γ i
+//!
+//! in which σ = 1 / incubation_period, γ = 1 / infectious_period and β is the
+//! daily number of infections per infectious agent in a fully susceptible
+//! population (i.e., contacts * prob_infection).
+use crate::{params::EpiParamsLocalT, prelude::Real};
+use ndarray::{array, Array2};
+
+/// Jacobian of the mean-field ODE at the disease-free equilibrium.
+///
+/// Only the infected compartments (E, I) are included, in this order. The
+/// susceptible and recovered directions have null eigenvalues and do not
+/// affect the stability of the equilibrium. An introduction grows if the
+/// leading eigenvalue of this matrix is positive.
+pub fn jacobian_at_dfe(params: &impl EpiParamsLocalT, beta: Real) -> Array2<Real> {
+    let sigma = 1.0 / params.incubation_period();
+    let gamma = 1.0 / params.infectious_period();
+    return array![[-sigma, beta], [sigma, -gamma]];
+}
+
+/// Basic reproduction number β / γ of the mean-field ODE.
+pub fn estimated_r0(params: &impl EpiParamsLocalT, beta: Real) -> Real {
+    beta * params.infectious_period()
+}
+
+/// Exponential growth rate of an introduction, i.e., the spectral abscissa
+/// (largest real part of the eigenvalues) of jacobian_at_dfe().
+pub fn growth_rate(params: &impl EpiParamsLocalT, beta: Real) -> Real {
+    let jacobian = jacobian_at_dfe(params, beta);
+    let trace = jacobian[(0, 0)] + jacobian[(1, 1)];
+    let det = jacobian[(0, 0)] * jacobian[(1, 1)] - jacobian[(0, 1)] * jacobian[(1, 0)];
+    let disc = trace * trace / 4.0 - det;
+    return trace / 2.0 + disc.max(0.0).sqrt();
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::EpiParamsFull;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn jacobian_spectral_abscissa_is_positive_iff_r0_above_one() {
+        let params: EpiParamsFull<Real> = Default::default();
+        let gamma = 1.0 / params.infectious_period();
+        for k in 1..40 {
+            let beta = 0.05 * k as Real * gamma;
+            let r0 = estimated_r0(&params, beta);
+            let rate = growth_rate(&params, beta);
+            assert_eq!(rate > 0.0, r0 > 1.0, "beta: {}, r0: {}", beta, r0);
+        }
+        assert_approx_eq!(growth_rate(&params, gamma), 0.0);
+
+        let jacobian = jacobian_at_dfe(&params, 0.5);
+        assert_eq!(jacobian.dim(), (2, 2));
+        assert_approx_eq!(jacobian[(1, 0)], 1.0 / params.incubation_period());
+        assert_eq!(jacobian[(0, 1)], 0.5);
+    }
+}
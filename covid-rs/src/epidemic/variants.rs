@@ -1,10 +1,11 @@
 use crate::prelude::Real;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::convert::{From, TryFrom};
 
 /// Baseline/Variant of concern.
 /// A bool-like enum that defines if a variant is a Variant of concern
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Variant {
     Baseline,
     VoC,
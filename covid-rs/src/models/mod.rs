@@ -1,15 +1,28 @@
+use crate::{
+    assert_model_indices,
+    epidemic::{EpiModel, SEICHARLike, SEIRLike, SEIRVLike},
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Debug;
 
 pub mod seair;
 pub mod seichar;
+pub mod seicharp;
 pub mod seir;
+pub mod seirv;
 pub mod simple;
 pub mod sir;
+pub mod sis;
+pub mod staged;
 pub use seair::*;
 pub use seichar::*;
+pub use seicharp::*;
 pub use seir::*;
+pub use seirv::*;
 pub use simple::*;
 pub use sir::*;
+pub use sis::*;
+pub use staged::*;
 
 impl<C: Debug> Debug for SIR<C> {
     default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -22,6 +35,24 @@ impl<C: Debug> Debug for SIR<C> {
     }
 }
 
+impl<C: Debug> Debug for SIS<C> {
+    default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Susceptible => write!(f, "S"),
+            Self::Infectious(c) => write!(f, "I({:?})", c),
+        }
+    }
+}
+
+impl Debug for SIS<()> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Susceptible => write!(f, "S"),
+            Self::Infectious(_) => write!(f, "I"),
+        }
+    }
+}
+
 impl<C: Debug> Debug for SEIR<C> {
     default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -34,6 +65,19 @@ impl<C: Debug> Debug for SEIR<C> {
     }
 }
 
+impl<C: Debug, const LEAK: u8> Debug for SEIRV<C, LEAK> {
+    default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Susceptible => write!(f, "S"),
+            Self::Vaccinated(c) => write!(f, "V({:?})", c),
+            Self::Exposed(c) => write!(f, "E({:?})", c),
+            Self::Infectious(c) => write!(f, "I({:?})", c),
+            Self::Recovered(c) => write!(f, "R({:?})", c),
+            Self::Dead(c) => write!(f, "D({:?})", c),
+        }
+    }
+}
+
 impl<C: Debug> Debug for SEAIR<C> {
     default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -62,12 +106,29 @@ impl<C: Debug> Debug for SEICHAR<C> {
     }
 }
 
+impl<C: Debug> Debug for SEICHARP<C> {
+    default fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Susceptible => write!(f, "S"),
+            Self::Exposed(c) => write!(f, "E({:?})", c),
+            Self::Infectious(c) => write!(f, "I({:?})", c),
+            Self::Critical(c) => write!(f, "C({:?})", c),
+            Self::Severe(c) => write!(f, "H({:?})", c),
+            Self::Asymptomatic(c) => write!(f, "A({:?})", c),
+            Self::PostAcute(c) => write!(f, "P({:?})", c),
+            Self::Recovered(c) => write!(f, "R({:?})", c),
+            Self::Dead(c) => write!(f, "D({:?})", c),
+        }
+    }
+}
+
 /// Implements a debug
 macro_rules! implDebug {
     (SIR: $ty:ty) => {implDebug!(SIR<$ty> { });};
     (SEIR: $ty:ty) => {implDebug!(SEIR<$ty> { Exposed: "E" });};
     (SEAIR: $ty:ty) => {implDebug!(SEAIR<$ty> { Exposed: "E", Asymptomatic: "A" });};
     (SEICHAR: $ty:ty) => {implDebug!(SEICHAR<$ty> { Exposed: "E", Asymptomatic: "A", Severe: "H", Critical: "C" });};
+    (SEICHARP: $ty:ty) => {implDebug!(SEICHARP<$ty> { Exposed: "E", Asymptomatic: "A", Severe: "H", Critical: "C", PostAcute: "P" });};
 
     ($ty:ty { $($st:ident: $opt:literal),* $(,)? }) => {
         impl Debug for $ty {
@@ -89,29 +150,129 @@ macro_rules! implDebug {
 
 implDebug!(SIR: ());
 implDebug!(SEIR: ());
+implDebug!(SEIRV<()> { Vaccinated: "V", Exposed: "E" });
 implDebug!(SEAIR: ());
 implDebug!(SEICHAR: ());
+implDebug!(SEICHARP: ());
+
+/// Implements Serialize/Deserialize representing each state by its letter in
+/// CSV_HEADER. Clinical information is not stored and is restored to its
+/// default value.
+macro_rules! implSerde {
+    ($name:ident { $($st:ident: $opt:literal),* $(,)? }) => {
+        impl<C: Clone> Serialize for $name<C> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let name = Self::CSV_HEADER.split(',').nth(self.index()).unwrap();
+                serializer.serialize_str(name)
+            }
+        }
+
+        impl<'de, C: Clone + Default> Deserialize<'de> for $name<C> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match String::deserialize(deserializer)?.as_str() {
+                    "S" => Ok(Self::Susceptible),
+                    $(
+                        $opt => Ok(Self::$st(C::default())),
+                    )*
+                    other => Err(D::Error::custom(format!("invalid state: {}", other))),
+                }
+            }
+        }
+    };
+}
+
+implSerde!(SIR {
+    Infectious: "I",
+    Recovered: "R",
+    Dead: "D"
+});
+implSerde!(SIS { Infectious: "I" });
+implSerde!(SEIR {
+    Exposed: "E",
+    Infectious: "I",
+    Recovered: "R",
+    Dead: "D"
+});
+implSerde!(SEIRV {
+    Vaccinated: "V",
+    Exposed: "E",
+    Infectious: "I",
+    Recovered: "R",
+    Dead: "D"
+});
+implSerde!(SEAIR {
+    Exposed: "E",
+    Asymptomatic: "A",
+    Infectious: "I",
+    Recovered: "R",
+    Dead: "D"
+});
+implSerde!(SEICHAR {
+    Exposed: "E",
+    Infectious: "I",
+    Critical: "C",
+    Severe: "H",
+    Asymptomatic: "A",
+    Recovered: "R",
+    Dead: "D",
+});
+implSerde!(SEICHARP {
+    Exposed: "E",
+    Infectious: "I",
+    Critical: "C",
+    Severe: "H",
+    Asymptomatic: "A",
+    PostAcute: "P",
+    Recovered: "R",
+    Dead: "D",
+});
+
+assert_model_indices!(SIR<()>: E, I, R);
+assert_model_indices!(SIS<()>: E, I, R);
+assert_model_indices!(SEIR<()>: E, I, R);
+assert_model_indices!(SEIRV<()>: V, E, I, R);
+assert_model_indices!(SEAIR<()>: E, I, R, C, H, A);
+assert_model_indices!(SEICHAR<()>: E, I, R, C, H, A);
+assert_model_indices!(SEICHARP<()>: E, I, R, C, H, A, P);
 
 /// Type alias for simple SIR model enumeration
 pub type SIRm = SIR<()>;
 
+/// Type alias for simple SIS model enumeration
+pub type SISm = SIS<()>;
+
 /// Type alias for simple SEIR model enumeration
 pub type SEIRm = SEIR<()>;
 
+/// Type alias for simple SEIRV model enumeration
+pub type SEIRVm = SEIRV<()>;
+
 /// Type alias for simple SEAIR model enumeration
 pub type SEAIRm = SEAIR<()>;
 
 /// Type alias for simple SEICHAR model enumeration
 pub type SEICHARm = SEICHAR<()>;
 
+/// Type alias for simple SEICHARP model enumeration
+pub type SEICHARPm = SEICHARP<()>;
+
 /// Type alias for simple SIR agent
 pub type SirAgent<V> = SimpleAgent<SIRm, V>;
 
+/// Type alias for simple SIS agent
+pub type SisAgent<V> = SimpleAgent<SISm, V>;
+
 /// Type alias for simple SIR agent
 pub type SeirAgent<V> = SimpleAgent<SEIRm, V>;
 
+/// Type alias for simple SEIRV agent
+pub type SeirvAgent<V> = SimpleAgent<SEIRVm, V>;
+
 /// Type alias for simple SIR agent
 pub type SeairAgent<V> = SimpleAgent<SEAIRm, V>;
 
 /// Type alias for simple SIR agent
 pub type SeicharAgent<V> = SimpleAgent<SEICHARm, V>;
+
+/// Type alias for simple SEICHARP agent
+pub type SeicharpAgent<V> = SimpleAgent<SEICHARPm, V>;
@@ -3,7 +3,7 @@ use rand::Rng;
 use crate::{
     epidemic::{EpiModel, SEICHARLike, SEIRLike},
     params::EpiParamsLocalT,
-    prelude::Real,
+    prelude::{Real, Time},
     sim::RandomUpdate,
 };
 
@@ -90,11 +90,27 @@ impl<C: Clone> EpiModel for SEAIR<C> {
         }
     }
 
+    fn is_symptomatic(&self) -> bool {
+        self.is_contagious() && !matches!(self, Self::Asymptomatic(_))
+    }
+
     fn is_recovered(&self) -> bool {
         self.index() == Self::R
     }
 
+    fn reinfection_odds(&self) -> Real {
+        match self {
+            Self::Recovered(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Recovered agents keep their own clinical data when re-exposed.
     fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        if let Self::Recovered(c) = self {
+            *self = Self::Exposed(c.clone());
+            return true;
+        }
         other.clinical().map(|c| *self = Self::Exposed(c)).is_some()
     }
 }
@@ -155,4 +171,8 @@ where
             _ => (),
         }
     }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
 }
@@ -3,7 +3,7 @@ use rand::Rng;
 use crate::{
     epidemic::{EpiModel, SEICHARLike, SEIRLike},
     params::EpiParamsLocalT,
-    prelude::Real,
+    prelude::{Real, Time},
     sim::RandomUpdate,
 };
 
@@ -103,11 +103,27 @@ impl<C: Clone> EpiModel for SEICHAR<C> {
         }
     }
 
+    fn is_symptomatic(&self) -> bool {
+        self.is_contagious() && !matches!(self, Self::Asymptomatic(_))
+    }
+
     fn is_recovered(&self) -> bool {
         self.index() == Self::R
     }
 
+    fn reinfection_odds(&self) -> Real {
+        match self {
+            Self::Recovered(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Recovered agents keep their own clinical data when re-exposed.
     fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        if let Self::Recovered(c) = self {
+            *self = Self::Exposed(c.clone());
+            return true;
+        }
         other.clinical().map(|c| *self = Self::Exposed(c)).is_some()
     }
 }
@@ -141,14 +157,19 @@ where
     P: EpiParamsLocalT,
 {
     fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        // Competing exits from a compartment are resolved with a single
+        // categorical draw. Each branch takes its share of the exit
+        // probability p, which is the exact split of competing hazards.
+        let risks = params.competing_risks();
+        let split = |p: Real, branch: Real| [p * branch, p * (1.0 - branch)];
         match self {
             Self::Exposed(c) => {
-                if rng.gen_bool(params.incubation_transition_prob()) {
-                    if rng.gen_bool(params.prob_asymptomatic()) {
-                        *self = Self::Asymptomatic(c.clone())
-                    } else {
-                        *self = Self::Infectious(c.clone())
-                    }
+                let p = params.incubation_transition_prob();
+                let branch = params.prob_asymptomatic();
+                match risks.sample(&split(p, branch), rng) {
+                    Some(0) => *self = Self::Asymptomatic(c.clone()),
+                    Some(_) => *self = Self::Infectious(c.clone()),
+                    None => (),
                 }
             }
             Self::Asymptomatic(c) => {
@@ -157,33 +178,103 @@ where
                 }
             }
             Self::Infectious(c) => {
-                if rng.gen_bool(params.infectious_transition_prob()) {
-                    if rng.gen_bool(params.prob_severe()) {
-                        *self = Self::Severe(c.clone())
-                    } else {
-                        *self = Self::Recovered(c.clone());
-                    }
+                let p = params.infectious_transition_prob();
+                let branch = params.prob_severe();
+                match risks.sample(&split(p, branch), rng) {
+                    Some(0) => *self = Self::Severe(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
                 }
             }
             Self::Severe(c) => {
-                if rng.gen_bool(params.severe_transition_prob()) {
-                    if rng.gen_bool(params.prob_critical()) {
-                        *self = Self::Critical(c.clone())
-                    } else {
-                        *self = Self::Recovered(c.clone());
-                    }
+                let p = params.severe_transition_prob();
+                let branch = params.prob_critical();
+                match risks.sample(&split(p, branch), rng) {
+                    Some(0) => *self = Self::Critical(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
                 }
             }
             Self::Critical(c) => {
-                if rng.gen_bool(params.critical_transition_prob()) {
-                    if rng.gen_bool(params.prob_death()) {
-                        *self = Self::Dead(c.clone());
-                    } else {
-                        *self = Self::Recovered(c.clone());
-                    }
+                let p = params.critical_transition_prob();
+                let base = params.prob_death();
+                let branch = params.healthcare().map_or(base, |h| h.prob_death(base));
+                match risks.sample(&split(p, branch), rng) {
+                    Some(0) => *self = Self::Dead(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
                 }
             }
             _ => (),
         }
     }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params::EpiParamsFull, utils::seeded_rng};
+    use assert_approx_eq::assert_approx_eq;
+
+    fn exposed_outcomes<P: EpiParamsLocalT>(params: &P, n: usize) -> [Real; 3] {
+        let rng = &mut seeded_rng(42u64);
+        let mut counts = [0.0; 3];
+        for _ in 0..n {
+            let mut st: SEICHAR<()> = SEICHAR::Exposed(());
+            st.random_update(params, rng);
+            match st {
+                SEICHAR::Exposed(_) => counts[0] += 1.0 / n as Real,
+                SEICHAR::Asymptomatic(_) => counts[1] += 1.0 / n as Real,
+                SEICHAR::Infectious(_) => counts[2] += 1.0 / n as Real,
+                _ => panic!("invalid transition"),
+            }
+        }
+        return counts;
+    }
+
+    #[test]
+    fn exposed_transitions_match_multinomial() {
+        let mut params: EpiParamsFull<Real> = Default::default();
+        let (p, prob_asymptomatic): (Real, Real) = (0.6, 0.3);
+        params
+            .epidemic
+            .set_incubation_period(-1.0 / (1.0 - p).ln())
+            .set_prob_asymptomatic(prob_asymptomatic);
+
+        let counts = exposed_outcomes(&params, 20_000);
+        let expected = [
+            1.0 - p,
+            p * prob_asymptomatic,
+            p * (1.0 - prob_asymptomatic),
+        ];
+        for (k, e) in counts.iter().zip(expected.iter()) {
+            assert_approx_eq!(k, e, 0.015);
+        }
+    }
+
+    #[test]
+    fn competing_exits_preserve_mean_dwell_time() {
+        let params: EpiParamsFull<Real> = Default::default();
+        let p = params.incubation_transition_prob();
+        let rng = &mut seeded_rng(42u64);
+
+        let n = 20_000;
+        let mut steps = 0;
+        for _ in 0..n {
+            let mut st: SEICHAR<()> = SEICHAR::Exposed(());
+            while let SEICHAR::Exposed(_) = st {
+                st.random_update(&params, rng);
+                steps += 1;
+            }
+        }
+        assert_approx_eq!(steps as Real / n as Real, 1.0 / p, 0.05 / p);
+    }
 }
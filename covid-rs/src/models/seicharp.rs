@@ -0,0 +1,321 @@
+use rand::Rng;
+
+use crate::{
+    epidemic::{EpiModel, SEICHARLike, SEIRLike},
+    params::EpiParamsLocalT,
+    prelude::{Real, Time},
+    sim::RandomUpdate,
+};
+
+/// A SEICHAR variant in which survivors of critical (and optionally severe)
+/// cases may go through a PostAcute state of long-term sequelae before full
+/// recovery.
+///
+/// The PostAcute state is not contagious and is tracked separately from
+/// Recovered.
+#[derive(Clone, Copy, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub enum SEICHARP<C> {
+    #[default]
+    Susceptible,
+    Exposed(C),
+    Infectious(C),
+    Critical(C),
+    Severe(C),
+    Asymptomatic(C),
+    PostAcute(C),
+    Recovered(C),
+    Dead(C),
+}
+
+impl<C> SEICHARP<C> {
+    /// Index of the PostAcute state.
+    pub const P: usize = 6;
+
+    pub fn clinical(&self) -> Option<C>
+    where
+        C: Clone,
+    {
+        match self {
+            Self::Susceptible => None,
+            Self::Exposed(c)
+            | Self::Infectious(c)
+            | Self::Critical(c)
+            | Self::Severe(c)
+            | Self::Asymptomatic(c)
+            | Self::PostAcute(c)
+            | Self::Recovered(c)
+            | Self::Dead(c) => Some(c.clone()),
+        }
+    }
+
+    /// Return true if agent is in the `PostAcute` state.
+    pub fn is_post_acute(&self) -> bool {
+        matches!(self, Self::PostAcute(_))
+    }
+}
+
+impl<C: Clone> EpiModel for SEICHARP<C> {
+    const CARDINALITY: usize = 9;
+    const CSV_HEADER: &'static str = "S,E,I,C,H,A,P,R,D";
+    const S: usize = 0;
+    const D: usize = 8;
+
+    type Disease = ();
+    type Clinical = C;
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Susceptible => Self::S,
+            Self::Exposed(_) => Self::E,
+            Self::Infectious(_) => Self::I,
+            Self::Critical(_) => Self::C,
+            Self::Severe(_) => Self::H,
+            Self::Asymptomatic(_) => Self::A,
+            Self::PostAcute(_) => Self::P,
+            Self::Recovered(_) => Self::R,
+            Self::Dead(_) => Self::D,
+        }
+    }
+
+    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+        Self::Infectious(clinical.clone())
+    }
+
+    fn force_infectious(&mut self, force_dead: bool) -> bool {
+        match self {
+            Self::Susceptible => false,
+            Self::Exposed(c)
+            | Self::Critical(c)
+            | Self::Severe(c)
+            | Self::Asymptomatic(c)
+            | Self::Infectious(c)
+            | Self::PostAcute(c)
+            | Self::Recovered(c) => {
+                *self = Self::Infectious(c.clone());
+                return true;
+            }
+            Self::Dead(c) => {
+                if force_dead {
+                    *self = Self::Infectious(c.clone());
+                    return true;
+                }
+                return false;
+            }
+        }
+    }
+
+    fn contagion_odds(&self) -> Real {
+        match self {
+            Self::Infectious(_) => 1.0,
+            Self::Asymptomatic(_) => 0.42,
+            Self::Severe(_) => 0.1,
+            Self::Critical(_) => 0.1,
+            _ => 0.0,
+        }
+    }
+
+    fn is_symptomatic(&self) -> bool {
+        self.is_contagious() && !matches!(self, Self::Asymptomatic(_))
+    }
+
+    fn is_recovered(&self) -> bool {
+        self.index() == Self::R
+    }
+
+    fn reinfection_odds(&self) -> Real {
+        match self {
+            Self::Recovered(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Recovered agents keep their own clinical data when re-exposed.
+    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        if let Self::Recovered(c) = self {
+            *self = Self::Exposed(c.clone());
+            return true;
+        }
+        other.clinical().map(|c| *self = Self::Exposed(c)).is_some()
+    }
+}
+
+impl<C: Clone> SEIRLike for SEICHARP<C> {
+    const E: usize = 1;
+    const I: usize = 2;
+    const R: usize = 7;
+
+    fn is_exposed(&self) -> bool {
+        self.index() == Self::E
+    }
+
+    fn expose(&mut self, with: &Self::Clinical) {
+        *self = Self::Exposed(with.clone())
+    }
+
+    fn infect(&mut self, with: &Self::Clinical) {
+        *self = Self::Infectious(with.clone())
+    }
+}
+
+impl<C: Clone> SEICHARLike for SEICHARP<C> {
+    const C: usize = 3;
+    const H: usize = 4;
+    const A: usize = 5;
+}
+
+impl<C: Clone, P> RandomUpdate<P> for SEICHARP<C>
+where
+    P: EpiParamsLocalT,
+{
+    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        // Like in SEICHAR, competing exits split the exit probability p in
+        // proportion to each branch and are resolved with a single draw.
+        let risks = params.competing_risks();
+        let split =
+            |p: Real, branches: &[Real]| -> Vec<Real> { branches.iter().map(|b| p * b).collect() };
+        match self {
+            Self::Exposed(c) => {
+                let p = params.incubation_transition_prob();
+                let branch = params.prob_asymptomatic();
+                match risks.sample(&split(p, &[branch, 1.0 - branch]), rng) {
+                    Some(0) => *self = Self::Asymptomatic(c.clone()),
+                    Some(_) => *self = Self::Infectious(c.clone()),
+                    None => (),
+                }
+            }
+            Self::Asymptomatic(c) => {
+                if rng.gen_bool(params.infectious_transition_prob()) {
+                    *self = Self::Recovered(c.clone())
+                }
+            }
+            Self::Infectious(c) => {
+                let p = params.infectious_transition_prob();
+                let branch = params.prob_severe();
+                match risks.sample(&split(p, &[branch, 1.0 - branch]), rng) {
+                    Some(0) => *self = Self::Severe(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
+                }
+            }
+            Self::Severe(c) => {
+                let p = params.severe_transition_prob();
+                let critical = params.prob_critical();
+                let post_acute = (1.0 - critical) * params.prob_post_acute_severe();
+                let branches = [critical, post_acute, 1.0 - critical - post_acute];
+                match risks.sample(&split(p, &branches), rng) {
+                    Some(0) => *self = Self::Critical(c.clone()),
+                    Some(1) => *self = Self::PostAcute(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
+                }
+            }
+            Self::Critical(c) => {
+                let p = params.critical_transition_prob();
+                let base = params.prob_death();
+                let death = params.healthcare().map_or(base, |h| h.prob_death(base));
+                let post_acute = (1.0 - death) * params.prob_post_acute();
+                let branches = [death, post_acute, 1.0 - death - post_acute];
+                match risks.sample(&split(p, &branches), rng) {
+                    Some(0) => *self = Self::Dead(c.clone()),
+                    Some(1) => *self = Self::PostAcute(c.clone()),
+                    Some(_) => *self = Self::Recovered(c.clone()),
+                    None => (),
+                }
+            }
+            Self::PostAcute(c) if rng.gen_bool(params.post_acute_transition_prob()) => {
+                *self = Self::Recovered(c.clone());
+            }
+            _ => (),
+        }
+    }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{epidemic::HealthcareCapacity, params::EpiParamsFull, utils::seeded_rng};
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn critical_survivors_enter_post_acute() {
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.clinical.set_prob_post_acute(1.0);
+        params.clinical.set_post_acute_period(30.0);
+        let rng = &mut seeded_rng(42u64);
+
+        let (mut survivors, mut post_acute) = (0, 0);
+        for _ in 0..1000 {
+            let mut st = SEICHARP::Critical(());
+            while st.index() == SEICHARP::<()>::C {
+                st.random_update(&params, rng);
+            }
+            survivors += !st.is_dead() as usize;
+            post_acute += st.is_post_acute() as usize;
+        }
+        assert!(survivors > 0);
+        assert_eq!(post_acute, survivors);
+    }
+
+    #[test]
+    fn severe_and_critical_exits_preserve_mean_periods() {
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.clinical.set_prob_post_acute(0.5);
+        params.clinical.set_prob_post_acute_severe(0.5);
+        let rng = &mut seeded_rng(42u64);
+
+        let n = 20_000;
+        for (start, p) in [
+            (SEICHARP::Severe(()), params.severe_transition_prob()),
+            (SEICHARP::Critical(()), params.critical_transition_prob()),
+        ] {
+            let mut steps = 0;
+            for _ in 0..n {
+                let mut st = start.clone();
+                while st.index() == start.index() {
+                    st.random_update(&params, rng);
+                    steps += 1;
+                }
+            }
+            assert_approx_eq!(steps as Real / n as Real, 1.0 / p, 0.05 / p);
+        }
+    }
+
+    #[test]
+    fn collapsed_icus_increase_deaths() {
+        let params: EpiParamsFull<Real> = Default::default();
+        let deaths = |healthcare: Option<HealthcareCapacity>| {
+            let mut params = params.cached();
+            params.set_healthcare(healthcare);
+            let rng = &mut seeded_rng(42u64);
+            let mut deaths = 0;
+            for _ in 0..1000 {
+                let mut st = SEICHARP::Critical(());
+                while st.index() == SEICHARP::<()>::C {
+                    st.random_update(&params, rng);
+                }
+                deaths += st.is_dead() as usize;
+            }
+            deaths
+        };
+        let collapsed = HealthcareCapacity::new(0, 0);
+        assert!(deaths(Some(collapsed)) > deaths(None));
+    }
+
+    #[test]
+    fn recovered_agents_can_be_reinfected() {
+        let mut st = SEICHARP::Recovered(1);
+        assert_eq!(st.reinfection_odds(), 1.0);
+        assert_eq!(SEICHARP::PostAcute(1).reinfection_odds(), 0.0);
+        assert!(st.transfer_contamination_from(&SEICHARP::Infectious(2)));
+        assert_eq!(st.clinical(), Some(1));
+        assert!(st.is_exposed());
+    }
+}
@@ -3,8 +3,9 @@ use rand::Rng;
 use crate::{
     epidemic::{EpiModel, SEIRLike},
     params::EpiParamsLocalT,
-    prelude::Real,
+    prelude::{Real, Time},
     sim::RandomUpdate,
+    utils::gamma,
 };
 
 /// Enumeration used internally to distinguish Exposed from Infectious in SEIR.
@@ -73,7 +74,7 @@ impl<C: Clone> EpiModel for SEIR<C> {
         }
     }
 
-    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+    default fn new_infectious_with(clinical: &Self::Clinical) -> Self {
         Self::Infectious(clinical.clone())
     }
 
@@ -88,7 +89,19 @@ impl<C: Clone> EpiModel for SEIR<C> {
         self.index() == Self::R
     }
 
-    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+    fn reinfection_odds(&self) -> Real {
+        match self {
+            Self::Recovered(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Recovered agents keep their own clinical data when re-exposed.
+    default fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        if let Self::Recovered(c) = self {
+            *self = Self::Exposed(c.clone());
+            return true;
+        }
         other.clinical().map(|c| *self = Self::Exposed(c)).is_some()
     }
 }
@@ -102,11 +115,11 @@ impl<C: Clone> SEIRLike for SEIR<C> {
         self.index() == Self::E
     }
 
-    fn expose(&mut self, with: &Self::Clinical) {
+    default fn expose(&mut self, with: &Self::Clinical) {
         *self = Self::Exposed(with.clone())
     }
 
-    fn infect(&mut self, with: &Self::Clinical) {
+    default fn infect(&mut self, with: &Self::Clinical) {
         *self = Self::Infectious(with.clone())
     }
 }
@@ -115,7 +128,7 @@ impl<C: Clone, P> RandomUpdate<P> for SEIR<C>
 where
     P: EpiParamsLocalT,
 {
-    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+    default fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
         match self {
             Self::Exposed(c) => {
                 if rng.gen_bool(params.incubation_transition_prob()) {
@@ -134,4 +147,151 @@ where
             _ => (),
         }
     }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+/// Clinical data that stores the remaining days of the infectious period of
+/// an agent.
+///
+/// SEIR<ClinicalDuration> samples the infectious period of each agent from a
+/// Gamma distribution with mean infectious_period and shape
+/// infectious_period_shape, rounded to whole days, when it leaves the exposed
+/// compartment. The agent then recovers or dies after exactly that number of
+/// updates, instead of flipping a coin with a fixed transition probability.
+///
+/// Agents created with new_infectious_with() or infect() take the duration of
+/// the given clinical template. Durations are never inherited in contagion:
+/// contaminated agents are exposed and sample their own duration. Templates
+/// without a duration (e.g., the default) are sampled at the first update.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClinicalDuration {
+    remaining: Option<Real>,
+}
+
+impl ClinicalDuration {
+    /// Create clinical data with the given number of remaining days.
+    pub fn new(remaining: Real) -> Self {
+        ClinicalDuration {
+            remaining: Some(remaining),
+        }
+    }
+
+    /// Remaining days in the infectious compartment, if already sampled.
+    pub fn remaining(&self) -> Option<Real> {
+        self.remaining
+    }
+
+    /// Sample a new duration from params. Durations are rounded to whole days
+    /// and are at least one day long.
+    pub fn sample<P: EpiParamsLocalT, R: Rng>(params: &P, rng: &mut R) -> Self {
+        let shape = params.infectious_period_shape();
+        let scale = params.infectious_period() / shape;
+        return Self::new(gamma(shape, scale, rng).round().max(1.0));
+    }
+}
+
+impl EpiModel for SEIR<ClinicalDuration> {
+    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+        Self::Infectious(*clinical)
+    }
+
+    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        if self.is_recovered() || other.clinical().is_some() {
+            *self = Self::Exposed(ClinicalDuration::default());
+            return true;
+        }
+        return false;
+    }
+}
+
+impl SEIRLike for SEIR<ClinicalDuration> {
+    fn expose(&mut self, _with: &Self::Clinical) {
+        *self = Self::Exposed(ClinicalDuration::default())
+    }
+
+    fn infect(&mut self, with: &Self::Clinical) {
+        *self = Self::Infectious(*with)
+    }
+}
+
+impl<P> RandomUpdate<P> for SEIR<ClinicalDuration>
+where
+    P: EpiParamsLocalT,
+{
+    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        match self {
+            Self::Exposed(_) => {
+                if rng.gen_bool(params.incubation_transition_prob()) {
+                    *self = Self::Infectious(ClinicalDuration::sample(params, rng))
+                }
+            }
+            Self::Infectious(c) => {
+                let remaining = match c.remaining {
+                    Some(days) => days,
+                    None => ClinicalDuration::sample(params, rng).remaining.unwrap(),
+                };
+                if remaining > 1.0 {
+                    c.remaining = Some(remaining - 1.0);
+                } else if rng.gen_bool(params.infection_fatality_ratio()) {
+                    *self = Self::Dead(ClinicalDuration::default());
+                } else {
+                    *self = Self::Recovered(ClinicalDuration::default());
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params::EpiParamsFull, utils::seeded_rng};
+
+    #[test]
+    fn clinical_duration_is_sampled_and_decremented() {
+        let template = ClinicalDuration::new(3.0);
+        let mut st = SEIR::new_infectious_with(&template);
+        assert_eq!(st.clinical(), Some(template));
+
+        let mut exposed = SEIR::Susceptible;
+        assert!(exposed.contaminate_from(&SEIR::Infectious(template)));
+        assert_eq!(exposed, SEIR::Exposed(ClinicalDuration::default()));
+
+        let mut params = EpiParamsFull::<Real>::default();
+        params.epidemic.set_infectious_period(5.0);
+        params.epidemic.set_case_fatality_ratio(0.0);
+        let mut params = params.cached();
+        params.set_infectious_period_shape(1e4);
+
+        let rng = &mut seeded_rng(42u64);
+        st.random_update(&params, rng);
+        assert_eq!(st.clinical().unwrap().remaining(), Some(2.0));
+        st.random_update(&params, rng);
+        st.random_update(&params, rng);
+        assert_eq!(st, SEIR::Recovered(ClinicalDuration::default()));
+
+        let sampled = ClinicalDuration::sample(&params, rng);
+        assert_eq!(sampled.remaining(), Some(5.0));
+
+        for template in [ClinicalDuration::default(), sampled] {
+            for _ in 0..100 {
+                let mut st = SEIR::new_infectious_with(&template);
+                let mut days = 0;
+                while st.is_infectious() {
+                    st.random_update(&params, rng);
+                    days += 1;
+                }
+                assert_eq!(st, SEIR::Recovered(ClinicalDuration::default()));
+                assert_eq!(days, 5);
+            }
+        }
+    }
 }
@@ -0,0 +1,217 @@
+use rand::Rng;
+
+use crate::{
+    epidemic::{EpiModel, SEIRLike, SEIRVLike},
+    params::EpiParamsLocalT,
+    prelude::{Real, Time},
+    sim::RandomUpdate,
+};
+
+/// SEIR model with an explicit Vaccinated compartment, so vaccinated agents
+/// appear as a separate column in epicurves.
+///
+/// Vaccinated agents are still susceptible, but with a leaky vaccine their
+/// susceptibility is reduced to LEAK percent of that of an unvaccinated agent.
+/// Simulation uses susceptibility() to decide whether a contact with a
+/// vaccinated agent results in infection.
+#[derive(Clone, Copy, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub enum SEIRV<C, const LEAK: u8 = 30> {
+    #[default]
+    Susceptible,
+    Vaccinated(C),
+    Exposed(C),
+    Infectious(C),
+    Recovered(C),
+    Dead(C),
+}
+
+impl<C, const LEAK: u8> SEIRV<C, LEAK> {
+    pub fn clinical(&self) -> Option<C>
+    where
+        C: Clone,
+    {
+        match self {
+            Self::Susceptible | Self::Vaccinated(_) => None,
+            Self::Exposed(c) | Self::Infectious(c) | Self::Recovered(c) | Self::Dead(c) => {
+                Some(c.clone())
+            }
+        }
+    }
+
+    /// Relative susceptibility of vaccinated agents.
+    pub fn leak() -> Real {
+        LEAK as Real / 100.0
+    }
+}
+
+impl<C: Clone, const LEAK: u8> EpiModel for SEIRV<C, LEAK> {
+    const CARDINALITY: usize = 6;
+    const CSV_HEADER: &'static str = "S,V,E,I,R,D";
+    const S: usize = 0;
+    const D: usize = 5;
+
+    type Disease = ();
+    type Clinical = C;
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Susceptible => Self::S,
+            Self::Vaccinated(_) => Self::V,
+            Self::Exposed(_) => Self::E,
+            Self::Infectious(_) => Self::I,
+            Self::Recovered(_) => Self::R,
+            Self::Dead(_) => Self::D,
+        }
+    }
+
+    fn force_infectious(&mut self, force_dead: bool) -> bool {
+        match self {
+            Self::Susceptible | Self::Vaccinated(_) => false,
+            Self::Exposed(c) | Self::Infectious(c) | Self::Recovered(c) => {
+                *self = Self::Infectious(c.clone());
+                return true;
+            }
+            Self::Dead(c) => {
+                if force_dead {
+                    *self = Self::Infectious(c.clone());
+                    return true;
+                }
+                return false;
+            }
+        }
+    }
+
+    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+        Self::Infectious(clinical.clone())
+    }
+
+    fn contagion_odds(&self) -> Real {
+        match self {
+            Self::Infectious(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn is_susceptible(&self) -> bool {
+        matches!(self, Self::Susceptible | Self::Vaccinated(_))
+    }
+
+    fn susceptibility(&self) -> Real {
+        match self {
+            Self::Susceptible => 1.0,
+            Self::Vaccinated(_) => Self::leak(),
+            _ => 0.0,
+        }
+    }
+
+    fn is_recovered(&self) -> bool {
+        self.index() == Self::R
+    }
+
+    /// Expose self. Vaccinated agents resist infection with probability
+    /// 1 - leak(), which is resolved by the caller using susceptibility().
+    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        other.clinical().map(|c| *self = Self::Exposed(c)).is_some()
+    }
+}
+
+impl<C: Clone, const LEAK: u8> SEIRLike for SEIRV<C, LEAK> {
+    const E: usize = 2;
+    const I: usize = 3;
+    const R: usize = 4;
+
+    fn is_exposed(&self) -> bool {
+        self.index() == Self::E
+    }
+
+    fn expose(&mut self, with: &Self::Clinical) {
+        *self = Self::Exposed(with.clone())
+    }
+
+    fn infect(&mut self, with: &Self::Clinical) {
+        *self = Self::Infectious(with.clone())
+    }
+}
+
+impl<C: Clone, const LEAK: u8> SEIRVLike for SEIRV<C, LEAK> {
+    const V: usize = 1;
+
+    fn vaccinate(&mut self, with: &Self::Clinical) -> bool {
+        if let Self::Susceptible = self {
+            *self = Self::Vaccinated(with.clone());
+            return true;
+        }
+        return false;
+    }
+}
+
+impl<C: Clone, P, const LEAK: u8> RandomUpdate<P> for SEIRV<C, LEAK>
+where
+    P: EpiParamsLocalT,
+{
+    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        match self {
+            Self::Exposed(c) if rng.gen_bool(params.incubation_transition_prob()) => {
+                *self = Self::Infectious(c.clone())
+            }
+            Self::Infectious(c) if rng.gen_bool(params.infectious_transition_prob()) => {
+                if rng.gen_bool(params.infection_fatality_ratio()) {
+                    *self = Self::Dead(c.clone());
+                } else {
+                    *self = Self::Recovered(c.clone());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        epidemic::EpiModelPopulationExt, models::SEIRVm, params::EpiParamsLocal, sim::Simulation,
+        utils::seeded_rng,
+    };
+
+    #[test]
+    fn vaccinated_agents_are_susceptible_with_reduced_odds() {
+        let mut st = SEIRVm::default();
+        assert!(st.vaccinate(&()));
+        assert!(st.is_vaccinated() && st.is_susceptible() && !st.is_contaminated());
+        assert_eq!(st.susceptibility(), 0.3);
+        assert!(!st.vaccinate(&()));
+        assert_eq!(SEIRV::<(), 10>::Vaccinated(()).susceptibility(), 0.1);
+
+        assert!(st.contaminate_from(&SEIRV::Infectious(())));
+        assert!(st.is_exposed() && !st.is_susceptible());
+        assert_eq!(SEIRVm::CSV_HEADER.split(',').nth(SEIRVm::V), Some("V"));
+    }
+
+    #[test]
+    fn vaccinated_compartment_is_tracked_in_epicurves() {
+        let params = EpiParamsLocal::default();
+        let mut population = vec![SEIRVm::default(); 2000];
+        for st in population.iter_mut().step_by(2) {
+            st.vaccinate(&());
+        }
+        let mut sim = Simulation::new_simple(params, population, 8.0, 0.2);
+        sim.seed(42);
+        sim.contaminate_at_random(20, &mut seeded_rng(42u64));
+        sim.run(100);
+
+        let [s, v, e, i, r, n] = sim.population().count_seirv();
+        assert_eq!(s + v + e + i + r + sim.population().n_dead(), n);
+        assert!(v > 2 * s, "vaccinated: {}, susceptible: {}", v, s);
+        assert_eq!(sim.get_epistate(0, false).unwrap()[SEIRVm::V], 1000.0);
+        assert_eq!(sim.get_epicurve(SEIRVm::V, false).unwrap()[100], v as Real);
+    }
+}
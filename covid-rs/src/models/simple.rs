@@ -1,14 +1,18 @@
 use getset::{CopyGetters, Getters};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use super::{SEAIRm, SEICHARPm, SEICHARm, SEIRm, SIRm, SISm, SEIR, SIR};
 use crate::{
-    epidemic::EpiModel,
+    epidemic::{EpiModel, Variant},
     prelude::{Age, Real, Time},
     sim::{HasAge, HasEpiModel, Population, RandomUpdate},
 };
 
 /// A simple agent with an age, epidemic model and vaccine model.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Getters, CopyGetters)]
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Default, Getters, CopyGetters, Serialize, Deserialize,
+)]
 pub struct SimpleAgent<M, V> {
     age: Age,
     #[getset(get = "pub")]
@@ -19,6 +23,15 @@ pub struct SimpleAgent<M, V> {
     vaccine: V,
     #[getset(get_copy = "pub")]
     vaccine_t: Time,
+    /// Days since the last transition of the epidemic model. It is kept at
+    /// zero while agent is susceptible, since infections happen outside
+    /// random_update().
+    #[getset(get_copy = "pub")]
+    #[serde(default)]
+    state_t: Time,
+    /// Isolated agents do not take part in contacts. See EpiModel::isolate().
+    #[serde(default)]
+    isolated: bool,
 }
 
 impl<M, V: Clone> SimpleAgent<M, V> {
@@ -53,22 +66,189 @@ impl<M: EpiModel, V> HasEpiModel for SimpleAgent<M, V> {
 
     fn set_epimodel(&mut self, value: Self::Model) -> &mut Self {
         self.epimodel = value;
+        self.state_t = 0;
         return self;
     }
+
+    fn isolated(&self) -> bool {
+        self.isolated
+    }
+
+    fn set_isolated(&mut self, value: bool) -> bool {
+        self.isolated = value;
+        return true;
+    }
+}
+
+/// A vaccine that reduces the probability of infection in each contact by
+/// the given efficacy (the default, with zero efficacy, means no vaccine).
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct LeakyVaccine {
+    pub efficacy: Real,
+}
+
+// Specialization cannot be generic over the epidemic model, so we implement
+// it for each concrete model.
+macro_rules! impl_leaky_vaccine {
+    ($($model:ty),*) => {
+        $(
+            impl EpiModel for SimpleAgent<$model, LeakyVaccine> {
+                fn susceptibility(&self) -> Real {
+                    self.epimodel.susceptibility() * (1.0 - self.vaccine.efficacy)
+                }
+            }
+        )*
+    };
+}
+
+impl_leaky_vaccine!(SIRm, SISm, SEIRm, SEAIRm, SEICHARm, SEICHARPm);
+
+/// Pre-existing immunity that reduces the probability of infection in
+/// contacts with agents carrying the given strain by the given efficacy.
+/// Contacts with other strains are not affected.
+///
+/// Strains are stored as the clinical data of models like SEIR<Variant>. The
+/// default, with zero efficacy, means no cross-immunity.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct CrossImmunity {
+    pub against: Variant,
+    pub efficacy: Real,
+}
+
+macro_rules! impl_cross_immunity {
+    ($($model:ty),*) => {
+        $(
+            impl EpiModel for SimpleAgent<$model, CrossImmunity> {
+                fn susceptibility_to(&self, other: &Self) -> Real {
+                    let susceptibility = self.epimodel.susceptibility();
+                    match other.epimodel.clinical() {
+                        Some(strain) if strain == self.vaccine.against => {
+                            susceptibility * (1.0 - self.vaccine.efficacy)
+                        }
+                        _ => susceptibility,
+                    }
+                }
+            }
+        )*
+    };
 }
 
+impl_cross_immunity!(SIR<Variant>, SEIR<Variant>);
+
 impl<M, V, W> RandomUpdate<W> for SimpleAgent<M, V>
 where
     Self: HasEpiModel<Model = M>,
     M: RandomUpdate<W> + EpiModel,
 {
+    /// Contagious agents do not leave their state before state_t reaches
+    /// min_infectious_days.
     default fn random_update<R: Rng>(&mut self, world: &W, rng: &mut R) {
-        self.epimodel_random_update(world, rng);
+        let index = self.epimodel.index();
+        if !self.epimodel.is_contagious() || self.state_t >= M::min_infectious_days(world) {
+            self.epimodel_random_update(world, rng);
+        }
+        if self.epimodel.is_susceptible() || self.epimodel.index() != index {
+            self.state_t = 0;
+        } else {
+            self.state_t += 1;
+        }
         self.epimodel_t += 1;
         self.vaccine_t += 1;
     }
 }
 
+/// Order in which boosters are distributed when there are more eligible
+/// agents than available doses.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BoosterPriority {
+    /// Oldest agents first.
+    Oldest,
+    /// Agents with the longest time since their last dose first.
+    LongestSinceDose,
+}
+
+/// Re-vaccinate agents whose last dose is older than interval_days, using at
+/// most daily_doses each time the policy is applied.
+///
+/// Agents are re-vaccinated with the vaccine they already have, which resets
+/// their vaccine_t counter. Agents with the default (i.e., no) vaccine are
+/// never eligible.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoosterPolicy {
+    pub interval_days: Time,
+    pub priority: BoosterPriority,
+    pub daily_doses: usize,
+}
+
+/// Vaccinate agents over time with a limited supply of doses.
+///
+/// Each time the campaign is applied, at most doses_per_day agents that do not
+/// have the campaign vaccine yet are vaccinated, in decreasing order of the
+/// priority function, e.g., |ag| ag.age() vaccinates the oldest agents first.
+/// Dead agents are skipped. The campaign stops when total_supply doses are used
+/// or when all living agents are vaccinated.
+#[derive(Debug, Clone, Getters, CopyGetters)]
+pub struct VaccinationCampaign<V, F> {
+    #[getset(get = "pub")]
+    vaccine: V,
+    #[getset(get_copy = "pub")]
+    doses_per_day: usize,
+    #[getset(get_copy = "pub")]
+    total_supply: usize,
+    #[getset(get_copy = "pub")]
+    doses_used: usize,
+    #[getset(get = "pub")]
+    priority: F,
+}
+
+impl<V, F> VaccinationCampaign<V, F> {
+    pub fn new(vaccine: V, doses_per_day: usize, total_supply: usize, priority: F) -> Self {
+        VaccinationCampaign {
+            vaccine,
+            doses_per_day,
+            total_supply,
+            doses_used: 0,
+            priority,
+        }
+    }
+
+    /// Number of doses still available.
+    pub fn remaining_supply(&self) -> usize {
+        self.total_supply.saturating_sub(self.doses_used)
+    }
+
+    /// Vaccinate agents for a single day and return the number of doses used.
+    pub fn apply<M, C, P>(&mut self, population: &mut P) -> usize
+    where
+        P: SimpleAgentPopulationExt<M, V>,
+        M: EpiModel,
+        V: Clone + PartialEq,
+        F: FnMut(&SimpleAgent<M, V>) -> C,
+        C: Ord,
+    {
+        let n = self.doses_per_day.min(self.remaining_supply());
+        if n == 0 {
+            return 0;
+        }
+
+        let (vaccine, priority) = (&self.vaccine, &mut self.priority);
+        let mut keys = vec![];
+        population.each_agent(&mut |i, ag: &SimpleAgent<M, V>| {
+            if ag.vaccine != *vaccine && !ag.epimodel.is_dead() {
+                keys.push((priority(ag), i));
+            }
+        });
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        keys.truncate(n);
+
+        for &(_, id) in keys.iter() {
+            population.get_agent_mut(id).unwrap().vaccinate(vaccine);
+        }
+        self.doses_used += keys.len();
+        return keys.len();
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Extend population with trait
 ///////////////////////////////////////////////////////////////////////////////
@@ -122,12 +302,58 @@ where
         return self;
     }
 
+    /// Apply booster policy once and return the number of doses used.
+    fn apply_booster_policy(&mut self, policy: &BoosterPolicy) -> usize
+    where
+        V: Default + PartialEq,
+    {
+        let none = V::default();
+        let mut keys = vec![];
+        self.each_agent(&mut |i, ag: &Self::State| {
+            if ag.vaccine != none && ag.vaccine_t > policy.interval_days {
+                let key = match policy.priority {
+                    BoosterPriority::Oldest => ag.age as Time,
+                    BoosterPriority::LongestSinceDose => ag.vaccine_t,
+                };
+                keys.push((key, i));
+            }
+        });
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+
+        let n = keys.len().min(policy.daily_doses);
+        for &(_, id) in keys[..n].iter() {
+            let ag = self.get_agent_mut(id).unwrap();
+            ag.vaccine_t = 0;
+        }
+        return n;
+    }
+
     /// Vaccinate all individuals with the given vaccine and uniform
     /// probability
     fn vaccinate_random<R: Rng>(&mut self, value: V, prob: Real, rng: &mut R) -> &mut Self {
         self.vaccinate_if(value, |_| rng.gen_bool(prob))
     }
 
+    /// Make each agent cross-immune against the given strain with probability
+    /// fraction. Cross-immunity is stored as the vaccine of agents, so it
+    /// replaces any previous vaccine.
+    fn set_cross_immune_fraction<R: Rng>(
+        &mut self,
+        fraction: Real,
+        against_strain: Variant,
+        efficacy: Real,
+        rng: &mut R,
+    ) -> &mut Self
+    where
+        V: From<CrossImmunity>,
+    {
+        let immunity = CrossImmunity {
+            against: against_strain,
+            efficacy,
+        };
+        self.vaccinate_random(immunity.into(), fraction, rng)
+    }
+
     /// Vaccinate all individuals older than the given age.
     fn vaccinate_elderly_random(
         &mut self,
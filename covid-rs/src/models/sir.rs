@@ -1,7 +1,7 @@
 use crate::{
     epidemic::{EpiModel, SEIRLike},
     params::EpiParamsLocalT,
-    prelude::Real,
+    prelude::{Real, Time},
     sim::RandomUpdate,
 };
 use rand::Rng;
@@ -125,4 +125,8 @@ where
             _ => (),
         }
     }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
 }
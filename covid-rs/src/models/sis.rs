@@ -0,0 +1,150 @@
+use crate::{
+    epidemic::{EpiModel, SEIRLike, ABSENT},
+    params::EpiParamsLocalT,
+    prelude::{Real, Time},
+    sim::RandomUpdate,
+};
+use rand::Rng;
+
+/// Concrete implementation of the SIS model, for infections that do not confer
+/// immunity. Infectious agents return directly to the susceptible state and
+/// there is no Recovered or Dead compartment.
+///
+/// The R and D indices are ABSENT. As in SIR, there is no Exposed state and E
+/// is an alias to I.
+#[derive(Clone, Copy, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub enum SIS<C> {
+    #[default]
+    Susceptible,
+    Infectious(C),
+}
+
+impl<C> SIS<C> {
+    pub fn clinical(&self) -> Option<C>
+    where
+        C: Clone,
+    {
+        match self {
+            Self::Susceptible => None,
+            Self::Infectious(c) => Some(c.clone()),
+        }
+    }
+}
+
+impl<C: Clone> EpiModel for SIS<C> {
+    const CARDINALITY: usize = 2;
+    const CSV_HEADER: &'static str = "S,I";
+    const S: usize = 0;
+    const D: usize = ABSENT;
+    type Disease = ();
+    type Clinical = C;
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Susceptible => Self::S,
+            Self::Infectious(_) => Self::I,
+        }
+    }
+
+    fn force_infectious(&mut self, _force_dead: bool) -> bool {
+        self.is_infectious()
+    }
+
+    fn is_recovered(&self) -> bool {
+        false
+    }
+
+    fn is_dead(&self) -> bool {
+        false
+    }
+
+    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+        Self::Infectious(clinical.clone())
+    }
+
+    fn contagion_odds(&self) -> Real {
+        match self {
+            Self::Infectious(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        other
+            .clinical()
+            .map(|c| *self = Self::Infectious(c))
+            .is_some()
+    }
+}
+
+impl<C: Clone> SEIRLike for SIS<C> {
+    const E: usize = 1;
+    const I: usize = 1;
+    const R: usize = ABSENT;
+
+    fn is_exposed(&self) -> bool {
+        self.is_infectious()
+    }
+
+    fn infect(&mut self, with: &Self::Clinical) {
+        *self = Self::Infectious(with.clone())
+    }
+}
+
+impl<C: Clone, P> RandomUpdate<P> for SIS<C>
+where
+    P: EpiParamsLocalT,
+{
+    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        if self.is_infectious() && rng.gen_bool(params.infectious_transition_prob()) {
+            *self = Self::Susceptible;
+        }
+    }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        epidemic::EpiModelPopulationExt,
+        models::SISm,
+        params::EpiParamsLocal,
+        sim::Simulation,
+        utils::seeded_rng,
+    };
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn sis_reaches_endemic_equilibrium() {
+        let params = EpiParamsLocal::default();
+        let p = params.infectious_transition_prob();
+        let prob = 0.1;
+        // Daily infections per infectious agent that result in R0 = 2
+        let contacts = 2.0 * p / (prob * (1.0 - p));
+        let population = vec![SISm::default(); 10_000];
+        let mut sim = Simulation::new_simple(params, population, contacts, prob);
+        sim.seed(42);
+        sim.contaminate_at_random(100, &mut seeded_rng(42u64));
+        sim.run(300);
+
+        let epistate = sim.epistate(true);
+        assert_eq!(epistate.len(), 2);
+        assert_approx_eq!(epistate[SISm::I], 0.5, 0.1);
+        assert_eq!(sim.population().n_recovered(), 0);
+        assert_eq!(sim.population().n_dead(), 0);
+        assert!(sim.render_epicurve_csv("S,I").lines().count() > 300);
+
+        let summary = sim.summary();
+        assert_eq!(summary.deaths, 0);
+        assert_eq!(summary.peaks.len(), 2);
+        assert!(summary.peaks[SISm::I] > 0);
+    }
+}
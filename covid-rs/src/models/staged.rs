@@ -0,0 +1,293 @@
+use rand::Rng;
+
+use crate::{
+    epi_param_methods,
+    epidemic::{EpiModel, HealthcareCapacity, SEIRLike},
+    params::{CompetingRisks, EpiParamsLocalT},
+    prelude::{Real, Time},
+    sim::RandomUpdate,
+};
+use paste::paste;
+
+/// Wraps an epidemic model and splits its infectious compartment into K
+/// sequential sub-stages.
+///
+/// Each sub-stage lasts a geometric number of days with 1/K of the mean time
+/// spent in the infectious compartment by the base model, so the total
+/// sojourn follows an Erlang-like distribution with the same mean and a
+/// variance that shrinks roughly as mean^2 / K. K = 1 reproduces the base
+/// model. The per-stage probability is capped at 1, so K should be small
+/// compared to the mean infectious period.
+///
+/// Sub-stages are counted as distinct compartments. The first stage keeps the
+/// index of the infectious compartment of the base model and the remaining
+/// K - 1 stages are appended after the last compartment, so CARDINALITY is
+/// expanded to M::CARDINALITY + K - 1. The CSV_HEADER only names the base
+/// compartments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Staged<M, const K: usize> {
+    model: M,
+    stage: usize,
+}
+
+impl<M, const K: usize> Staged<M, K> {
+    /// Wrap model at the first stage.
+    pub fn new(model: M) -> Self {
+        assert!(K > 0, "number of stages must be positive");
+        Staged { model, stage: 0 }
+    }
+
+    /// Wrapped model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Current sub-stage of the infectious compartment. Agents in other
+    /// compartments are always at stage 0.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// Return the wrapped model.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+}
+
+impl<M: SEIRLike, const K: usize> EpiModel for Staged<M, K> {
+    const CARDINALITY: usize = M::CARDINALITY + K - 1;
+    const CSV_HEADER: &'static str = M::CSV_HEADER;
+    const S: usize = M::S;
+    const D: usize = M::D;
+
+    type Disease = M::Disease;
+    type Clinical = M::Clinical;
+
+    fn index(&self) -> usize {
+        if self.stage == 0 {
+            return self.model.index();
+        }
+        return M::CARDINALITY + self.stage - 1;
+    }
+
+    fn new_infectious_with(clinical: &Self::Clinical) -> Self {
+        Self::new(M::new_infectious_with(clinical))
+    }
+
+    fn force_infectious(&mut self, force_dead: bool) -> bool {
+        if self.model.is_infectious() {
+            return true;
+        }
+        self.stage = 0;
+        self.model.force_infectious(force_dead)
+    }
+
+    fn contagion_odds(&self) -> Real {
+        self.model.contagion_odds()
+    }
+
+    fn is_symptomatic(&self) -> bool {
+        self.model.is_symptomatic()
+    }
+
+    fn reinfection_odds(&self) -> Real {
+        self.model.reinfection_odds()
+    }
+
+    fn susceptibility(&self) -> Real {
+        self.model.susceptibility()
+    }
+
+    fn is_susceptible(&self) -> bool {
+        self.model.is_susceptible()
+    }
+
+    fn is_recovered(&self) -> bool {
+        self.model.is_recovered()
+    }
+
+    fn is_dead(&self) -> bool {
+        self.model.is_dead()
+    }
+
+    fn transfer_contamination_from(&mut self, other: &Self) -> bool {
+        self.stage = 0;
+        self.model.transfer_contamination_from(&other.model)
+    }
+}
+
+impl<M: SEIRLike, const K: usize> SEIRLike for Staged<M, K> {
+    const E: usize = M::E;
+    const I: usize = M::I;
+    const R: usize = M::R;
+
+    fn is_exposed(&self) -> bool {
+        self.model.is_exposed()
+    }
+
+    fn is_infectious(&self) -> bool {
+        self.model.is_infectious()
+    }
+
+    fn expose(&mut self, with: &Self::Clinical) {
+        self.stage = 0;
+        self.model.expose(with)
+    }
+
+    fn infect(&mut self, with: &Self::Clinical) {
+        self.stage = 0;
+        self.model.infect(with)
+    }
+}
+
+impl<M, P, const K: usize> RandomUpdate<P> for Staged<M, K>
+where
+    M: SEIRLike + for<'a> RandomUpdate<StagedParams<'a, P>>,
+    P: EpiParamsLocalT,
+{
+    fn random_update<R: Rng>(&mut self, params: &P, rng: &mut R) {
+        // Each stage exits K times faster, keeping the mean number of days in
+        // the compartment equal to that of the base model.
+        let prob = (K as Real * params.infectious_transition_prob()).min(1.0);
+        if self.model.is_infectious() && self.stage + 1 < K {
+            if rng.gen_bool(prob) {
+                self.stage += 1;
+            }
+            return;
+        }
+
+        let index = self.model.index();
+        let staged = StagedParams { params, prob };
+        self.model.random_update(&staged, rng);
+        if self.model.index() != index {
+            self.stage = 0;
+        }
+    }
+
+    fn min_infectious_days(params: &P) -> Time {
+        params.min_infectious_days()
+    }
+}
+
+/// Params seen by the wrapped model in a Staged model. It simply replaces
+/// the infectious transition probability by the exit probability of a single
+/// sub-stage.
+#[derive(Debug)]
+pub struct StagedParams<'a, P> {
+    params: &'a P,
+    prob: Real,
+}
+
+impl<'a, P: EpiParamsLocalT> EpiParamsLocalT for StagedParams<'a, P> {
+    epi_param_methods!(
+        delegate[params]: {
+            incubation_period,
+            infectious_period,
+            severe_period,
+            critical_period,
+            asymptomatic_infectiousness,
+            prob_asymptomatic,
+            prob_severe,
+            prob_critical,
+            case_fatality_ratio,
+            prob_death,
+            infection_fatality_ratio,
+            post_acute_period,
+            prob_post_acute,
+            prob_post_acute_severe,
+            incubation_transition_prob,
+            severe_transition_prob,
+            critical_transition_prob,
+            post_acute_transition_prob,
+            relative_susceptibility_recovered,
+            infectious_period_shape,
+        }
+    );
+
+    fn infectious_transition_prob(&self) -> Real {
+        self.prob
+    }
+
+    fn min_infectious_days(&self) -> Time {
+        self.params.min_infectious_days()
+    }
+
+    fn competing_risks(&self) -> CompetingRisks {
+        self.params.competing_risks()
+    }
+
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        self.params.healthcare()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{SEIRm, SEIR},
+        params::EpiParamsFull,
+        utils::seeded_rng,
+    };
+    use assert_approx_eq::assert_approx_eq;
+
+    fn infectious_periods<const K: usize>(params: &EpiParamsFull<Real>) -> Vec<Real> {
+        let rng = &mut seeded_rng(42u64);
+        let mut periods = vec![];
+        for _ in 0..20_000 {
+            let mut st = Staged::<SEIRm, K>::new(SEIR::Infectious(()));
+            let mut days = 0.0;
+            while st.is_infectious() {
+                st.random_update(params, rng);
+                days += 1.0;
+            }
+            periods.push(days);
+        }
+        return periods;
+    }
+
+    fn mean_var(xs: &[Real]) -> (Real, Real) {
+        let n = xs.len() as Real;
+        let mean = xs.iter().sum::<Real>() / n;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<Real>() / n;
+        return (mean, var);
+    }
+
+    #[test]
+    fn single_stage_matches_base_model() {
+        let params = EpiParamsFull::<Real>::default();
+        let mut base = SEIRm::Exposed(());
+        let mut staged = Staged::<SEIRm, 1>::new(base);
+        assert_eq!(Staged::<SEIRm, 1>::CARDINALITY, SEIRm::CARDINALITY);
+
+        let (rng_a, rng_b) = (&mut seeded_rng(42u64), &mut seeded_rng(42u64));
+        for _ in 0..50 {
+            base.random_update(&params, rng_a);
+            staged.random_update(&params, rng_b);
+            assert_eq!(staged.model(), &base);
+            assert_eq!(staged.index(), base.index());
+        }
+    }
+
+    #[test]
+    fn staged_infectious_period_has_erlang_variance() {
+        let mut params = EpiParamsFull::<Real>::default();
+        params.epidemic.infectious_period = 10.0;
+        assert_eq!(Staged::<SEIRm, 4>::CARDINALITY, SEIRm::CARDINALITY + 3);
+
+        let mean = 1.0 / params.infectious_transition_prob();
+        let (mean_1, var_1) = mean_var(&infectious_periods::<1>(&params));
+        let (mean_4, var_4) = mean_var(&infectious_periods::<4>(&params));
+        assert_approx_eq!(mean_1, mean, 0.3);
+        assert_approx_eq!(mean_4, mean, 0.3);
+        assert!(var_4 < 0.5 * var_1);
+
+        // Sum of K geometric variables with mean m/K has variance m^2/K - m
+        assert_approx_eq!(var_1, mean * mean - mean, 5.0);
+        assert_approx_eq!(var_4, mean * mean / 4.0 - mean, 1.5);
+    }
+}
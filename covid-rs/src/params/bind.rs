@@ -1,9 +1,11 @@
 use super::{
-    epi_local_params::EpiParamsLocalT, epi_params::EpiParamsT, EpiParamsGlobal, LocalBind,
-    MultiComponent,
+    epi_local_params::{CompetingRisks, EpiParamsLocalT},
+    epi_params::EpiParamsT,
+    EpiParamsGlobal, LocalBind, MultiComponent,
 };
 use crate::{
-    prelude::{Age, Real},
+    epidemic::HealthcareCapacity,
+    prelude::{Age, Real, Time},
     sim::HasAge,
 };
 use getset::{Getters, Setters};
@@ -86,7 +88,25 @@ impl<P: EpiParamsT<S>, S> EpiParamsLocalT for Bind<P, S> {
         infectious_transition_prob,
         severe_transition_prob,
         critical_transition_prob,
+        post_acute_period,
+        prob_post_acute,
+        prob_post_acute_severe,
+        post_acute_transition_prob,
+        relative_susceptibility_recovered,
+        infectious_period_shape,
     );
+
+    fn min_infectious_days(&self) -> Time {
+        self.params.min_infectious_days(&self.bind)
+    }
+
+    fn competing_risks(&self) -> CompetingRisks {
+        self.params.competing_risks(&self.bind)
+    }
+
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        self.params.healthcare(&self.bind)
+    }
 }
 
 impl<'a, P: EpiParamsT<S>, S> EpiParamsLocalT for BindRef<'a, P, S> {
@@ -106,7 +126,25 @@ impl<'a, P: EpiParamsT<S>, S> EpiParamsLocalT for BindRef<'a, P, S> {
         &infectious_transition_prob,
         &severe_transition_prob,
         &critical_transition_prob,
+        &post_acute_period,
+        &prob_post_acute,
+        &prob_post_acute_severe,
+        &post_acute_transition_prob,
+        &relative_susceptibility_recovered,
+        &infectious_period_shape,
     );
+
+    fn min_infectious_days(&self) -> Time {
+        self.params.min_infectious_days(&self.bind)
+    }
+
+    fn competing_risks(&self) -> CompetingRisks {
+        self.params.competing_risks(&self.bind)
+    }
+
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        self.params.healthcare(&self.bind)
+    }
 }
 
 impl<T, D> LocalBind<T> for Bind<EpiParamsGlobal<D>, Age>
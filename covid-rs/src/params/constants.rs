@@ -16,6 +16,11 @@ pub const INCUBATION_PERIOD: Real = 3.69;
 pub const INFECTIOUS_PERIOD: Real = 3.47;
 pub const SEVERE_PERIOD: Real = 7.19;
 pub const CRITICAL_PERIOD: Real = 17.50 - 7.19;
+pub const POST_ACUTE_PERIOD: Real = 0.0;
+pub const PROB_POST_ACUTE: Real = 0.0;
+pub const PROB_POST_ACUTE_SEVERE: Real = 0.0;
+pub const PROB_DEATH_ICU_COLLAPSE: Real = 0.9; // critical patients without intensive care
+pub const INFECTIOUS_PERIOD_SHAPE: Real = 1.0; // exponential, as in the geometric transitions
 
 // Distributions
 pub const PROB_ASYMPTOMATIC_DISTRIBUTION: AgeDistribution10 = [
@@ -38,4 +43,7 @@ pub const ASYMPTOMATIC_INFECTIOUSNESS_DISTRIBUTION: AgeDistribution10 = [0.50; 9
 pub const INCUBATION_PERIOD_DISTRIBUTION: AgeDistribution10 = [3.69; 9];
 pub const INFECTIOUS_PERIOD_DISTRIBUTION: AgeDistribution10 = [3.47; 9];
 pub const SEVERE_PERIOD_DISTRIBUTION: AgeDistribution10 = [7.19; 9];
-pub const CRITICAL_PERIOD_DISTRIBUTION: AgeDistribution10 = [17.50 - 7.19; 9];
\ No newline at end of file
+pub const CRITICAL_PERIOD_DISTRIBUTION: AgeDistribution10 = [17.50 - 7.19; 9];
+pub const POST_ACUTE_PERIOD_DISTRIBUTION: AgeDistribution10 = [POST_ACUTE_PERIOD; 9];
+pub const PROB_POST_ACUTE_DISTRIBUTION: AgeDistribution10 = [PROB_POST_ACUTE; 9];
+pub const PROB_POST_ACUTE_SEVERE_DISTRIBUTION: AgeDistribution10 = [PROB_POST_ACUTE_SEVERE; 9];
\ No newline at end of file
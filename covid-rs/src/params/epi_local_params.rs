@@ -1,5 +1,11 @@
 use super::epi_params::{daily_probability, EpiParamsT};
-use crate::prelude::Real;
+use crate::{
+    epidemic::HealthcareCapacity,
+    params::INFECTIOUS_PERIOD_SHAPE,
+    prelude::{Real, Time},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 macro_rules! method {
     ($name:ident) => {
@@ -33,6 +39,18 @@ pub trait EpiParamsLocalT {
     method!(prob_critical);
     method!(case_fatality_ratio);
 
+    fn post_acute_period(&self) -> Real {
+        0.0
+    }
+
+    fn prob_post_acute(&self) -> Real {
+        0.0
+    }
+
+    fn prob_post_acute_severe(&self) -> Real {
+        0.0
+    }
+
     fn prob_death(&self) -> Real {
         let factor = self.prob_critical() * self.prob_severe();
         return self.case_fatality_ratio() / factor;
@@ -55,7 +73,40 @@ pub trait EpiParamsLocalT {
     }
 
     fn critical_transition_prob(&self) -> Real {
-        self.daily_probability(self.severe_period())
+        self.daily_probability(self.critical_period())
+    }
+
+    fn post_acute_transition_prob(&self) -> Real {
+        self.daily_probability(self.post_acute_period())
+    }
+
+    /// Minimum number of days an agent must stay in a contagious compartment
+    /// before any transition is allowed.
+    fn min_infectious_days(&self) -> Time {
+        0
+    }
+
+    /// Policy used to resolve competing transitions from the same compartment.
+    fn competing_risks(&self) -> CompetingRisks {
+        CompetingRisks::default()
+    }
+
+    /// Probability of infection of a recovered agent relative to a fully
+    /// susceptible one. Zero means recovery confers complete immunity.
+    fn relative_susceptibility_recovered(&self) -> Real {
+        0.0
+    }
+
+    /// Shape of the Gamma distribution of infectious periods, for models that
+    /// sample a duration for each agent. The mean is given by
+    /// infectious_period and larger shapes produce less dispersed durations.
+    fn infectious_period_shape(&self) -> Real {
+        INFECTIOUS_PERIOD_SHAPE
+    }
+
+    /// Capacity and occupancy of the healthcare system, if it is modelled.
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        None
     }
 
     /// A helper method that computes the daily transition probability from the
@@ -64,6 +115,94 @@ pub trait EpiParamsLocalT {
     fn daily_probability(&self, value: Real) -> Real {
         daily_probability(value)
     }
+
+    /// Mean time spent in the given compartment.
+    fn transition_period(&self, compartment: Compartment) -> Real {
+        match compartment {
+            Compartment::Exposed => self.incubation_period(),
+            Compartment::Infectious => self.infectious_period(),
+            Compartment::Severe => self.severe_period(),
+            Compartment::Critical => self.critical_period(),
+        }
+    }
+
+    /// Daily probability of leaving the given compartment.
+    fn transition_prob(&self, compartment: Compartment) -> Real {
+        match compartment {
+            Compartment::Exposed => self.incubation_transition_prob(),
+            Compartment::Infectious => self.infectious_transition_prob(),
+            Compartment::Severe => self.severe_transition_prob(),
+            Compartment::Critical => self.critical_transition_prob(),
+        }
+    }
+
+    /// Cumulative hazard of leaving the given compartment after staying there
+    /// for the given number of days.
+    ///
+    /// The hazard is derived from the daily transition probability p, which is
+    /// integrated over time as H(t) = -t * ln(1 - p). The probability of having
+    /// left the compartment after t days is simply 1 - exp(-H(t)).
+    fn cumulative_hazard(&self, compartment: Compartment, days: Real) -> Real {
+        let prob = self.transition_prob(compartment);
+        return -days * (1.0 - prob).ln();
+    }
+}
+
+/// Compartments whose exit is controlled by a transition period.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Compartment {
+    Exposed,
+    Infectious,
+    Severe,
+    Critical,
+}
+
+impl Compartment {
+    /// Compartment associated with a state name, as used in the CSV_HEADER of
+    /// epidemic models. Return None for states without a timed exit.
+    pub fn from_state_name(name: &str) -> Option<Self> {
+        match name {
+            "E" => Some(Compartment::Exposed),
+            "I" | "A" => Some(Compartment::Infectious),
+            "H" => Some(Compartment::Severe),
+            "C" => Some(Compartment::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// Resolution of competing transitions whose probabilities sum to more than
+/// one in a single step.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum CompetingRisks {
+    /// Rescale all probabilities so they sum to one.
+    #[default]
+    Normalize,
+    /// Keep probabilities in order of priority and truncate the last ones.
+    Truncate,
+}
+
+impl CompetingRisks {
+    /// Draw a single outcome from the per-step probabilities of each competing
+    /// transition. Return the index of the chosen transition or None if the
+    /// agent stays in its compartment. Negative probabilities are treated as
+    /// zero.
+    pub fn sample<R: Rng + ?Sized>(self, probs: &[Real], rng: &mut R) -> Option<usize> {
+        let total: Real = probs.iter().map(|p| p.max(0.0)).sum();
+        let scale = match self {
+            CompetingRisks::Normalize if total > 1.0 => 1.0 / total,
+            _ => 1.0,
+        };
+        let u: Real = rng.gen();
+        let mut acc = 0.0;
+        for (i, p) in probs.iter().enumerate() {
+            acc += p.max(0.0) * scale;
+            if u < acc {
+                return Some(i);
+            }
+        }
+        return None;
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -89,4 +228,79 @@ where
     method!(infectious_transition_prob(()));
     method!(severe_transition_prob(()));
     method!(critical_transition_prob(()));
+    method!(post_acute_period(()));
+    method!(prob_post_acute(()));
+    method!(prob_post_acute_severe(()));
+    method!(post_acute_transition_prob(()));
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        params::{EpiParamsFull, EpiParamsMin},
+        utils::seeded_rng,
+    };
+    use assert_approx_eq::assert_approx_eq;
+    use rand::rngs::SmallRng;
+
+    #[test]
+    fn cumulative_hazard_at_mean_period() {
+        let params: EpiParamsMin<Real> = Default::default();
+        let period = params.infectious_period();
+        let hazard = params.cumulative_hazard(Compartment::Infectious, period);
+        assert_approx_eq!(hazard, 1.0, 1e-6);
+        assert_approx_eq!(1.0 - (-hazard).exp(), 0.632, 0.001);
+    }
+
+    #[test]
+    fn competing_risks_resolve_excess_probability() {
+        let rng = &mut seeded_rng(42u64);
+        let frequencies = |risks: CompetingRisks, probs: &[Real], rng: &mut SmallRng| {
+            let mut counts: Vec<Real> = vec![0.0; probs.len() + 1];
+            for _ in 0..10_000 {
+                let i = risks.sample(probs, rng).unwrap_or(probs.len());
+                counts[i] += 1e-4;
+            }
+            return counts;
+        };
+
+        let normalized = frequencies(CompetingRisks::Normalize, &[0.9, 0.6], rng);
+        assert_approx_eq!(normalized[0], 0.6, 0.02);
+        assert_approx_eq!(normalized[1], 0.4, 0.02);
+        assert_eq!(normalized[2], 0.0);
+
+        let truncated = frequencies(CompetingRisks::Truncate, &[0.9, 0.6], rng);
+        assert_approx_eq!(truncated[0], 0.9, 0.02);
+        assert_approx_eq!(truncated[1], 0.1, 0.02);
+
+        // Policies agree when probabilities sum to less than one
+        for risks in [CompetingRisks::Normalize, CompetingRisks::Truncate] {
+            let counts = frequencies(risks, &[0.1, 0.2], rng);
+            assert_approx_eq!(counts[0], 0.1, 0.02);
+            assert_approx_eq!(counts[1], 0.2, 0.02);
+            assert_approx_eq!(counts[2], 0.7, 0.02);
+        }
+    }
+
+    #[test]
+    fn critical_transition_prob_uses_critical_period() {
+        let params: EpiParamsFull<Real> = Default::default();
+        let cached = params.cached();
+        let period = EpiParamsLocalT::critical_period(&cached);
+        assert_ne!(period, EpiParamsLocalT::severe_period(&cached));
+        assert_approx_eq!(
+            EpiParamsLocalT::critical_transition_prob(&cached),
+            daily_probability(period)
+        );
+        let min: EpiParamsMin<Real> = Default::default();
+        assert_approx_eq!(
+            min.critical_transition_prob(),
+            daily_probability(min.critical_period())
+        );
+    }
 }
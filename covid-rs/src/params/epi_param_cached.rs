@@ -1,21 +1,49 @@
 use super::{
-    epi_local_params::EpiParamsLocalT,
+    epi_local_params::{CompetingRisks, EpiParamsLocalT},
     epi_params::{daily_probability, EpiParamsT, EpiParamsData},
-    EpiParamsFull, ForBind, FromLocalParams, LocalBind, MultiComponent,
+    EpiParamsFull, ForBind, FromLocalParams, LocalBind, MultiComponent, INFECTIOUS_PERIOD_SHAPE,
 };
-use crate::{epi_param_method, epi_param_methods, prelude::Real};
-use getset::Getters;
+use crate::{
+    epi_param_method, epi_param_methods,
+    epidemic::HealthcareCapacity,
+    prelude::{Real, Time},
+};
+use getset::{CopyGetters, Getters};
 use paste::paste;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Debug;
 
 /// A cached params take a params impl and caches all transition probability
 /// values. This avoids some potentially expensive computations involving
 /// exponentials by paying a fixed cost upfront when writing data for each
 /// corresponding transition period.
-#[derive(Copy, Clone, Debug, PartialEq, Getters)]
+#[derive(Copy, Clone, Debug, PartialEq, Getters, CopyGetters)]
 pub struct EpiParamsCached<P, T> {
     #[getset(get = "pub")]
     params: P,
+    /// Multiplicative factor applied to the case fatality ratio, and thus to
+    /// prob_death and infection_fatality_ratio. Used to model changes in
+    /// treatment over time.
+    #[getset(get_copy = "pub")]
+    case_fatality_scale: Real,
+    /// Minimum number of days in a contagious compartment. Zero disables
+    /// the constraint.
+    #[getset(get_copy = "pub")]
+    min_infectious_days: Time,
+    /// Policy used to resolve competing transitions.
+    #[getset(get_copy = "pub")]
+    competing_risks: CompetingRisks,
+    /// Probability of infection of recovered agents relative to susceptible
+    /// ones. Zero means recovery confers complete immunity.
+    #[getset(get_copy = "pub")]
+    relative_susceptibility_recovered: Real,
+    /// Shape of the Gamma distribution of sampled infectious periods.
+    #[getset(get_copy = "pub")]
+    infectious_period_shape: Real,
+    /// Capacity and current occupancy of the healthcare system. None means
+    /// that capacity is unlimited.
+    #[getset(get_copy = "pub")]
+    healthcare: Option<HealthcareCapacity>,
     incubation_transition_prob: T,
     infectious_transition_prob: T,
     severe_transition_prob: T,
@@ -38,8 +66,63 @@ where
             critical_transition_prob: params
                 .with_critical_period_data(|xs| xs.map_components(daily_probability)),
             params: params.clone(),
+            case_fatality_scale: 1.0,
+            min_infectious_days: 0,
+            competing_risks: CompetingRisks::default(),
+            relative_susceptibility_recovered: 0.0,
+            infectious_period_shape: INFECTIOUS_PERIOD_SHAPE,
+            healthcare: None,
         }
     }
+
+    fn with_settings(params: &P, settings: CachedSettings) -> Self {
+        let mut new = Self::new(params);
+        new.case_fatality_scale = settings.case_fatality_scale;
+        new.min_infectious_days = settings.min_infectious_days;
+        new.competing_risks = settings.competing_risks;
+        new.relative_susceptibility_recovered = settings.relative_susceptibility_recovered;
+        new.infectious_period_shape = settings.infectious_period_shape;
+        new.healthcare = settings.healthcare;
+        return new;
+    }
+}
+
+impl<P, T> EpiParamsCached<P, T> {
+    /// Set the multiplicative factor applied to the case fatality ratio.
+    pub fn set_case_fatality_scale(&mut self, value: Real) -> &mut Self {
+        self.case_fatality_scale = value;
+        return self;
+    }
+
+    /// Set the minimum number of days in a contagious compartment.
+    pub fn set_min_infectious_days(&mut self, value: Time) -> &mut Self {
+        self.min_infectious_days = value;
+        return self;
+    }
+
+    /// Set the policy used to resolve competing transitions.
+    pub fn set_competing_risks(&mut self, value: CompetingRisks) -> &mut Self {
+        self.competing_risks = value;
+        return self;
+    }
+
+    /// Set the relative susceptibility of recovered agents.
+    pub fn set_relative_susceptibility_recovered(&mut self, value: Real) -> &mut Self {
+        self.relative_susceptibility_recovered = value;
+        return self;
+    }
+
+    /// Set the shape of the Gamma distribution of sampled infectious periods.
+    pub fn set_infectious_period_shape(&mut self, value: Real) -> &mut Self {
+        self.infectious_period_shape = value;
+        return self;
+    }
+
+    /// Set the capacity and occupancy of the healthcare system.
+    pub fn set_healthcare(&mut self, value: Option<HealthcareCapacity>) -> &mut Self {
+        self.healthcare = value;
+        return self;
+    }
 }
 
 impl<P, T> Default for EpiParamsCached<P, T>
@@ -63,6 +146,72 @@ where
     }
 }
 
+/// Settings of cached params that are not derived from the inner params.
+/// Missing values are filled with the same defaults used by
+/// EpiParamsCached::new().
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct CachedSettings {
+    case_fatality_scale: Real,
+    min_infectious_days: Time,
+    competing_risks: CompetingRisks,
+    relative_susceptibility_recovered: Real,
+    infectious_period_shape: Real,
+    healthcare: Option<HealthcareCapacity>,
+}
+
+impl Default for CachedSettings {
+    fn default() -> Self {
+        CachedSettings {
+            case_fatality_scale: 1.0,
+            min_infectious_days: 0,
+            competing_risks: CompetingRisks::default(),
+            relative_susceptibility_recovered: 0.0,
+            infectious_period_shape: INFECTIOUS_PERIOD_SHAPE,
+            healthcare: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedData<P> {
+    params: P,
+    #[serde(default)]
+    settings: CachedSettings,
+}
+
+/// Cached params are serialized as the inner params and the settings that
+/// are not derived from them. Cached values are recomputed on
+/// deserialization.
+impl<P: Serialize, T> Serialize for EpiParamsCached<P, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let settings = CachedSettings {
+            case_fatality_scale: self.case_fatality_scale,
+            min_infectious_days: self.min_infectious_days,
+            competing_risks: self.competing_risks,
+            relative_susceptibility_recovered: self.relative_susceptibility_recovered,
+            infectious_period_shape: self.infectious_period_shape,
+            healthcare: self.healthcare,
+        };
+        CachedData {
+            params: &self.params,
+            settings,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P, T> Deserialize<'de> for EpiParamsCached<P, T>
+where
+    P: Deserialize<'de> + EpiParamsData<T> + Clone,
+    T: MultiComponent<Elem = Real>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = CachedData::<P>::deserialize(deserializer)?;
+        Ok(Self::with_settings(&data.params, data.settings))
+    }
+}
+
 impl<P, T, S> EpiParamsT<S> for EpiParamsCached<P, T>
 where
     P: EpiParamsT<S>,
@@ -77,9 +226,43 @@ where
     epi_param_method!(prob_asymptomatic[S], delegate = params);
     epi_param_method!(prob_severe[S], delegate = params);
     epi_param_method!(prob_critical[S], delegate = params);
-    epi_param_method!(prob_death[S], delegate = params);
-    epi_param_method!(case_fatality_ratio[S], delegate = params);
-    epi_param_method!(infection_fatality_ratio[S], delegate = params);
+    epi_param_method!(post_acute_period[S], delegate = params);
+    epi_param_method!(prob_post_acute[S], delegate = params);
+    epi_param_method!(prob_post_acute_severe[S], delegate = params);
+    epi_param_method!(post_acute_transition_prob[S], delegate = params);
+
+    // Scaled by case_fatality_scale
+    fn prob_death(&self, obj: &S) -> Real {
+        (self.params.prob_death(obj) * self.case_fatality_scale).min(1.0)
+    }
+
+    fn case_fatality_ratio(&self, obj: &S) -> Real {
+        self.params.case_fatality_ratio(obj) * self.case_fatality_scale
+    }
+
+    fn infection_fatality_ratio(&self, obj: &S) -> Real {
+        self.params.infection_fatality_ratio(obj) * self.case_fatality_scale
+    }
+
+    fn min_infectious_days(&self, _obj: &S) -> Time {
+        self.min_infectious_days
+    }
+
+    fn competing_risks(&self, _obj: &S) -> CompetingRisks {
+        self.competing_risks
+    }
+
+    fn relative_susceptibility_recovered(&self, _obj: &S) -> Real {
+        self.relative_susceptibility_recovered
+    }
+
+    fn infectious_period_shape(&self, _obj: &S) -> Real {
+        self.infectious_period_shape
+    }
+
+    fn healthcare(&self, _obj: &S) -> Option<HealthcareCapacity> {
+        self.healthcare
+    }
 
     // Read directly from attributes
     epi_param_methods!(
@@ -104,18 +287,54 @@ where
             infectious_period,
             asymptomatic_infectiousness,
             prob_asymptomatic,
-            case_fatality_ratio,
-            infection_fatality_ratio,
-            prob_death,
 
             // Clinical
             severe_period,
             critical_period,
             prob_severe,
             prob_critical,
+
+            // Post-acute
+            post_acute_period,
+            prob_post_acute,
+            prob_post_acute_severe,
+            post_acute_transition_prob,
         }
     );
 
+    // Scaled by case_fatality_scale
+    fn prob_death(&self) -> Real {
+        (self.params.prob_death() * self.case_fatality_scale).min(1.0)
+    }
+
+    fn case_fatality_ratio(&self) -> Real {
+        self.params.case_fatality_ratio() * self.case_fatality_scale
+    }
+
+    fn infection_fatality_ratio(&self) -> Real {
+        self.params.infection_fatality_ratio() * self.case_fatality_scale
+    }
+
+    fn min_infectious_days(&self) -> Time {
+        self.min_infectious_days
+    }
+
+    fn competing_risks(&self) -> CompetingRisks {
+        self.competing_risks
+    }
+
+    fn relative_susceptibility_recovered(&self) -> Real {
+        self.relative_susceptibility_recovered
+    }
+
+    fn infectious_period_shape(&self) -> Real {
+        self.infectious_period_shape
+    }
+
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        self.healthcare
+    }
+
     // Read directly from attributes
     epi_param_methods!(
        by_field: {
@@ -158,3 +377,60 @@ impl<S> LocalBind<S> for EpiParamsCached<EpiParamsFull<Real>, Real> {
         self
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::EpiParamsGlobal;
+
+    #[test]
+    fn serialization_preserves_settings() {
+        let mut params: EpiParamsGlobal<Real> = EpiParamsFull::default().cached();
+        let mut healthcare = HealthcareCapacity::new(10, 2);
+        healthcare.occupied_beds = 3;
+        params
+            .set_case_fatality_scale(0.5)
+            .set_min_infectious_days(3)
+            .set_competing_risks(CompetingRisks::Truncate)
+            .set_relative_susceptibility_recovered(0.25)
+            .set_infectious_period_shape(4.0)
+            .set_healthcare(Some(healthcare));
+
+        let data = toml::Value::try_from(params).unwrap().to_string();
+        let parsed: EpiParamsGlobal<Real> = toml::from_str(&data).unwrap();
+        assert_same_settings(&parsed, &params);
+
+        let data = serde_json::to_string(&params).unwrap();
+        let parsed: EpiParamsGlobal<Real> = serde_json::from_str(&data).unwrap();
+        assert_same_settings(&parsed, &params);
+    }
+
+    fn assert_same_settings(a: &EpiParamsGlobal<Real>, b: &EpiParamsGlobal<Real>) {
+        assert_eq!(a.case_fatality_scale(), b.case_fatality_scale());
+        assert_eq!(a.min_infectious_days(), b.min_infectious_days());
+        assert_eq!(a.competing_risks(), b.competing_risks());
+        assert_eq!(
+            a.relative_susceptibility_recovered(),
+            b.relative_susceptibility_recovered()
+        );
+        assert_eq!(a.infectious_period_shape(), b.infectious_period_shape());
+        assert_eq!(a.healthcare(), b.healthcare());
+    }
+
+    #[test]
+    fn missing_settings_use_defaults() {
+        let params: EpiParamsGlobal<Real> = EpiParamsFull::default().cached();
+        let data = toml::Value::try_from(params).unwrap();
+        let inner = toml::Value::try_from(params.params()).unwrap();
+        assert_eq!(data.get("params"), Some(&inner));
+
+        let mut table = toml::value::Table::new();
+        table.insert("params".into(), inner);
+        let parsed: EpiParamsGlobal<Real> = toml::Value::Table(table).try_into().unwrap();
+        assert_same_settings(&parsed, &params);
+    }
+}
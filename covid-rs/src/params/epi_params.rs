@@ -1,8 +1,12 @@
 use super::{
     bind::{Bind, BindRef},
-    MultiComponent,
+    epi_local_params::CompetingRisks,
+    MultiComponent, INFECTIOUS_PERIOD_SHAPE,
+};
+use crate::{
+    epidemic::HealthcareCapacity,
+    prelude::{Real, Time},
 };
-use crate::prelude::Real;
 
 /// A set of epidemiological parameters dependent on some value. If no such
 /// dependency exists, the trait can thought as EpiParams<()> and
@@ -80,6 +84,30 @@ pub trait EpiParamsT<S> {
         return self.case_fatality_ratio(obj) / factor;
     }
 
+    /// Average duration of the post-acute (long-term sequelae) phase.
+    ///
+    /// Defaults to zero, which means agents leave the post-acute state
+    /// immediately.
+    fn post_acute_period(&self, _obj: &S) -> Real {
+        0.0
+    }
+
+    /// Probability of a critical survivor developing long-term sequelae (C to P).
+    ///
+    /// The complement is the probability of transitioning directly to R.
+    /// Defaults to zero.
+    fn prob_post_acute(&self, _obj: &S) -> Real {
+        0.0
+    }
+
+    /// Probability of a severe survivor developing long-term sequelae (H to P).
+    ///
+    /// The complement is the probability of transitioning directly to R.
+    /// Defaults to zero.
+    fn prob_post_acute_severe(&self, _obj: &S) -> Real {
+        0.0
+    }
+
     /// Probability of death for (symptomatic) cases.
     /// Defaults to zero.
     fn case_fatality_ratio(&self, _obj: &S) -> Real;
@@ -109,7 +137,41 @@ pub trait EpiParamsT<S> {
 
     /// Probability of transition C -> (D or R) in a single day.
     fn critical_transition_prob(&self, obj: &S) -> Real {
-        self.daily_probability(self.severe_period(obj))
+        self.daily_probability(self.critical_period(obj))
+    }
+
+    /// Probability of transition P -> R in a single day.
+    fn post_acute_transition_prob(&self, obj: &S) -> Real {
+        self.daily_probability(self.post_acute_period(obj))
+    }
+
+    /// Minimum number of days an agent must stay in a contagious compartment
+    /// before any transition is allowed.
+    fn min_infectious_days(&self, _obj: &S) -> Time {
+        0
+    }
+
+    /// Policy used to resolve competing transitions from the same compartment.
+    fn competing_risks(&self, _obj: &S) -> CompetingRisks {
+        CompetingRisks::default()
+    }
+
+    /// Probability of infection of a recovered agent relative to a fully
+    /// susceptible one. Zero means recovery confers complete immunity.
+    fn relative_susceptibility_recovered(&self, _obj: &S) -> Real {
+        0.0
+    }
+
+    /// Shape of the Gamma distribution of infectious periods, for models that
+    /// sample a duration for each agent. The mean is given by
+    /// infectious_period and larger shapes produce less dispersed durations.
+    fn infectious_period_shape(&self, _obj: &S) -> Real {
+        INFECTIOUS_PERIOD_SHAPE
+    }
+
+    /// Capacity and occupancy of the healthcare system, if it is modelled.
+    fn healthcare(&self, _obj: &S) -> Option<HealthcareCapacity> {
+        None
     }
 
     /// A helper method that computes the daily transition probability from the
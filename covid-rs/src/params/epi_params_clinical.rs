@@ -1,6 +1,9 @@
 use super::{
-    constants as cte, epi_local_params::EpiParamsLocalT, epi_params::daily_probability, ForBind,
-    FromLocalParams, MultiComponent,
+    constants as cte,
+    epi_local_params::EpiParamsLocalT,
+    epi_params::daily_probability,
+    error::{check_non_negative, check_period, check_probability, ParamError},
+    ForBind, FromLocalParams, MultiComponent,
 };
 use crate::{
     epi_param_method,
@@ -26,15 +29,32 @@ pub struct EpiParamsClinical<T> {
     pub(crate) prob_severe: T,
     #[getset(get = "pub with_prefix")]
     pub(crate) prob_critical: T,
+    #[getset(get = "pub with_prefix")]
+    pub(crate) post_acute_period: T,
+    #[getset(get = "pub with_prefix")]
+    pub(crate) prob_post_acute: T,
+    #[getset(get = "pub with_prefix")]
+    pub(crate) prob_post_acute_severe: T,
 }
 
 impl<T> EpiParamsClinical<T> {
-    pub fn new(severe_period: T, critical_period: T, prob_severe: T, prob_critical: T) -> Self {
+    pub fn new(
+        severe_period: T,
+        critical_period: T,
+        prob_severe: T,
+        prob_critical: T,
+        post_acute_period: T,
+        prob_post_acute: T,
+        prob_post_acute_severe: T,
+    ) -> Self {
         EpiParamsClinical {
             severe_period,
             critical_period,
             prob_severe,
             prob_critical,
+            post_acute_period,
+            prob_post_acute,
+            prob_post_acute_severe,
         }
     }
 
@@ -47,6 +67,9 @@ impl<T> EpiParamsClinical<T> {
             critical_period: T::from_component(cte::CRITICAL_PERIOD),
             prob_severe: T::from_component(cte::PROB_SEVERE),
             prob_critical: T::from_component(cte::PROB_CRITICAL),
+            post_acute_period: T::from_component(cte::POST_ACUTE_PERIOD),
+            prob_post_acute: T::from_component(cte::PROB_POST_ACUTE),
+            prob_post_acute_severe: T::from_component(cte::PROB_POST_ACUTE_SEVERE),
         }
     }
 
@@ -56,9 +79,29 @@ impl<T> EpiParamsClinical<T> {
             critical_period: cte::CRITICAL_PERIOD_DISTRIBUTION,
             prob_severe: cte::PROB_SEVERE_DISTRIBUTION,
             prob_critical: cte::PROB_CRITICAL_DISTRIBUTION,
+            post_acute_period: cte::POST_ACUTE_PERIOD_DISTRIBUTION,
+            prob_post_acute: cte::PROB_POST_ACUTE_DISTRIBUTION,
+            prob_post_acute_severe: cte::PROB_POST_ACUTE_SEVERE_DISTRIBUTION,
         }
     }
 
+    /// Check that all periods are positive and all probabilities are in the
+    /// [0, 1] interval. The post-acute period may be zero, which disables the
+    /// post-acute compartment.
+    pub fn validate(&self) -> Result<(), ParamError>
+    where
+        T: MultiComponent<Elem = Real>,
+    {
+        check_period("severe_period", &self.severe_period)?;
+        check_period("critical_period", &self.critical_period)?;
+        check_probability("prob_severe", &self.prob_severe)?;
+        check_probability("prob_critical", &self.prob_critical)?;
+        check_non_negative("post_acute_period", &self.post_acute_period)?;
+        check_probability("prob_post_acute", &self.prob_post_acute)?;
+        check_probability("prob_post_acute_severe", &self.prob_post_acute_severe)?;
+        return Ok(());
+    }
+
     /// Maps function to each component of struct
     pub fn map<S>(&self, f: impl Fn(&T) -> S) -> EpiParamsClinical<S> {
         EpiParamsClinical {
@@ -66,6 +109,9 @@ impl<T> EpiParamsClinical<T> {
             critical_period: f(&self.critical_period),
             prob_severe: f(&self.prob_severe),
             prob_critical: f(&self.prob_critical),
+            post_acute_period: f(&self.post_acute_period),
+            prob_post_acute: f(&self.prob_post_acute),
+            prob_post_acute_severe: f(&self.prob_post_acute_severe),
         }
     }
 
@@ -83,10 +129,20 @@ impl<T> EpiParamsClinical<T> {
         daily_probability(self.critical_period(obj))
     }
 
+    pub fn post_acute_transition_prob<S>(&self, obj: &S) -> Real
+    where
+        T: ForBind<S, Output = Real>,
+    {
+        daily_probability(self.post_acute_period(obj))
+    }
+
     epi_param_method!(severe_period<S>);
     epi_param_method!(critical_period<S>);
     epi_param_method!(prob_severe<S>);
     epi_param_method!(prob_critical<S>);
+    epi_param_method!(post_acute_period<S>);
+    epi_param_method!(prob_post_acute<S>);
+    epi_param_method!(prob_post_acute_severe<S>);
 }
 
 impl<T: Default> Default for EpiParamsClinical<T> {
@@ -96,6 +152,9 @@ impl<T: Default> Default for EpiParamsClinical<T> {
             critical_period: T::default(),
             prob_severe: T::default(),
             prob_critical: T::default(),
+            post_acute_period: T::default(),
+            prob_post_acute: T::default(),
+            prob_post_acute_severe: T::default(),
         }
     }
 }
@@ -107,6 +166,9 @@ impl FromLocalParams for EpiParamsClinical<Real> {
             params.critical_period(),
             params.prob_severe(),
             params.prob_critical(),
+            params.post_acute_period(),
+            params.prob_post_acute(),
+            params.prob_post_acute_severe(),
         )
     }
 }
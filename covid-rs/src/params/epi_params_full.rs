@@ -1,6 +1,7 @@
 use super::{
-    epi_local_params::EpiParamsLocalT, epi_params::EpiParamsT, EpiParamsCached, EpiParamsClinical,
-    EpiParamsData, EpiParamsMin, ForBind, FromLocalParams, MultiComponent,
+    constants as cte, epi_local_params::EpiParamsLocalT, epi_params::EpiParamsT, EpiParamsCached,
+    EpiParamsClinical, EpiParamsData, EpiParamsMin, ForBind, FromLocalParams, MultiComponent,
+    ParamError,
 };
 use crate::{epi_param_method, epi_param_methods, prelude::Real};
 use paste::paste;
@@ -35,6 +36,63 @@ impl<T: Default> EpiParamsFull<T> {
     {
         EpiParamsCached::new(self)
     }
+
+    /// Check that all periods are positive and all probabilities are in the
+    /// [0, 1] interval. Return an error describing the first offending field.
+    pub fn validate(&self) -> Result<(), ParamError>
+    where
+        T: MultiComponent<Elem = Real>,
+    {
+        self.epidemic.validate()?;
+        self.clinical.validate()?;
+        return Ok(());
+    }
+
+    /// Like cached(), but validate params first.
+    pub fn checked_cached(&self) -> Result<EpiParamsCached<Self, T>, ParamError>
+    where
+        T: MultiComponent<Elem = Real> + Clone,
+    {
+        self.validate()?;
+        return Ok(self.cached());
+    }
+}
+
+impl EpiParamsFull<Real> {
+    /// Back-solve params from the doubling time, case fatality ratio and mean
+    /// generation interval observed in the early phase of an epidemic.
+    ///
+    /// The generation interval is split between incubation and infectious
+    /// periods in the same proportion as the default values and all other
+    /// params, including the asymptomatic fraction, are kept at their defaults.
+    /// Transmissibility is a property of the sampler, so the doubling time is
+    /// not stored in params. Instead, return the pair (params, rate) in which
+    /// rate is the daily number of infections per infectious agent (i.e.,
+    /// contacts * prob_infection) that reproduces the doubling time in the
+    /// discrete-time SEIR dynamics of Simulation.
+    pub fn from_observables(
+        doubling_time: Real,
+        cfr: Real,
+        mean_generation_interval: Real,
+    ) -> (Self, Real) {
+        let ratio = cte::INCUBATION_PERIOD / (cte::INCUBATION_PERIOD + cte::INFECTIOUS_PERIOD);
+        let mut params: Self = Default::default();
+        params
+            .epidemic
+            .set_incubation_period(ratio * mean_generation_interval)
+            .set_infectious_period((1.0 - ratio) * mean_generation_interval)
+            .set_case_fatality_ratio(cfr);
+
+        // Exposed agents become infectious with probability pe and transmit
+        // in the same step, infectious agents recover with probability pi
+        // before transmitting. Linearizing, the growth factor lambda per step
+        // is an eigenvalue of the (E, I) transition matrix.
+        let pe = EpiParamsLocalT::incubation_transition_prob(&params);
+        let pi = EpiParamsLocalT::infectious_transition_prob(&params);
+        let lambda = (2.0 as Real).powf(1.0 / doubling_time);
+        let rate = (lambda - 1.0 + pe) * (lambda - 1.0 + pi) / (pe * lambda);
+        return (params, rate);
+    }
 }
 
 impl<T, S> EpiParamsT<S> for EpiParamsFull<T>
@@ -57,6 +115,10 @@ where
     epi_param_method!(prob_critical[S], delegate = clinical);
     epi_param_method!(severe_transition_prob[S], delegate = clinical);
     epi_param_method!(critical_transition_prob[S], delegate = clinical);
+    epi_param_method!(post_acute_period[S], delegate = clinical);
+    epi_param_method!(prob_post_acute[S], delegate = clinical);
+    epi_param_method!(prob_post_acute_severe[S], delegate = clinical);
+    epi_param_method!(post_acute_transition_prob[S], delegate = clinical);
 }
 
 impl EpiParamsLocalT for EpiParamsFull<Real> {
@@ -76,6 +138,9 @@ impl EpiParamsLocalT for EpiParamsFull<Real> {
             critical_period,
             prob_severe,
             prob_critical,
+            post_acute_period,
+            prob_post_acute,
+            prob_post_acute_severe,
         }
     );
 }
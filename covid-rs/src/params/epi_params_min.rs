@@ -3,6 +3,7 @@ use super::{
     epi_local_params::EpiParamsLocalT,
     epi_params::{EpiParamsData, EpiParamsT},
     epi_params_clinical::EpiParamsClinical,
+    error::{check_non_negative, check_period, check_probability, ParamError},
     ForBind, FromLocalParams, MultiComponent,
 };
 use crate::{
@@ -88,6 +89,23 @@ impl<T> EpiParamsMin<T> {
         }
     }
 
+    /// Check that all periods are positive and all probabilities are in the
+    /// [0, 1] interval.
+    pub fn validate(&self) -> Result<(), ParamError>
+    where
+        T: MultiComponent<Elem = Real>,
+    {
+        check_period("incubation_period", &self.incubation_period)?;
+        check_period("infectious_period", &self.infectious_period)?;
+        check_non_negative(
+            "asymptomatic_infectiousness",
+            &self.asymptomatic_infectiousness,
+        )?;
+        check_probability("prob_asymptomatic", &self.prob_asymptomatic)?;
+        check_probability("case_fatality_ratio", &self.case_fatality_ratio)?;
+        return Ok(());
+    }
+
     /// Maps each param to function and construct a new EpidemicSEIRParams
     pub fn map<S>(&self, f: impl Fn(&T) -> S) -> EpiParamsMin<S> {
         EpiParamsMin {
@@ -0,0 +1,148 @@
+use super::MultiComponent;
+use crate::prelude::Real;
+use std::fmt;
+
+/// Error produced when an epidemiological param is outside its valid range.
+///
+/// The field name includes the index of the offending component for params
+/// that depend on age, e.g., "prob_severe[3]".
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// A probability outside the [0, 1] interval.
+    InvalidProbability { field: String, value: Real },
+    /// A period that is not positive.
+    InvalidPeriod { field: String, value: Real },
+    /// A negative value for a param that must be non-negative.
+    NegativeValue { field: String, value: Real },
+}
+
+impl ParamError {
+    /// Name of the offending field.
+    pub fn field(&self) -> &str {
+        match self {
+            ParamError::InvalidProbability { field, .. }
+            | ParamError::InvalidPeriod { field, .. }
+            | ParamError::NegativeValue { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::InvalidProbability { field, value } => {
+                write!(f, "{} = {} must be a probability in [0, 1]", field, value)
+            }
+            ParamError::InvalidPeriod { field, value } => {
+                write!(f, "{} = {} must be a positive period", field, value)
+            }
+            ParamError::NegativeValue { field, value } => {
+                write!(f, "{} = {} must be non-negative", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Check that all components of value satisfy the predicate, building the
+/// error from the field name and the first offending component otherwise.
+fn check<T>(
+    name: &str,
+    value: &T,
+    ok: impl Fn(Real) -> bool,
+    err: impl Fn(String, Real) -> ParamError,
+) -> Result<(), ParamError>
+where
+    T: MultiComponent<Elem = Real>,
+{
+    let components = value.components();
+    for (i, &x) in components.iter().enumerate() {
+        if !ok(x) {
+            let field = if components.len() == 1 {
+                name.to_string()
+            } else {
+                format!("{}[{}]", name, i)
+            };
+            return Err(err(field, x));
+        }
+    }
+    return Ok(());
+}
+
+pub(crate) fn check_probability<T>(name: &str, value: &T) -> Result<(), ParamError>
+where
+    T: MultiComponent<Elem = Real>,
+{
+    check(
+        name,
+        value,
+        |x| (0.0..=1.0).contains(&x),
+        |field, value| ParamError::InvalidProbability { field, value },
+    )
+}
+
+pub(crate) fn check_period<T>(name: &str, value: &T) -> Result<(), ParamError>
+where
+    T: MultiComponent<Elem = Real>,
+{
+    check(
+        name,
+        value,
+        |x| x > 0.0,
+        |field, value| ParamError::InvalidPeriod { field, value },
+    )
+}
+
+pub(crate) fn check_non_negative<T>(name: &str, value: &T) -> Result<(), ParamError>
+where
+    T: MultiComponent<Elem = Real>,
+{
+    check(
+        name,
+        value,
+        |x| x >= 0.0,
+        |field, value| ParamError::NegativeValue { field, value },
+    )
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        params::{EpiParamsClinical, EpiParamsFull, EpiParamsMin},
+        prelude::AgeDistribution10,
+    };
+
+    #[test]
+    fn out_of_range_params_are_rejected() {
+        let mut params = EpiParamsFull::<Real>::default();
+        assert_eq!(params.validate(), Ok(()));
+        assert!(params.checked_cached().is_ok());
+
+        params.epidemic.set_case_fatality_ratio(1.8);
+        let err = params.checked_cached().unwrap_err();
+        assert_eq!(err.field(), "case_fatality_ratio");
+        assert_eq!(
+            err.to_string(),
+            "case_fatality_ratio = 1.8 must be a probability in [0, 1]"
+        );
+
+        let mut params = EpiParamsFull::new(
+            EpiParamsMin::<AgeDistribution10>::default_distributions(),
+            EpiParamsClinical::<AgeDistribution10>::default_distributions(),
+        );
+        assert_eq!(params.validate(), Ok(()));
+        params.clinical.prob_severe[3] = -0.1;
+        assert_eq!(params.validate().unwrap_err().field(), "prob_severe[3]");
+        params.epidemic.infectious_period[0] = 0.0;
+        assert!(matches!(
+            params.validate(),
+            Err(ParamError::InvalidPeriod { .. })
+        ));
+    }
+}
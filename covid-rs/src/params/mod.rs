@@ -12,7 +12,9 @@ mod epi_params;
 mod epi_params_clinical;
 mod epi_params_full;
 mod epi_params_min;
+mod error;
 mod macros;
+mod scheduled;
 mod vaccine_simple;
 
 pub use bind::*;
@@ -23,9 +25,12 @@ pub use epi_params::*;
 pub use epi_params_clinical::*;
 pub use epi_params_full::*;
 pub use epi_params_min::*;
+pub use error::*;
+pub use scheduled::*;
 pub use vaccine_simple::*;
 
-use crate::{prelude::{Age, AgeParam, ForAge, Real}, sim::HasAge};
+use crate::{prelude::{Age, AgeBands, AgeParam, ForAge, Real}, sim::HasAge};
+use std::cell::RefCell;
 
 ///////////////////////////////////////////////////////////////////////////////
 // Basic public traits
@@ -72,6 +77,11 @@ pub trait LocalBind<S> {
     /// Just a convenience function that extract bind data and then binds
     fn bind_to_object(&mut self, _: &S);
 
+    /// Notify params about the current simulation step. This is called once
+    /// at the start of each step and is used by params that change over time.
+    /// The default implementation does nothing.
+    fn bind_step(&mut self, _step: usize) {}
+
     /// Return a copy bound to the given bind state
     fn clone_to_object(&self, obj: &S) -> Self::Local
     where
@@ -138,6 +148,19 @@ where
 
     /// Create data from single element, possibly replicating it for all keys.
     fn from_component(x: Self::Elem) -> Self;
+
+    /// Return a vector with all components.
+    fn components(&self) -> Vec<Self::Elem>
+    where
+        Self::Elem: Copy,
+    {
+        let data = RefCell::new(vec![]);
+        self.map_components(|x| {
+            data.borrow_mut().push(x);
+            x
+        });
+        return data.into_inner();
+    }
 }
 
 impl MultiComponent for Real {
@@ -167,6 +190,21 @@ where
     }
 }
 
+impl<T, const N: usize, const WIDTH: u8> MultiComponent for AgeBands<T, N, WIDTH>
+where
+    T: Sized + Copy,
+{
+    type Elem = T;
+
+    fn map_components(&self, f: impl Fn(Self::Elem) -> Self::Elem) -> Self {
+        self.map(f)
+    }
+
+    fn from_component(x: Self::Elem) -> Self {
+        AgeBands([x; N])
+    }
+}
+
 impl MultiComponent for AgeParam {
     type Elem = Real;
 
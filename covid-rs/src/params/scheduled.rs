@@ -0,0 +1,129 @@
+use super::LocalBind;
+
+/// Switch between different sets of params during a simulation.
+///
+/// The schedule is a list of (step, params) pairs and each entry becomes
+/// active from the given step onwards, until the next entry. Steps before
+/// the first entry use the first params of the schedule. This can be used to
+/// model, e.g., therapeutics that shorten the infectious period in the middle
+/// of an epidemic.
+///
+/// All LocalBind methods delegate to the active params. In particular,
+/// world_mut() modifies only the params active at the current step, so
+/// world_update closures must reapply their changes at each step to survive
+/// a switch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledParams<W> {
+    schedule: Vec<(usize, W)>,
+    active: usize,
+}
+
+impl<W> ScheduledParams<W> {
+    /// Create new schedule. Entries are sorted by step.
+    pub fn new(schedule: Vec<(usize, W)>) -> Self {
+        assert!(
+            !schedule.is_empty(),
+            "schedule must have at least one entry"
+        );
+        let mut schedule = schedule;
+        schedule.sort_by_key(|(step, _)| *step);
+        ScheduledParams {
+            schedule,
+            active: 0,
+        }
+    }
+
+    /// List of (step, params) entries, sorted by step.
+    pub fn schedule(&self) -> &[(usize, W)] {
+        &self.schedule
+    }
+
+    /// Params active at the current step.
+    pub fn active(&self) -> &W {
+        &self.schedule[self.active].1
+    }
+
+    /// Mutable reference to the params active at the current step.
+    pub fn active_mut(&mut self) -> &mut W {
+        &mut self.schedule[self.active].1
+    }
+}
+
+impl<S, W: LocalBind<S>> LocalBind<S> for ScheduledParams<W> {
+    type Local = W::Local;
+    type World = W::World;
+    type Bind = W::Bind;
+
+    fn bind(&mut self, bind: Self::Bind) {
+        self.active_mut().bind(bind)
+    }
+
+    fn bind_step(&mut self, step: usize) {
+        self.active = self
+            .schedule
+            .iter()
+            .rposition(|(start, _)| *start <= step)
+            .unwrap_or(0);
+        self.active_mut().bind_step(step);
+    }
+
+    fn local(&self) -> &Self::Local {
+        self.active().local()
+    }
+
+    fn world(&self) -> &Self::World {
+        self.active().world()
+    }
+
+    fn world_mut(&mut self) -> &mut Self::World {
+        self.active_mut().world_mut()
+    }
+
+    fn bind_to_object(&mut self, obj: &S) {
+        self.active_mut().bind_to_object(obj)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        epidemic::{EpiModelPopulationExt, SEIRLike},
+        models::SEIRm,
+        params::{EpiParamsFull, EpiParamsLocal, EpiParamsLocalT},
+        prelude::Real,
+        sim::Simulation,
+    };
+
+    fn with_infectious_period(period: Real) -> EpiParamsLocal {
+        let mut params = EpiParamsFull::<Real>::default();
+        params.epidemic.set_infectious_period(period);
+        return EpiParamsLocal::new(&params);
+    }
+
+    #[test]
+    fn scheduled_params_switch_at_given_step() {
+        let schedule = vec![
+            (5, with_infectious_period(0.1)),
+            (0, with_infectious_period(1e6)),
+        ];
+        let params = ScheduledParams::new(schedule);
+        assert_eq!(params.schedule()[0].0, 0);
+
+        let population = vec![SEIRm::Infectious(()); 1000];
+        let mut sim = Simulation::new_simple(params, population, 0.0, 0.0);
+        sim.seed(42);
+        sim.run(5);
+        assert!(sim.population().iter().all(|st| st.is_infectious()));
+
+        sim.run(5);
+        assert_eq!(sim.population().n_infectious(), 0);
+        let params = sim.params().borrow();
+        let world = LocalBind::<SEIRm>::world(&*params);
+        assert_eq!(world.infectious_period(), 0.1);
+    }
+}
@@ -1,7 +1,10 @@
-use super::{EpiParamsLocalT, EpiParamsT, EpiParamsGlobal, LocalBind, MultiComponent};
+use super::{
+    CompetingRisks, EpiParamsGlobal, EpiParamsLocalT, EpiParamsT, LocalBind, MultiComponent,
+};
 use crate::{
+    epidemic::HealthcareCapacity,
     models::SimpleAgent,
-    prelude::{Age, Real},
+    prelude::{Age, Real, Time},
     sim::HasAge,
 };
 use getset::*;
@@ -88,6 +91,13 @@ where
 
             asymptomatic_infectiousness,
             prob_asymptomatic,
+
+            post_acute_period,
+            prob_post_acute,
+            prob_post_acute_severe,
+            post_acute_transition_prob,
+            relative_susceptibility_recovered,
+            infectious_period_shape,
         }
     );
 
@@ -98,6 +108,18 @@ where
             case_fatality_ratio,
         }
     );
+
+    fn min_infectious_days(&self) -> Time {
+        self.params.min_infectious_days(&self.age)
+    }
+
+    fn competing_risks(&self) -> CompetingRisks {
+        self.params.competing_risks(&self.age)
+    }
+
+    fn healthcare(&self) -> Option<HealthcareCapacity> {
+        self.params.healthcare(&self.age)
+    }
 }
 
 impl<P> From<P> for BindVaccine<P> {
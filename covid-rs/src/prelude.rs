@@ -1,11 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryInto;
 // pub use crate::agent::Ag;
 pub use crate::epidemic::*;
 // pub use crate::pop_builder::PopBuilder;
 // pub use crate::simulation::Simulation;
 // pub use crate::reporter::{Report};
 pub use crate::sampler::{
-    AnySampler, ContactMatrixSampler, PopulationSampler, Sampler, SimpleSampler,
+    AnySampler, BoxedSampler, ContactMatrixSampler, PopulationSampler, Sampler, SimpleSampler,
 };
 
 /// Basic representation of time. This crate usually assumes time is measured
@@ -39,6 +40,98 @@ pub const AGE_DISTRIBUTION_BRAZIL: AgeDistribution10 = [
 /// Count population in each bin of 10 years.
 pub type AgeCount10 = [u32; 9];
 
+/// An age distribution in bins of 5 years (0-4, 5-9, ..., 80+).
+pub type AgeDistribution5 = AgeBins<17>;
+
+/// Real-valued age bins of WIDTH years, which defaults to 5. Ages above the
+/// top bin are clamped to it.
+pub type AgeBins<const N: usize, const WIDTH: u8 = 5> = AgeBands<Real, N, WIDTH>;
+
+/// Age-dependent values in N bands of WIDTH years. The last band is
+/// open-ended and includes all older ages.
+///
+/// AgeDistribution10 is kept as a plain [Real; 9] array, which has the same
+/// semantics as AgeBands<Real, 9, 10>.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AgeBands<T, const N: usize, const WIDTH: u8>(pub [T; N]);
+
+impl<T, const N: usize, const WIDTH: u8> AgeBands<T, N, WIDTH> {
+    /// Index of the band that contains age.
+    #[inline]
+    pub fn band(age: Age) -> usize {
+        ((age / WIDTH) as usize).min(N - 1)
+    }
+
+    pub fn map<S>(&self, f: impl Fn(T) -> S) -> AgeBands<S, N, WIDTH>
+    where
+        T: Copy,
+    {
+        AgeBands(self.0.map(f))
+    }
+}
+
+impl<const N: usize, const WIDTH: u8> AgeBands<Real, N, WIDTH> {
+    /// Aggregate bins into an AgeDistribution10, for compatibility with
+    /// params that use 10-year groups.
+    ///
+    /// Each 10-year group receives the mean value of its ages, i.e., bins are
+    /// weighted by the number of years they overlap with the group. This is
+    /// appropriate for rates and probabilities, but not for population counts.
+    /// The open-ended 80+ group is computed from ages 80-89.
+    pub fn resample_to_10(&self) -> AgeDistribution10 {
+        let mut out = [0.0; 9];
+        for (i, x) in out.iter_mut().enumerate() {
+            let start = 10 * i as Age;
+            *x = (start..start + 10)
+                .map(|age| self.for_age(age))
+                .sum::<Real>()
+                / 10.0;
+        }
+        return out;
+    }
+}
+
+impl<T: Default + Copy, const N: usize, const WIDTH: u8> Default for AgeBands<T, N, WIDTH> {
+    fn default() -> Self {
+        AgeBands([T::default(); N])
+    }
+}
+
+impl<T, const N: usize, const WIDTH: u8> From<[T; N]> for AgeBands<T, N, WIDTH> {
+    fn from(data: [T; N]) -> Self {
+        AgeBands(data)
+    }
+}
+
+impl<T: Copy, const N: usize, const WIDTH: u8> ForAge for AgeBands<T, N, WIDTH> {
+    type Output = T;
+
+    #[inline]
+    fn for_age(&self, age: Age) -> T {
+        self.0[Self::band(age)]
+    }
+}
+
+impl<T: Serialize, const N: usize, const WIDTH: u8> Serialize for AgeBands<T, N, WIDTH> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.as_ref().serialize(serializer)
+    }
+}
+
+impl<'de, T, const N: usize, const WIDTH: u8> Deserialize<'de> for AgeBands<T, N, WIDTH>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data: Vec<T> = Vec::deserialize(deserializer)?;
+        let n = data.len();
+        let data = data
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(n, &format!("{} age bands", N).as_str()))?;
+        return Ok(AgeBands(data));
+    }
+}
+
 /// Simple trait to simplify the use of age-dependent values/parameters.
 /// Basically, ForAge data is simply an encoding for a function like
 /// fn(Age) -> Output;
@@ -77,8 +170,7 @@ where
 
     #[inline]
     fn for_age(&self, age: Age) -> T {
-        let idx = (age / 10).min(8);
-        self[idx as usize]
+        self[AgeBands::<T, 9, 10>::band(age)]
     }
 }
 
@@ -145,3 +237,67 @@ impl ForAge for AgeParam {
         }
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{EpiParamsFull, EpiParamsT};
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn five_year_bands_resolve_ages() {
+        let mut data = [0.0; 17];
+        for (i, x) in data.iter_mut().enumerate() {
+            *x = i as Real;
+        }
+        let bands: AgeDistribution5 = data.into();
+        assert_eq!(bands.for_age(7), 1.0);
+        assert_eq!(bands.for_age(12), 2.0);
+        assert_eq!(bands.for_age(72), 14.0);
+        assert_eq!(bands.for_age(95), 16.0);
+
+        let mut params: EpiParamsFull<AgeDistribution5> = Default::default();
+        params.clinical.set_prob_severe(bands.map(|x| x / 100.0));
+        assert_eq!(params.prob_severe(&72), 0.14);
+
+        let data = toml::to_string(&params).unwrap();
+        let parsed: EpiParamsFull<AgeDistribution5> = toml::from_str(&data).unwrap();
+        assert_eq!(parsed, params);
+    }
+
+    #[test]
+    fn age_bins_clamp_and_resample_to_10() {
+        let bins: AgeBins<4, 20> = [0.1, 0.2, 0.3, 0.4].into();
+        assert_eq!(bins.for_age(19), 0.1);
+        assert_eq!(bins.for_age(65), 0.4);
+        assert_eq!(bins.for_age(120), 0.4);
+
+        let expected = [0.1, 0.1, 0.2, 0.2, 0.3, 0.3, 0.4, 0.4, 0.4];
+        for (x, y) in bins.resample_to_10().iter().zip(expected) {
+            assert_approx_eq!(x, y);
+        }
+
+        let mut data = [0.0; 17];
+        for (i, x) in data.iter_mut().enumerate() {
+            *x = i as Real;
+        }
+        let fine: AgeBins<17> = data.into();
+        let coarse = fine.resample_to_10();
+        assert_eq!(coarse[0], 0.5);
+        assert_eq!(coarse[7], 14.5);
+        assert_eq!(coarse[8], 16.0);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Data {
+            bins: AgeBins<4, 20>,
+        }
+        let data = toml::to_string(&Data { bins }).unwrap();
+        assert_eq!(data.trim(), "bins = [0.1, 0.2, 0.3, 0.4]");
+        assert_eq!(toml::from_str::<Data>(&data).unwrap(), Data { bins });
+        assert!(toml::from_str::<Data>("bins = [0.1, 0.2]").is_err());
+    }
+}
@@ -1,10 +1,16 @@
 use crate::{
+    params::EpiParamsLocalT,
     prelude::{EpiModel, Real},
     sim::{HasAge, Population},
+    utils::{gamma, poisson},
 };
 use getset::*;
+use log::warn;
 use ndarray::prelude::*;
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, collections::HashSet, path::Path};
 
 pub trait Sampler {
     /// Baseline probability of infection. Different samplers may interpret this
@@ -36,17 +42,126 @@ where
     /// requires any state  
     fn sample_infection_pairs(&self, population: &P, rng: &mut impl Rng) -> Vec<(usize, usize)>;
 
+    /// Like sample_infection_pairs, but receive the index of the current
+    /// simulation step. This is used by samplers that change over time and
+    /// the default implementation ignores the step.
+    fn sample_infection_pairs_at(
+        &self,
+        _step: usize,
+        population: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        self.sample_infection_pairs(population, rng)
+    }
+
+    /// Advance internal clock by one step. This is called by the simulation
+    /// once per step, after infection pairs are sampled, and the default
+    /// implementation does nothing.
+    fn advance_step(&mut self) {}
+
     /// Return the expected number of infection pairs for population.
     fn expected_infection_pairs(&self, population: &P) -> Real {
         let mut rng = SmallRng::from_entropy();
         self.sample_infection_pairs(population, &mut rng).len() as Real
     }
 
+    /// Probability that agent id is infected in the next step, given the
+    /// current state of population.
+    ///
+    /// The default implementation assumes contacts are uniformly distributed
+    /// in the population, with contacts() contacts per contagious individual.
+    fn infection_hazard(&self, population: &P, id: usize) -> Real
+    where
+        P::State: EpiModel,
+    {
+        if population.map_agent(id, is_exposable) != Some(true) {
+            return 0.0;
+        }
+        let prob = self.prob_infection();
+        let mut rate = 0.0;
+        population.each_agent(&mut |i, st: &P::State| {
+            if i != id {
+                rate += (prob * st.contagion_odds()).min(1.0);
+            }
+        });
+        return 1.0 - (-rate * self.contacts() / population.count() as Real).exp();
+    }
+
+    /// Search for the probability of infection that produces the given basic
+    /// reproduction number, set it in the sampler and return it.
+    ///
+    /// R0 is estimated from the expected number of infection pairs per
+    /// contagious agent in population, multiplied by the infectious period in
+    /// params. Population should contain a few contagious agents and be
+    /// otherwise fully susceptible. If R0 cannot be reached even when every
+    /// contact results in infection, the probability is clamped to 1.0 and a
+    /// warning is logged.
+    ///
+    /// The result is deterministic for samplers that compute
+    /// expected_infection_pairs() analytically, like SimpleSampler and
+    /// ContactMatrixSampler. Other samplers estimate it from a random sample
+    /// and the result may change between calls.
+    fn calibrate_prob_infection_for_r0(
+        &mut self,
+        population: &P,
+        params: &impl EpiParamsLocalT,
+        r0: Real,
+    ) -> Real
+    where
+        P::State: EpiModel,
+        Self: Sized,
+    {
+        let mut n_contagious = 0;
+        population.each_agent(&mut |_, st: &P::State| {
+            n_contagious += st.is_contagious() as usize;
+        });
+        if n_contagious == 0 {
+            warn!("cannot calibrate R0 in a population without contagious agents");
+            return self.prob_infection();
+        }
+
+        let period = params.infectious_period();
+        let r0_for = |sampler: &mut Self, prob: Real| {
+            sampler.set_prob_infection(prob);
+            sampler.expected_infection_pairs(population) / n_contagious as Real * period
+        };
+        if r0_for(self, 1.0) < r0 {
+            warn!(
+                "R0 = {} is not reachable, clamping prob_infection to 1.0",
+                r0
+            );
+            return 1.0;
+        }
+
+        let (mut a, mut b) = (0.0, 1.0);
+        for _ in 0..50 {
+            let mid = 0.5 * (a + b);
+            if r0_for(self, mid) < r0 {
+                a = mid;
+            } else {
+                b = mid;
+            }
+        }
+        let prob = 0.5 * (a + b);
+        self.set_prob_infection(prob);
+        return prob;
+    }
+
     /// Update any necessary internal state from the initial list of agents.
     /// This is called everytime the sampler is registered in a simulation.
     /// The sampler may modify  
     fn init(&mut self, _population: &mut P) {}
 
+    /// Return true if the sampler handles agents appended to the population
+    /// after init(), as done by susceptible replenishment in simulations.
+    ///
+    /// Samplers that index the initial population, such as contact networks
+    /// or age groups, ignore new agents and the default implementation
+    /// returns false.
+    fn supports_population_growth(&self) -> bool {
+        false
+    }
+
     /// Update population of EpiModels by sampling pairs and than contaminating
     /// each pair using the model.contaminate_from() method.
     ///
@@ -118,12 +233,16 @@ impl<P: Population> PopulationSampler<P> for NoOpSampler {
     fn sample_infection_pairs(&self, _pool: &P, _rng: &mut impl Rng) -> Vec<(usize, usize)> {
         vec![]
     }
+
+    fn supports_population_growth(&self) -> bool {
+        true
+    }
 }
 
 /// A simple sampling strategy that picks up a fixed number of contacts per
 /// infectious individual and infect randomly in population using the given
 /// probability of infection.
-#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct SimpleSampler {
     contacts: Real,
     prob_infection: Real,
@@ -137,6 +256,23 @@ impl SimpleSampler {
         }
     }
 
+    /// Create sampler whose probability of infection produces the given R0
+    /// in a fully susceptible population, i.e., R0 = contacts * prob_infection
+    /// * infectious_period.
+    ///
+    /// Probabilities above 1.0 are clamped and a warning is logged, since the
+    /// target R0 is not reachable with the given number of contacts.
+    pub fn from_r0(r0: Real, contacts: Real, infectious_period: Real) -> Self {
+        let prob = r0 / (contacts * infectious_period);
+        if prob > 1.0 {
+            warn!(
+                "R0 = {} is not reachable with {} contacts, clamping prob_infection to 1.0",
+                r0, contacts
+            );
+        }
+        return SimpleSampler::new(contacts, prob.min(1.0));
+    }
+
     fn each_infection_pair<P, R, F>(&self, pop: &P, rng: &mut R, f: F)
     where
         F: FnMut(usize, usize),
@@ -150,17 +286,14 @@ impl SimpleSampler {
         pop.each_agent(&mut |i, st| {
             let odds = st.contagion_odds();
             if odds > 0.0 {
-                let mut m = round_probabilistically(self.contacts, rng);
-                while m > 0 {
+                let m = round_probabilistically(self.contacts, rng);
+                for _ in 0..m {
                     if rng.gen_bool((self.prob_infection * odds).min(1.0)) {
                         let j = rng.gen_range(0..n);
-                        if i == j {
-                            continue;
-                        } else if pop.map_agent(j, |ag| ag.is_susceptible()) == Some(true) {
+                        if i != j && pop.map_agent(j, |ag| st.can_contaminate(ag)) == Some(true) {
                             action(i, j);
                         }
                     }
-                    m -= 1;
                 }
             }
         });
@@ -198,34 +331,150 @@ where
         return pairs;
     }
 
-    fn expected_infection_pairs(&self, population: &P) -> Real {
-        use crate::utils::default_rng;
+    fn supports_population_growth(&self) -> bool {
+        true
+    }
 
-        let mut total = 0;
-        let mut rng = default_rng();
-        self.each_infection_pair(population, &mut rng, |_, _| total += 1);
-        return total as Real;
+    fn expected_infection_pairs(&self, pop: &P) -> Real {
+        uniform_expected_pairs(self.contacts, self.prob_infection, pop)
+    }
+}
+
+/// Like SimpleSampler, but the number of contacts of each infectious agent is
+/// overdispersed.
+///
+/// Contacts are drawn from a Gamma-Poisson mixture (a negative binomial) with
+/// mean contacts and dispersion k. Small values of k concentrate transmission
+/// in a minority of agents, while k -> infinity recovers a Poisson number of
+/// contacts.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverdispersedSampler {
+    contacts: Real,
+    prob_infection: Real,
+    dispersion: Real,
+}
+
+impl OverdispersedSampler {
+    pub fn new(contacts: Real, prob_infection: Real, dispersion: Real) -> Self {
+        let mut new = OverdispersedSampler {
+            contacts,
+            prob_infection,
+            dispersion: 1.0,
+        };
+        new.set_dispersion(dispersion);
+        return new;
+    }
+
+    /// Dispersion parameter k of the negative binomial distribution.
+    pub fn dispersion(&self) -> Real {
+        self.dispersion
+    }
+
+    /// Set dispersion parameter k. Must be positive.
+    pub fn set_dispersion(&mut self, k: Real) -> &mut Self {
+        assert!(k > 0.0, "dispersion must be positive");
+        self.dispersion = k;
+        return self;
+    }
+
+    fn each_infection_pair<P, R, F>(&self, pop: &P, rng: &mut R, f: F)
+    where
+        F: FnMut(usize, usize),
+        R: Rng,
+        P: Population,
+        P::State: EpiModel,
+    {
+        let n = pop.count();
+        let k = self.dispersion;
+        let mut action = f;
+
+        pop.each_agent(&mut |i, st| {
+            let odds = st.contagion_odds();
+            if odds > 0.0 {
+                let lambda = gamma(k, self.contacts / k, rng);
+                for _ in 0..poisson(lambda, rng) {
+                    if rng.gen_bool((self.prob_infection * odds).min(1.0)) {
+                        let j = rng.gen_range(0..n);
+                        if i != j && pop.map_agent(j, |ag| st.can_contaminate(ag)) == Some(true) {
+                            action(i, j);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl Sampler for OverdispersedSampler {
+    fn prob_infection(&self) -> Real {
+        self.prob_infection
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.prob_infection = value;
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        self.contacts
+    }
+
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        self.contacts = value;
+        return self;
+    }
+}
+
+impl<P> PopulationSampler<P> for OverdispersedSampler
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn sample_infection_pairs(&self, pop: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        self.each_infection_pair(pop, rng, |i, j| pairs.push((i, j)));
+        return pairs;
     }
 
-    // fn expected_infection_pairs(&self, pop: &P) -> Real {
-    //     let mut total = 0.0;
-    //     let prob = self.prob_infection;
-    //     let mut s = 0;
+    fn supports_population_growth(&self) -> bool {
+        true
+    }
 
-    //     pop.each_agent(&mut |_, st| {
-    //         let odds = st.contagion_odds();
-    //         s += st.is_susceptible() as usize;
-    //         total += (prob * odds).min(1.0);
-    //     });
+    /// Overdispersion does not change the mean number of contacts, so the
+    /// expectation is the same as in SimpleSampler.
+    fn expected_infection_pairs(&self, pop: &P) -> Real {
+        uniform_expected_pairs(self.contacts, self.prob_infection, pop)
+    }
+}
 
-    //     return total * self.contacts * (s as Real / pop.count() as Real);
-    // }
+/// Expected number of infection pairs when each contagious agent makes
+/// contacts with uniformly chosen agents, as in SimpleSampler.
+fn uniform_expected_pairs<P>(contacts: Real, prob: Real, pop: &P) -> Real
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    let n = pop.count();
+    if n == 0 {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    let mut s = 0;
+    pop.each_agent(&mut |_, st| {
+        s += is_exposable(st) as usize;
+        total += (prob * st.contagion_odds()).min(1.0);
+    });
+    return total * contacts * (s as Real / n as Real);
 }
 
 /// A simple sampling strategy that picks up a fixed number of contacts per
 /// infectious individual and infect randomly in population using the given
 /// probability of infection.
-#[derive(Debug, Clone, PartialEq, Getters, CopyGetters, Setters)]
+///
+/// Serialization skips the age_groups index, which depends on the population.
+/// It is rebuilt by PopulationSampler::init() when the sampler is attached to
+/// a population after loading.
+#[derive(Debug, Clone, PartialEq, Getters, CopyGetters, Setters, Serialize, Deserialize)]
 pub struct ContactMatrixSampler {
     /// Age range in each bin. Usually 10 years.
     age_range: u8,
@@ -241,17 +490,21 @@ pub struct ContactMatrixSampler {
     ///
     /// The vectors do not have the same length and the number of vectors is
     /// equal to the number of age groups.
+    #[serde(skip)]
     age_groups: Vec<Vec<usize>>,
 
+    n_contacts: Real,
+
+    /// Probability of infection for a single contact
+    prob_infection: Real,
+
     /// The contact matrix C[i, j] determines the average number of daily
     /// contacts an individual in age group i does with an individual in age
     /// group j.
+    ///
+    /// Declared last since TOML requires tables after plain values.
     #[getset(get = "pub", set = "pub")]
     contact_matrix: Array2<Real>,
-    n_contacts: Real,
-
-    /// Probability of infection for a single contact
-    prob_infection: Real,
 }
 
 impl ContactMatrixSampler {
@@ -324,9 +577,7 @@ where
         let nbins = self.n_bins();
         let bin_size = self.age_range;
 
-        for _ in 0..(255 / self.age_range) {
-            self.age_groups.push(vec![]);
-        }
+        self.age_groups = vec![vec![]; 255 / self.age_range as usize];
 
         pop.each_agent(&mut |i, st| {
             let k = Self::age_group_static(st.age(), bin_size, nbins);
@@ -343,17 +594,20 @@ where
             if odds > 0.0 {
                 let u = self.age_group(st.age());
                 for v in 0..self.n_bins() {
-                    let mut m = round_probabilistically(self.contact_matrix[(u, v)], rng);
-                    let group = &self.age_groups[v];
-                    while m > 0 {
+                    let m = round_probabilistically(self.contact_matrix[(u, v)], rng);
+                    // Contacts with empty age groups are dropped
+                    let group = match self.age_groups.get(v) {
+                        Some(group) if !group.is_empty() => group,
+                        _ => continue,
+                    };
+                    for _ in 0..m {
                         if rng.gen_bool((self.prob_infection * odds).min(1.0)) {
                             let j = group[rng.gen_range(0..group.len())];
-                            if i == j {
-                                continue;
+                            if i != j && pop.map_agent(j, |ag| st.can_contaminate(ag)) == Some(true)
+                            {
+                                pairs.push((i, j));
                             }
-                            pairs.push((i, j));
                         }
-                        m -= 1;
                     }
                 }
             }
@@ -361,12 +615,72 @@ where
         return pairs;
     }
 
-    fn expected_infection_pairs(&self, population: &P) -> Real {
-        let sampler = SimpleSampler::new(self.n_contacts(), self.prob_infection);
-        return sampler.expected_infection_pairs(population);
+    /// Expected number of infections of susceptible agents. Each contact of a
+    /// contagious agent with age group j is weighted by the susceptible
+    /// fraction of j, so saturated groups do not contribute.
+    ///
+    /// Like in sample_infection_pairs(), contacts are drawn from the whole
+    /// group and a draw of the agent itself consumes the contact. Contagious
+    /// agents are not susceptible, so they never count in the numerator.
+    fn expected_infection_pairs(&self, pop: &P) -> Real {
+        let susceptible: Vec<usize> = self
+            .age_groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .filter(|&&j| pop.map_agent(j, is_exposable) == Some(true))
+                    .count()
+            })
+            .collect();
+
+        let mut total = 0.0;
+        pop.each_agent(&mut |_, st| {
+            let odds = st.contagion_odds();
+            if odds > 0.0 {
+                let u = self.age_group(st.age());
+                let prob = (self.prob_infection * odds).min(1.0);
+                for (v, &n_susceptible) in susceptible.iter().enumerate().take(self.n_bins()) {
+                    let size = self.age_groups[v].len();
+                    if size > 0 {
+                        total += self.contact_matrix[(u, v)] * prob * n_susceptible as Real
+                            / size as Real;
+                    }
+                }
+            }
+        });
+        return total;
+    }
+
+    /// Hazard depends on the age group of agent, since contagious individuals
+    /// pick contacts uniformly from each age group.
+    fn infection_hazard(&self, pop: &P, id: usize) -> Real {
+        let v = match pop.map_agent(id, |ag| is_exposable(ag).then(|| ag.age())) {
+            Some(Some(age)) => self.age_group(age),
+            _ => return 0.0,
+        };
+        let size = self.age_groups.get(v).map_or(0, |g| g.len());
+        if size == 0 {
+            return 0.0;
+        }
+        let mut rate = 0.0;
+        pop.each_agent(&mut |i, st| {
+            let odds = st.contagion_odds();
+            if i != id && odds > 0.0 {
+                let u = self.age_group(st.age());
+                rate += self.contact_matrix[(u, v)] * (self.prob_infection * odds).min(1.0);
+            }
+        });
+        return 1.0 - (-rate / size as Real).exp();
     }
 }
 
+/// True if agent is susceptible and not isolated, i.e., if it can be infected
+/// in a contact.
+fn is_exposable<S: EpiModel>(st: &S) -> bool {
+    st.is_susceptible() && !st.is_isolated()
+}
+
 fn round_probabilistically(f: Real, rng: &mut impl Rng) -> usize {
     let int = f as usize;
     if rng.gen_bool(f - (int as Real)) {
@@ -375,81 +689,1734 @@ fn round_probabilistically(f: Real, rng: &mut impl Rng) -> usize {
     return int;
 }
 
-/// TODO: impl PythonSampler and use dyn to make this go away!
-#[derive(Debug, Clone, PartialEq)]
-pub enum AnySampler {
-    Simple(SimpleSampler),
-    ContactMatrix(ContactMatrixSampler),
+/// Agents are grouped in households and meet all members of their household
+/// every day, in addition to contacts() random contacts in the community.
+///
+/// Each contact, inside or outside the household, transmits infection with
+/// probability prob_infection(). Agents that are not assigned to a household
+/// only have community contacts.
+#[derive(Debug, Clone, PartialEq, Getters)]
+pub struct HouseholdSampler {
+    /// List of agents in each household.
+    #[getset(get = "pub")]
+    households: Vec<Vec<usize>>,
+    household_of: Vec<Option<usize>>,
+    community: SimpleSampler,
 }
 
-impl Sampler for AnySampler {
-    fn prob_infection(&self) -> Real {
-        match self {
-            AnySampler::Simple(s) => s.prob_infection(),
-            AnySampler::ContactMatrix(s) => s.prob_infection(),
+impl HouseholdSampler {
+    /// Create sampler from explicit household membership lists.
+    pub fn new(households: Vec<Vec<usize>>, contacts: Real, prob_infection: Real) -> Self {
+        let n = households
+            .iter()
+            .flatten()
+            .map(|&i| i + 1)
+            .max()
+            .unwrap_or(0);
+        let mut household_of = vec![None; n];
+        for (k, members) in households.iter().enumerate() {
+            for &i in members {
+                household_of[i] = Some(k);
+            }
+        }
+        HouseholdSampler {
+            households,
+            household_of,
+            community: SimpleSampler::new(contacts, prob_infection),
         }
     }
 
-    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
-        match *self {
-            AnySampler::Simple(ref mut s) => {
-                s.set_prob_infection(value);
-            }
-            AnySampler::ContactMatrix(ref mut s) => {
-                s.set_prob_infection(value);
-            }
+    /// Place n_agents in households with sizes drawn from the given list of
+    /// (size, probability) pairs, as household sizes are usually reported in
+    /// census data. Probabilities do not need to be normalized. Agents are
+    /// shuffled before placement and the last household is truncated to fit
+    /// the remaining agents.
+    ///
+    /// Community contacts and the probability of infection start at zero and
+    /// should be set with set_contacts() and set_prob_infection().
+    ///
+    /// Return an error if the list is empty or probabilities are invalid (e.g.,
+    /// negative or all zero).
+    pub fn from_size_distribution(
+        sizes: &[(usize, Real)],
+        n_agents: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let distrib = WeightedIndex::new(sizes.iter().map(|(_, p)| *p))
+            .map_err(|e| format!("invalid household size distribution: {}", e))?;
+        let mut agents: Vec<usize> = (0..n_agents).collect();
+        agents.shuffle(rng);
+
+        let mut households = vec![];
+        let mut rest = &agents[..];
+        while !rest.is_empty() {
+            let size = sizes[distrib.sample(rng)].0.clamp(1, rest.len());
+            households.push(rest[..size].to_vec());
+            rest = &rest[size..];
+        }
+        return Ok(Self::new(households, 0.0, 0.0));
+    }
+
+    /// Mean number of agents per household.
+    pub fn mean_household_size(&self) -> Real {
+        let n: usize = self.households.iter().map(|h| h.len()).sum();
+        return n as Real / self.households.len() as Real;
+    }
+
+    /// Members of the household of agent i, including i.
+    pub fn household(&self, i: usize) -> &[usize] {
+        match self.household_of.get(i) {
+            Some(Some(k)) => &self.households[*k],
+            _ => &[],
         }
+    }
+}
+
+impl Sampler for HouseholdSampler {
+    fn prob_infection(&self) -> Real {
+        self.community.prob_infection()
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.community.set_prob_infection(value);
         return self;
     }
 
+    /// Number of community contacts per day. Household contacts are not
+    /// included.
     fn contacts(&self) -> Real {
-        match self {
-            AnySampler::Simple(s) => s.contacts(),
-            AnySampler::ContactMatrix(s) => s.contacts(),
-        }
+        self.community.contacts()
     }
 
     fn set_contacts(&mut self, value: Real) -> &mut Self {
-        match self {
-            AnySampler::Simple(s) => {
-                s.set_contacts(value);
-            }
-            AnySampler::ContactMatrix(s) => {
-                s.set_contacts(value);
-            }
-        };
+        self.community.set_contacts(value);
         return self;
     }
 }
 
-impl<P> PopulationSampler<P> for AnySampler
+impl<P> PopulationSampler<P> for HouseholdSampler
 where
     P: Population,
-    P::State: HasAge + EpiModel,
+    P::State: EpiModel,
 {
-    fn sample_infection_pairs(&self, pool: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
-        match self {
-            AnySampler::Simple(s) => s.sample_infection_pairs(pool, rng),
-            AnySampler::ContactMatrix(s) => s.sample_infection_pairs(pool, rng),
+    fn sample_infection_pairs(&self, pop: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        let prob = self.prob_infection();
+        pop.each_agent(&mut |i, st| {
+            let odds = st.contagion_odds();
+            if odds > 0.0 {
+                for &j in self.household(i) {
+                    if i != j
+                        && pop.map_agent(j, |ag| st.can_contaminate(ag)) == Some(true)
+                        && rng.gen_bool((prob * odds).min(1.0))
+                    {
+                        pairs.push((i, j));
+                    }
+                }
+            }
+        });
+        self.community
+            .each_infection_pair(pop, rng, |i, j| pairs.push((i, j)));
+        return pairs;
+    }
+}
+
+/// Contacts occur only along the edges of a static small-world network.
+///
+/// The network is a Watts-Strogatz graph: a ring lattice in which each agent
+/// is connected to its mean_degree nearest neighbors, with each edge rewired
+/// to a random agent with probability beta. It is built by init() once the
+/// population size is known, so the same pairs meet every day. Agents
+/// without neighbors (including agents added after init) have no contacts.
+///
+/// Networks can also be loaded from edge lists with from_edges() or
+/// from_csv(). Those are never re-sampled.
+#[derive(Debug, Clone, Getters, CopyGetters)]
+pub struct NetworkSampler {
+    #[getset(get_copy = "pub")]
+    beta: Real,
+    #[getset(get_copy = "pub")]
+    prob_infection: Real,
+    /// Half of the lattice degree, or None for networks loaded from data.
+    half_degree: Option<usize>,
+
+    /// Neighbors of each agent.
+    #[getset(get = "pub")]
+    adjacency: Vec<Vec<usize>>,
+    rng: SmallRng,
+}
+
+impl NetworkSampler {
+    /// Create a sampler for a Watts-Strogatz network with the given mean
+    /// degree (rounded to an even number) and rewiring probability. The
+    /// network is built from rng when the sampler is initialized.
+    pub fn watts_strogatz(mean_degree: Real, beta: Real, rng: &mut impl Rng) -> Self {
+        NetworkSampler {
+            beta,
+            prob_infection: 1.0,
+            half_degree: Some((mean_degree / 2.0).round().max(0.0) as usize),
+            adjacency: vec![],
+            rng: SmallRng::from_rng(rng).unwrap(),
         }
     }
 
-    fn init(&mut self, pool: &mut P) {
-        match self {
-            AnySampler::Simple(s) => s.init(pool),
-            AnySampler::ContactMatrix(s) => s.init(pool),
+    /// Create a sampler for a network of n agents from a list of undirected
+    /// edges. Duplicate edges and self loops are ignored.
+    ///
+    /// Return an error reporting the first id that is not smaller than n.
+    pub fn from_edges(
+        n: usize,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<Self, String> {
+        let mut adjacency = vec![vec![]; n];
+        let mut seen = HashSet::new();
+        for (i, j) in edges {
+            if let Some(id) = [i, j].iter().find(|&&id| id >= n) {
+                return Err(format!("invalid id {} in network of {} agents", id, n));
+            }
+            if i != j && seen.insert((i.min(j), i.max(j))) {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+            }
         }
+        return Ok(NetworkSampler {
+            beta: 0.0,
+            prob_infection: 1.0,
+            half_degree: None,
+            adjacency,
+            rng: SmallRng::seed_from_u64(0),
+        });
     }
-}
 
-impl From<SimpleSampler> for AnySampler {
-    fn from(sampler: SimpleSampler) -> AnySampler {
-        AnySampler::Simple(sampler)
+    /// Read network from a CSV file with two integer columns with the ids of
+    /// each edge. A header row is skipped if present and the number of agents
+    /// is inferred from the largest id.
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Self, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| e.to_string())?;
+
+        let mut edges = vec![];
+        for (line, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| e.to_string())?;
+            let parse = |k: usize| record.get(k).and_then(|x| x.parse::<usize>().ok());
+            match (parse(0), parse(1)) {
+                (Some(i), Some(j)) => edges.push((i, j)),
+                _ if line == 0 => continue,
+                _ => return Err(format!("invalid edge at line {}", line + 1)),
+            }
+        }
+        let n = edges.iter().map(|&(i, j)| i.max(j) + 1).max().unwrap_or(0);
+        return Self::from_edges(n, edges);
+    }
+
+    /// Rebuild network for a population of n agents.
+    fn build(&mut self, n: usize) {
+        let k = match self.half_degree {
+            Some(k) => k.min(n.saturating_sub(1) / 2),
+            None => return,
+        };
+        let mut adjacency = vec![Vec::with_capacity(2 * k); n];
+        for i in 0..n {
+            for d in 1..=k {
+                let j = (i + d) % n;
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+            }
+        }
+
+        for i in 0..n {
+            for d in 1..=k {
+                let j = (i + d) % n;
+                if !self.rng.gen_bool(self.beta) || !adjacency[i].contains(&j) {
+                    continue;
+                }
+                // Saturated nodes cannot be rewired
+                if adjacency[i].len() >= n - 1 {
+                    continue;
+                }
+                let mut w = self.rng.gen_range(0..n);
+                while w == i || adjacency[i].contains(&w) {
+                    w = self.rng.gen_range(0..n);
+                }
+                adjacency[i].retain(|&x| x != j);
+                adjacency[j].retain(|&x| x != i);
+                adjacency[i].push(w);
+                adjacency[w].push(i);
+            }
+        }
+        self.adjacency = adjacency;
     }
 }
 
-impl From<ContactMatrixSampler> for AnySampler {
-    fn from(sampler: ContactMatrixSampler) -> AnySampler {
-        AnySampler::ContactMatrix(sampler)
+impl Sampler for NetworkSampler {
+    fn prob_infection(&self) -> Real {
+        self.prob_infection
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.prob_infection = value;
+        return self;
+    }
+
+    /// Mean degree of the network.
+    fn contacts(&self) -> Real {
+        if self.adjacency.is_empty() {
+            return 2.0 * self.half_degree.unwrap_or(0) as Real;
+        }
+        let total: usize = self.adjacency.iter().map(|v| v.len()).sum();
+        return total as Real / self.adjacency.len() as Real;
+    }
+
+    /// Re-sample the network with the new mean degree, if it changes.
+    /// Networks loaded from data are not modified.
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        let half_degree = (value / 2.0).round().max(0.0) as usize;
+        if self.half_degree.is_some_and(|k| k != half_degree) {
+            self.half_degree = Some(half_degree);
+            if !self.adjacency.is_empty() {
+                self.build(self.adjacency.len());
+            }
+        }
+        return self;
+    }
+}
+
+impl<P> PopulationSampler<P> for NetworkSampler
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn init(&mut self, pop: &mut P) {
+        if self.half_degree.is_some() && self.adjacency.len() != pop.count() {
+            self.build(pop.count());
+        }
+    }
+
+    fn sample_infection_pairs(&self, pop: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        sample_network_pairs(&self.adjacency, self.prob_infection, pop, rng)
+    }
+
+    /// Only contagious neighbors contribute to the hazard.
+    fn infection_hazard(&self, pop: &P, id: usize) -> Real {
+        network_hazard(&self.adjacency, self.prob_infection, pop, id)
+    }
+}
+
+/// Contacts occur only along the edges of a static scale-free network.
+///
+/// The network is grown by preferential attachment (Barabasi-Albert model):
+/// it starts as a complete graph of m + 1 agents and each new agent connects
+/// to m distinct agents chosen with probability proportional to their degree.
+/// The resulting degree distribution has a power-law tail, which is useful to
+/// study super-spreading. If the population has no more than m + 1 agents,
+/// the network is a complete graph.
+#[derive(Debug, Clone, Getters, CopyGetters)]
+pub struct BarabasiAlbertSampler {
+    /// Number of edges of each new agent.
+    #[getset(get_copy = "pub")]
+    m: usize,
+    #[getset(get_copy = "pub")]
+    prob_infection: Real,
+
+    /// Neighbors of each agent.
+    #[getset(get = "pub")]
+    adjacency: Vec<Vec<usize>>,
+    rng: SmallRng,
+}
+
+impl BarabasiAlbertSampler {
+    /// Create sampler with m edges per new agent. The network is built from
+    /// rng when the sampler is initialized.
+    pub fn new(m: usize, rng: &mut impl Rng) -> Self {
+        BarabasiAlbertSampler {
+            m,
+            prob_infection: 1.0,
+            adjacency: vec![],
+            rng: SmallRng::from_rng(rng).unwrap(),
+        }
+    }
+
+    /// Number of agents with each degree, i.e., histogram[k] is the number of
+    /// agents with k neighbors.
+    pub fn degree_histogram(&self) -> Vec<usize> {
+        let max = self.adjacency.iter().map(|v| v.len()).max().unwrap_or(0);
+        let mut histogram = vec![0; max + 1];
+        for neighbors in self.adjacency.iter() {
+            histogram[neighbors.len()] += 1;
+        }
+        return histogram;
+    }
+
+    /// Rebuild network for a population of n agents.
+    fn build(&mut self, n: usize) {
+        let core = (self.m + 1).min(n);
+        let mut adjacency = vec![vec![]; n];
+
+        // Each node appears in endpoints once per edge, so sampling uniformly
+        // from it selects nodes proportionally to their degree.
+        let mut endpoints = vec![];
+        for i in 0..core {
+            for j in 0..i {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+                endpoints.extend_from_slice(&[i, j]);
+            }
+        }
+
+        for i in core..n {
+            // There are at least m + 1 distinct nodes in endpoints, so this
+            // loop always terminates
+            let mut targets = Vec::with_capacity(self.m);
+            while targets.len() < self.m {
+                let j = endpoints[self.rng.gen_range(0..endpoints.len())];
+                if !targets.contains(&j) {
+                    targets.push(j);
+                }
+            }
+            for j in targets {
+                adjacency[i].push(j);
+                adjacency[j].push(i);
+                endpoints.extend_from_slice(&[i, j]);
+            }
+        }
+        self.adjacency = adjacency;
+    }
+}
+
+impl Sampler for BarabasiAlbertSampler {
+    fn prob_infection(&self) -> Real {
+        self.prob_infection
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.prob_infection = value;
+        return self;
+    }
+
+    /// Mean degree of the network.
+    fn contacts(&self) -> Real {
+        if self.adjacency.is_empty() {
+            return 2.0 * self.m as Real;
+        }
+        let total: usize = self.adjacency.iter().map(|v| v.len()).sum();
+        return total as Real / self.adjacency.len() as Real;
+    }
+
+    /// Re-sample the network with m = value / 2, since the mean degree of
+    /// large networks is 2m.
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        let m = (value / 2.0).round().max(0.0) as usize;
+        if m != self.m {
+            self.m = m;
+            if !self.adjacency.is_empty() {
+                self.build(self.adjacency.len());
+            }
+        }
+        return self;
+    }
+}
+
+impl<P> PopulationSampler<P> for BarabasiAlbertSampler
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn init(&mut self, pop: &mut P) {
+        if self.adjacency.len() != pop.count() {
+            self.build(pop.count());
+        }
+    }
+
+    fn sample_infection_pairs(&self, pop: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        sample_network_pairs(&self.adjacency, self.prob_infection, pop, rng)
+    }
+
+    /// Only contagious neighbors contribute to the hazard.
+    fn infection_hazard(&self, pop: &P, id: usize) -> Real {
+        network_hazard(&self.adjacency, self.prob_infection, pop, id)
+    }
+}
+
+/// Each contagious agent infects each of its susceptible neighbors with
+/// probability prob * contagion_odds.
+fn sample_network_pairs<P>(
+    adjacency: &[Vec<usize>],
+    prob: Real,
+    pop: &P,
+    rng: &mut impl Rng,
+) -> Vec<(usize, usize)>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    let mut pairs = Vec::new();
+    pop.each_agent(&mut |i, st| {
+        let odds = st.contagion_odds();
+        if odds > 0.0 {
+            let prob = (prob * odds).min(1.0);
+            for &j in adjacency.get(i).map_or(&[][..], |v| &v[..]) {
+                if pop.map_agent(j, |ag| st.can_contaminate(ag)) == Some(true) && rng.gen_bool(prob)
+                {
+                    pairs.push((i, j));
+                }
+            }
+        }
+    });
+    return pairs;
+}
+
+/// Probability that a susceptible agent is infected by any of its neighbors.
+fn network_hazard<P>(adjacency: &[Vec<usize>], prob: Real, pop: &P, id: usize) -> Real
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    if pop.map_agent(id, is_exposable) != Some(true) {
+        return 0.0;
+    }
+    let mut escape = 1.0;
+    for &j in adjacency.get(id).map_or(&[][..], |v| &v[..]) {
+        if let Some(odds) = pop.map_agent(j, |ag| ag.contagion_odds()) {
+            escape *= 1.0 - (prob * odds).min(1.0);
+        }
+    }
+    return 1.0 - escape;
+}
+
+/// Switch between samplers according to a schedule of interventions.
+///
+/// The schedule is a list of (start_step, sampler) pairs and the active
+/// sampler at each step is the one with the latest start not after the
+/// current step (or the first one, before the schedule starts). Methods that
+/// do not receive the step refer to the sampler active at the last sampled
+/// step.
+#[derive(Debug, Clone)]
+pub struct ScheduledSampler<S> {
+    schedule: Vec<(usize, S)>,
+    step: Cell<usize>,
+}
+
+impl<S> ScheduledSampler<S> {
+    /// Create sampler from a non-empty schedule.
+    pub fn new(schedule: Vec<(usize, S)>) -> Self {
+        assert!(!schedule.is_empty(), "Schedule must not be empty");
+        let mut schedule = schedule;
+        schedule.sort_by_key(|(start, _)| *start);
+        ScheduledSampler {
+            schedule,
+            step: Cell::new(0),
+        }
+    }
+
+    /// List of (start_step, sampler) pairs, sorted by start.
+    pub fn schedule(&self) -> &[(usize, S)] {
+        &self.schedule
+    }
+
+    /// Sampler active at the given step.
+    pub fn active_at(&self, step: usize) -> &S {
+        let n = self
+            .schedule
+            .iter()
+            .take_while(|(start, _)| *start <= step)
+            .count();
+        return &self.schedule[n.max(1) - 1].1;
+    }
+
+    fn active(&self) -> &S {
+        self.active_at(self.step.get())
+    }
+}
+
+impl<S: Sampler> Sampler for ScheduledSampler<S> {
+    fn prob_infection(&self) -> Real {
+        self.active().prob_infection()
+    }
+
+    /// Set the probability of infection of all samplers in schedule.
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        for (_, sampler) in self.schedule.iter_mut() {
+            sampler.set_prob_infection(value);
+        }
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        self.active().contacts()
+    }
+
+    /// Rescale contacts of all samplers in schedule by the same factor, so
+    /// the active one has the given number of contacts.
+    ///
+    /// If the active sampler has no contacts (e.g., a full lockdown), the
+    /// factor is undefined and all samplers are set to the given value.
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        let current = self.contacts();
+        for (_, sampler) in self.schedule.iter_mut() {
+            if current > 0.0 {
+                let contacts = sampler.contacts();
+                sampler.set_contacts(contacts * value / current);
+            } else {
+                sampler.set_contacts(value);
+            }
+        }
+        return self;
+    }
+}
+
+impl<P, S> PopulationSampler<P> for ScheduledSampler<S>
+where
+    P: Population,
+    S: PopulationSampler<P>,
+{
+    fn init(&mut self, population: &mut P) {
+        for (_, sampler) in self.schedule.iter_mut() {
+            sampler.init(population);
+        }
+    }
+
+    fn sample_infection_pairs(&self, population: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        self.active().sample_infection_pairs(population, rng)
+    }
+
+    fn sample_infection_pairs_at(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        self.step.set(step);
+        self.active_at(step)
+            .sample_infection_pairs_at(step, population, rng)
+    }
+
+    fn advance_step(&mut self) {
+        for (_, sampler) in self.schedule.iter_mut() {
+            sampler.advance_step();
+        }
+    }
+
+    fn supports_population_growth(&self) -> bool {
+        self.schedule
+            .iter()
+            .all(|(_, sampler)| sampler.supports_population_growth())
+    }
+
+    fn expected_infection_pairs(&self, population: &P) -> Real {
+        self.active().expected_infection_pairs(population)
+    }
+
+    fn infection_hazard(&self, population: &P, id: usize) -> Real
+    where
+        P::State: EpiModel,
+    {
+        self.active().infection_hazard(population, id)
+    }
+}
+
+/// Modulate the probability of infection of a sampler by a weekly pattern.
+///
+/// The probability of infection at step t is base * factors[t % 7], clamped
+/// to [0, 1]. This is useful to model the drop of contacts on weekends. Like
+/// SeasonalSampler, the step is advanced by advance_step(), which simulations
+/// call once per step.
+#[derive(Debug, Clone)]
+pub struct PeriodicModulationSampler<S> {
+    sampler: S,
+    base: Real,
+    factors: [Real; 7],
+    step: usize,
+}
+
+impl<S: Sampler> PeriodicModulationSampler<S> {
+    /// Wrap sampler, using its current probability of infection as the
+    /// baseline.
+    pub fn new(sampler: S, factors: [Real; 7]) -> Self {
+        let base = sampler.prob_infection();
+        let mut new = PeriodicModulationSampler {
+            sampler,
+            base,
+            factors,
+            step: 0,
+        };
+        new.update_prob_infection();
+        return new;
+    }
+
+    /// Wrapped sampler.
+    pub fn sampler(&self) -> &S {
+        &self.sampler
+    }
+
+    /// Weekly modulation factors, indexed by step % 7.
+    pub fn factors(&self) -> &[Real; 7] {
+        &self.factors
+    }
+
+    /// Baseline probability of infection.
+    pub fn base_prob_infection(&self) -> Real {
+        self.base
+    }
+
+    /// Probability of infection at the given step.
+    pub fn prob_infection_at(&self, step: usize) -> Real {
+        (self.base * self.factors[step % 7]).clamp(0.0, 1.0)
+    }
+
+    /// Return the wrapped sampler with the baseline probability of infection.
+    pub fn into_inner(self) -> S {
+        let mut sampler = self.sampler;
+        sampler.set_prob_infection(self.base);
+        return sampler;
+    }
+
+    fn update_prob_infection(&mut self) {
+        let prob = self.prob_infection_at(self.step);
+        self.sampler.set_prob_infection(prob);
+    }
+}
+
+impl<S: Sampler> Sampler for PeriodicModulationSampler<S> {
+    fn prob_infection(&self) -> Real {
+        self.sampler.prob_infection()
+    }
+
+    /// Set the baseline probability of infection.
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.base = value;
+        self.update_prob_infection();
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        self.sampler.contacts()
+    }
+
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        self.sampler.set_contacts(value);
+        return self;
+    }
+}
+
+impl<P, S> PopulationSampler<P> for PeriodicModulationSampler<S>
+where
+    P: Population,
+    S: PopulationSampler<P>,
+{
+    fn init(&mut self, population: &mut P) {
+        self.sampler.init(population);
+    }
+
+    fn sample_infection_pairs(&self, population: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        self.sampler.sample_infection_pairs(population, rng)
+    }
+
+    fn sample_infection_pairs_at(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        self.sampler
+            .sample_infection_pairs_at(step, population, rng)
+    }
+
+    fn advance_step(&mut self) {
+        self.step += 1;
+        self.update_prob_infection();
+        self.sampler.advance_step();
+    }
+
+    fn supports_population_growth(&self) -> bool {
+        self.sampler.supports_population_growth()
+    }
+
+    fn expected_infection_pairs(&self, population: &P) -> Real {
+        self.sampler.expected_infection_pairs(population)
+    }
+
+    fn infection_hazard(&self, population: &P, id: usize) -> Real
+    where
+        P::State: EpiModel,
+    {
+        self.sampler.infection_hazard(population, id)
+    }
+}
+
+/// Seasonal forcing of the probability of infection of a sampler.
+///
+/// At step t, the probability of infection of the wrapped sampler is set to
+/// base * (1 + amplitude * sin(2π t / period + phase)), clamped to [0, 1].
+#[derive(Debug, Clone)]
+pub struct SeasonalSampler<S> {
+    sampler: S,
+    base: Real,
+    amplitude: Real,
+    period: Real,
+    phase: Real,
+    step: usize,
+}
+
+impl<S: Sampler> SeasonalSampler<S> {
+    /// Wrap sampler, using its current probability of infection as the
+    /// baseline. Period is measured in steps and phase in radians.
+    pub fn new(sampler: S, amplitude: Real, period: Real, phase: Real) -> Self {
+        let base = sampler.prob_infection();
+        let mut new = SeasonalSampler {
+            sampler,
+            base,
+            amplitude,
+            period,
+            phase,
+            step: 0,
+        };
+        new.update_prob_infection();
+        return new;
+    }
+
+    /// Wrapped sampler.
+    pub fn sampler(&self) -> &S {
+        &self.sampler
+    }
+
+    /// Baseline probability of infection.
+    pub fn base_prob_infection(&self) -> Real {
+        self.base
+    }
+
+    /// Probability of infection at the given step.
+    pub fn prob_infection_at(&self, step: usize) -> Real {
+        let angle = 2.0 * std::f64::consts::PI * step as Real / self.period + self.phase;
+        let prob = self.base * (1.0 + self.amplitude * angle.sin());
+        return prob.clamp(0.0, 1.0);
+    }
+
+    fn update_prob_infection(&mut self) {
+        let prob = self.prob_infection_at(self.step);
+        self.sampler.set_prob_infection(prob);
+    }
+}
+
+impl<S: Sampler> Sampler for SeasonalSampler<S> {
+    fn prob_infection(&self) -> Real {
+        self.sampler.prob_infection()
+    }
+
+    /// Set the baseline probability of infection.
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.base = value;
+        self.update_prob_infection();
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        self.sampler.contacts()
+    }
+
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        self.sampler.set_contacts(value);
+        return self;
+    }
+}
+
+impl<P, S> PopulationSampler<P> for SeasonalSampler<S>
+where
+    P: Population,
+    S: PopulationSampler<P>,
+{
+    fn init(&mut self, population: &mut P) {
+        self.sampler.init(population);
+    }
+
+    fn sample_infection_pairs(&self, population: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        self.sampler.sample_infection_pairs(population, rng)
+    }
+
+    fn sample_infection_pairs_at(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        self.sampler
+            .sample_infection_pairs_at(step, population, rng)
+    }
+
+    fn advance_step(&mut self) {
+        self.step += 1;
+        self.update_prob_infection();
+        self.sampler.advance_step();
+    }
+
+    fn supports_population_growth(&self) -> bool {
+        self.sampler.supports_population_growth()
+    }
+
+    fn expected_infection_pairs(&self, population: &P) -> Real {
+        self.sampler.expected_infection_pairs(population)
+    }
+
+    fn infection_hazard(&self, population: &P, id: usize) -> Real
+    where
+        P::State: EpiModel,
+    {
+        self.sampler.infection_hazard(population, id)
+    }
+}
+
+/// Object-safe version of PopulationSampler.
+///
+/// Methods mirror those of Sampler and PopulationSampler with a dyn_ prefix,
+/// to avoid ambiguities, and receive a `&mut dyn RngCore` rather than a
+/// generic rng. It is implemented for every PopulationSampler, so it is
+/// usually only necessary through BoxedSampler.
+pub trait DynPopulationSampler<P>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn dyn_prob_infection(&self) -> Real;
+
+    fn dyn_set_prob_infection(&mut self, value: Real);
+
+    fn dyn_contacts(&self) -> Real;
+
+    fn dyn_set_contacts(&mut self, value: Real);
+
+    fn dyn_init(&mut self, population: &mut P);
+
+    fn dyn_sample_infection_pairs(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(usize, usize)>;
+
+    fn dyn_advance_step(&mut self);
+
+    fn dyn_supports_population_growth(&self) -> bool;
+
+    fn dyn_expected_infection_pairs(&self, population: &P) -> Real;
+
+    fn dyn_infection_hazard(&self, population: &P, id: usize) -> Real;
+}
+
+impl<P, S> DynPopulationSampler<P> for S
+where
+    P: Population,
+    P::State: EpiModel,
+    S: PopulationSampler<P>,
+{
+    fn dyn_prob_infection(&self) -> Real {
+        self.prob_infection()
+    }
+
+    fn dyn_set_prob_infection(&mut self, value: Real) {
+        self.set_prob_infection(value);
+    }
+
+    fn dyn_contacts(&self) -> Real {
+        self.contacts()
+    }
+
+    fn dyn_set_contacts(&mut self, value: Real) {
+        self.set_contacts(value);
+    }
+
+    fn dyn_init(&mut self, population: &mut P) {
+        self.init(population);
+    }
+
+    fn dyn_sample_infection_pairs(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(usize, usize)> {
+        let mut rng = rng;
+        self.sample_infection_pairs_at(step, population, &mut rng)
+    }
+
+    fn dyn_advance_step(&mut self) {
+        self.advance_step();
+    }
+
+    fn dyn_supports_population_growth(&self) -> bool {
+        self.supports_population_growth()
+    }
+
+    fn dyn_expected_infection_pairs(&self, population: &P) -> Real {
+        self.expected_infection_pairs(population)
+    }
+
+    fn dyn_infection_hazard(&self, population: &P, id: usize) -> Real {
+        self.infection_hazard(population, id)
+    }
+}
+
+/// A sampler that uses dynamic dispatch to select between arbitrary
+/// PopulationSampler implementations at runtime.
+///
+/// Unlike AnySampler, it accepts any sampler without modifications to this
+/// crate, but it cannot be cloned or serialized.
+pub struct BoxedSampler<P> {
+    sampler: Box<dyn DynPopulationSampler<P>>,
+}
+
+impl<P> BoxedSampler<P>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    pub fn new<S>(sampler: S) -> Self
+    where
+        S: PopulationSampler<P> + 'static,
+    {
+        BoxedSampler {
+            sampler: Box::new(sampler),
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for BoxedSampler<P>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoxedSampler")
+            .field("contacts", &self.sampler.dyn_contacts())
+            .field("prob_infection", &self.sampler.dyn_prob_infection())
+            .finish()
+    }
+}
+
+impl<P> Sampler for BoxedSampler<P>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn prob_infection(&self) -> Real {
+        self.sampler.dyn_prob_infection()
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        self.sampler.dyn_set_prob_infection(value);
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        self.sampler.dyn_contacts()
+    }
+
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        self.sampler.dyn_set_contacts(value);
+        return self;
+    }
+}
+
+impl<P> PopulationSampler<P> for BoxedSampler<P>
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn init(&mut self, population: &mut P) {
+        self.sampler.dyn_init(population);
+    }
+
+    fn sample_infection_pairs(&self, population: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        self.sampler.dyn_sample_infection_pairs(0, population, rng)
+    }
+
+    fn sample_infection_pairs_at(
+        &self,
+        step: usize,
+        population: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        self.sampler
+            .dyn_sample_infection_pairs(step, population, rng)
+    }
+
+    fn advance_step(&mut self) {
+        self.sampler.dyn_advance_step();
+    }
+
+    fn supports_population_growth(&self) -> bool {
+        self.sampler.dyn_supports_population_growth()
+    }
+
+    fn expected_infection_pairs(&self, population: &P) -> Real {
+        self.sampler.dyn_expected_infection_pairs(population)
+    }
+
+    fn infection_hazard(&self, population: &P, id: usize) -> Real {
+        self.sampler.dyn_infection_hazard(population, id)
+    }
+}
+
+/// Enumeration of the serializable samplers.
+///
+/// This is useful to store the sampler in configuration files. For runtime
+/// polymorphism over arbitrary samplers, use BoxedSampler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum AnySampler {
+    Simple(SimpleSampler),
+    ContactMatrix(ContactMatrixSampler),
+}
+
+impl Sampler for AnySampler {
+    fn prob_infection(&self) -> Real {
+        match self {
+            AnySampler::Simple(s) => s.prob_infection(),
+            AnySampler::ContactMatrix(s) => s.prob_infection(),
+        }
+    }
+
+    fn set_prob_infection(&mut self, value: Real) -> &mut Self {
+        match *self {
+            AnySampler::Simple(ref mut s) => {
+                s.set_prob_infection(value);
+            }
+            AnySampler::ContactMatrix(ref mut s) => {
+                s.set_prob_infection(value);
+            }
+        }
+        return self;
+    }
+
+    fn contacts(&self) -> Real {
+        match self {
+            AnySampler::Simple(s) => s.contacts(),
+            AnySampler::ContactMatrix(s) => s.contacts(),
+        }
+    }
+
+    fn set_contacts(&mut self, value: Real) -> &mut Self {
+        match self {
+            AnySampler::Simple(s) => {
+                s.set_contacts(value);
+            }
+            AnySampler::ContactMatrix(s) => {
+                s.set_contacts(value);
+            }
+        };
+        return self;
+    }
+}
+
+impl<P> PopulationSampler<P> for AnySampler
+where
+    P: Population,
+    P::State: HasAge + EpiModel,
+{
+    fn sample_infection_pairs(&self, pool: &P, rng: &mut impl Rng) -> Vec<(usize, usize)> {
+        match self {
+            AnySampler::Simple(s) => s.sample_infection_pairs(pool, rng),
+            AnySampler::ContactMatrix(s) => s.sample_infection_pairs(pool, rng),
+        }
+    }
+
+    fn sample_infection_pairs_at(
+        &self,
+        step: usize,
+        pool: &P,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        match self {
+            AnySampler::Simple(s) => s.sample_infection_pairs_at(step, pool, rng),
+            AnySampler::ContactMatrix(s) => s.sample_infection_pairs_at(step, pool, rng),
+        }
+    }
+
+    fn advance_step(&mut self) {
+        match self {
+            AnySampler::Simple(s) => PopulationSampler::<P>::advance_step(s),
+            AnySampler::ContactMatrix(s) => PopulationSampler::<P>::advance_step(s),
+        }
+    }
+
+    fn expected_infection_pairs(&self, pool: &P) -> Real {
+        match self {
+            AnySampler::Simple(s) => s.expected_infection_pairs(pool),
+            AnySampler::ContactMatrix(s) => s.expected_infection_pairs(pool),
+        }
+    }
+
+    fn init(&mut self, pool: &mut P) {
+        match self {
+            AnySampler::Simple(s) => s.init(pool),
+            AnySampler::ContactMatrix(s) => s.init(pool),
+        }
+    }
+
+    fn supports_population_growth(&self) -> bool {
+        match self {
+            AnySampler::Simple(s) => PopulationSampler::<P>::supports_population_growth(s),
+            AnySampler::ContactMatrix(s) => PopulationSampler::<P>::supports_population_growth(s),
+        }
+    }
+
+    fn infection_hazard(&self, pool: &P, id: usize) -> Real {
+        match self {
+            AnySampler::Simple(s) => s.infection_hazard(pool, id),
+            AnySampler::ContactMatrix(s) => s.infection_hazard(pool, id),
+        }
+    }
+}
+
+impl From<SimpleSampler> for AnySampler {
+    fn from(sampler: SimpleSampler) -> AnySampler {
+        AnySampler::Simple(sampler)
+    }
+}
+
+impl From<ContactMatrixSampler> for AnySampler {
+    fn from(sampler: ContactMatrixSampler) -> AnySampler {
+        AnySampler::ContactMatrix(sampler)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{SEIRm, SeirAgent, SEIR},
+        params::EpiParamsLocal,
+        sim::HasEpiModel,
+        utils::seeded_rng,
+    };
+    use assert_approx_eq::assert_approx_eq;
+
+    #[derive(Serialize, Deserialize)]
+    struct Bundle {
+        sampler: AnySampler,
+    }
+
+    fn round_trip(sampler: &AnySampler) -> AnySampler {
+        let bundle = Bundle {
+            sampler: sampler.clone(),
+        };
+        let data = toml::to_string(&bundle).unwrap();
+        return toml::from_str::<Bundle>(&data).unwrap().sampler;
+    }
+
+    fn ring_distance(i: usize, j: usize, n: usize) -> usize {
+        let d = i.abs_diff(j);
+        return d.min(n - d);
+    }
+
+    #[test]
+    fn simple_sampler_terminates_without_valid_partners() {
+        let sampler = SimpleSampler::new(10.0, 1.0);
+        let rng = &mut seeded_rng(42u64);
+        let pop = vec![SEIRm::Infectious(())];
+        assert!(sampler.sample_infection_pairs(&pop, rng).is_empty());
+
+        let pop = vec![SEIRm::Infectious(()), SEIR::Dead(())];
+        assert!(sampler.sample_infection_pairs(&pop, rng).is_empty());
+    }
+
+    #[test]
+    fn samplers_do_not_target_recovered_agents() {
+        let mut pop = vec![SEIRm::Recovered(()); 1000];
+        for ag in pop.iter_mut().step_by(10) {
+            *ag = SEIR::Infectious(());
+        }
+        let sampler = SimpleSampler::new(10.0, 1.0);
+        assert_eq!(sampler.expected_infection_pairs(&pop), 0.0);
+        let overdispersed = OverdispersedSampler::new(10.0, 1.0, 0.5);
+        assert_eq!(overdispersed.expected_infection_pairs(&pop), 0.0);
+
+        let rng = &mut seeded_rng(42u64);
+        assert_eq!(sampler.update_epimodel_population(&mut pop, rng), 0);
+        assert!(pop.iter().all(|ag| !matches!(ag, SEIR::Exposed(_))));
+
+        let mut network = NetworkSampler::watts_strogatz(10.0, 0.1, rng);
+        network.set_prob_infection(1.0);
+        network.init(&mut pop);
+        assert!(network.sample_infection_pairs(&pop, rng).is_empty());
+    }
+
+    #[test]
+    fn watts_strogatz_network_without_rewiring_is_ring_lattice() {
+        let mut pop = vec![SeirAgent::<()>::default(); 100];
+        let mut sampler = NetworkSampler::watts_strogatz(4.0, 0.0, &mut thread_rng());
+        sampler.init(&mut pop);
+
+        assert_eq!(sampler.contacts(), 4.0);
+        for (i, neighbors) in sampler.adjacency().iter().enumerate() {
+            assert_eq!(neighbors.len(), 4);
+            assert!(neighbors.iter().all(|&j| ring_distance(i, j, 100) <= 2));
+        }
+    }
+
+    #[test]
+    fn watts_strogatz_rewiring_preserves_edges() {
+        let mut pop = vec![SeirAgent::<()>::default(); 1000];
+        let mut sampler = NetworkSampler::watts_strogatz(6.0, 0.2, &mut seeded_rng(42u64));
+        sampler.init(&mut pop);
+
+        let adjacency = sampler.adjacency();
+        let long_range = (0..1000)
+            .flat_map(|i| adjacency[i].iter().map(move |&j| (i, j)))
+            .filter(|&(i, j)| ring_distance(i, j, 1000) > 3)
+            .count();
+        assert_eq!(sampler.contacts(), 6.0);
+        assert_approx_eq!(long_range as Real / 6000.0, 0.2, 0.03);
+        for (i, neighbors) in adjacency.iter().enumerate() {
+            assert!(neighbors
+                .iter()
+                .all(|&j| j != i && adjacency[j].contains(&i)));
+        }
+
+        sampler.set_contacts(2.0);
+        assert_eq!(sampler.contacts(), 2.0);
+        sampler.set_contacts(0.0);
+        assert_eq!(sampler.contacts(), 0.0);
+    }
+
+    #[test]
+    fn network_sampler_infects_only_neighbors() {
+        let mut pop = vec![SeirAgent::<()>::default(); 50];
+        pop[10].set_epimodel(SEIR::Infectious(()));
+        let mut sampler = NetworkSampler::watts_strogatz(4.0, 0.0, &mut seeded_rng(42u64));
+        sampler.set_prob_infection(1.0);
+        sampler.init(&mut pop);
+
+        let mut pairs = sampler.sample_infection_pairs(&pop, &mut seeded_rng(1u64));
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(10, 8), (10, 9), (10, 11), (10, 12)]);
+        assert_eq!(sampler.infection_hazard(&pop, 12), 1.0);
+        assert_eq!(sampler.infection_hazard(&pop, 13), 0.0);
+
+        // Isolated nodes
+        pop.push(SeirAgent::default());
+        pop[50].set_epimodel(SEIR::Infectious(()));
+        let pairs = sampler.sample_infection_pairs(&pop, &mut seeded_rng(1u64));
+        assert!(pairs.iter().all(|&(i, _)| i != 50));
+        sampler.set_contacts(0.0);
+        assert!(sampler
+            .sample_infection_pairs(&pop, &mut seeded_rng(1u64))
+            .is_empty());
+    }
+
+    #[test]
+    fn contact_matrix_sampler_skips_empty_age_groups() {
+        let mut pop = vec![SeirAgent::<()>::default(); 3];
+        for (i, ag) in pop.iter_mut().enumerate() {
+            ag.set_age(20 * i as u8);
+        }
+        pop[0].set_epimodel(SEIR::Infectious(()));
+        let mut sampler = ContactMatrixSampler::new(10, Array2::from_elem((9, 9), 2.0), 1.0);
+        sampler.init(&mut pop);
+
+        let mut rng = seeded_rng(42u64);
+        let mut count = 0;
+        for _ in 0..100 {
+            for (i, j) in sampler.sample_infection_pairs(&pop, &mut rng) {
+                assert!(i == 0 && (j == 1 || j == 2));
+                count += 1;
+            }
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn contact_matrix_expected_pairs_accounts_for_depleted_groups() {
+        let mut pop = vec![SeirAgent::<()>::default(); 1000];
+        for (i, ag) in pop.iter_mut().enumerate() {
+            ag.set_age(if i < 500 { 5 } else { 45 });
+            if i % 50 == 0 {
+                ag.set_epimodel(SEIR::Infectious(()));
+            } else if i >= 500 && i % 10 != 1 {
+                ag.set_epimodel(SEIR::Recovered(()));
+            }
+        }
+        let matrix = array![[2.0, 1.0], [1.0, 3.0]];
+        let mut sampler = ContactMatrixSampler::new(10, matrix, 0.5);
+        sampler.init(&mut pop);
+
+        let mut rng = seeded_rng(42u64);
+        let n_runs = 500;
+        let mut total = 0;
+        for _ in 0..n_runs {
+            total += sampler
+                .sample_infection_pairs(&pop, &mut rng)
+                .iter()
+                .filter(|(_, j)| pop[*j].is_susceptible())
+                .count();
+        }
+        let mean = total as Real / n_runs as Real;
+        let expected = sampler.expected_infection_pairs(&pop);
+        assert!(
+            (expected - mean).abs() < 0.05 * mean,
+            "{} vs {}",
+            expected,
+            mean
+        );
+    }
+
+    #[test]
+    fn contact_matrix_expected_pairs_counts_self_draws_in_small_groups() {
+        let mut pop = vec![SeirAgent::<()>::default(); 4];
+        pop[0].set_epimodel(SEIR::Infectious(()));
+        let mut sampler = ContactMatrixSampler::new(255, array![[3.0]], 1.0);
+        sampler.init(&mut pop);
+
+        // 3 contacts drawn from 4 agents, 3 of which are susceptible
+        let expected = sampler.expected_infection_pairs(&pop);
+        assert_approx_eq!(expected, 2.25);
+
+        let mut rng = seeded_rng(42u64);
+        let n_runs = 10_000;
+        let mut total = 0;
+        for _ in 0..n_runs {
+            total += sampler.sample_infection_pairs(&pop, &mut rng).len();
+        }
+        let mean = total as Real / n_runs as Real;
+        assert_approx_eq!(mean, expected, 0.05);
+    }
+
+    #[test]
+    fn household_sizes_follow_distribution() {
+        let sizes = [(1, 0.2), (2, 0.3), (3, 0.3), (5, 0.2)];
+        let rng = &mut seeded_rng(42u64);
+        let sampler = HouseholdSampler::from_size_distribution(&sizes, 100_000, rng).unwrap();
+        assert_approx_eq!(sampler.mean_household_size(), 2.7, 0.05);
+
+        let mut members: Vec<usize> = sampler.households().iter().flatten().cloned().collect();
+        members.sort_unstable();
+        assert_eq!(members, (0..100_000).collect::<Vec<_>>());
+        let households = sampler.households();
+        assert!(households[..households.len() - 1]
+            .iter()
+            .all(|h| [1, 2, 3, 5].contains(&h.len())));
+
+        assert!(HouseholdSampler::from_size_distribution(&[], 10, rng).is_err());
+        assert!(HouseholdSampler::from_size_distribution(&[(2, 0.0)], 10, rng).is_err());
+        assert!(HouseholdSampler::from_size_distribution(&[(2, -1.0)], 10, rng).is_err());
+    }
+
+    #[test]
+    fn household_sampler_infects_household_members() {
+        let mut pop = vec![SeirAgent::<()>::default(); 6];
+        pop[1].set_epimodel(SEIR::Infectious(()));
+        pop[2].set_epimodel(SEIR::Recovered(()));
+        let sampler = HouseholdSampler::new(vec![vec![0, 1, 2], vec![3, 4]], 0.0, 1.0);
+        assert_eq!(sampler.household(5), &[] as &[usize]);
+
+        let mut pairs = sampler.sample_infection_pairs(&pop, &mut seeded_rng(42u64));
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(1, 0)]);
+    }
+
+    #[test]
+    fn network_from_edges_validates_and_deduplicates() {
+        let edges = vec![(0, 1), (1, 0), (1, 2), (2, 2), (0, 1)];
+        let sampler = NetworkSampler::from_edges(4, edges).unwrap();
+        assert_eq!(
+            sampler.adjacency(),
+            &vec![vec![1], vec![0, 2], vec![1], vec![]]
+        );
+
+        let mut pop = vec![SeirAgent::<()>::default(); 4];
+        let mut sampler = sampler;
+        sampler.init(&mut pop);
+        sampler.set_contacts(10.0);
+        assert_eq!(sampler.adjacency()[3], Vec::<usize>::new());
+        assert_eq!(sampler.contacts(), 1.0);
+
+        let err = NetworkSampler::from_edges(3, vec![(0, 1), (1, 5), (7, 0)]).unwrap_err();
+        assert!(err.contains('5'));
+    }
+
+    #[test]
+    fn network_from_csv_skips_header() {
+        let path = std::env::temp_dir().join(format!("covid-edges-{}.csv", std::process::id()));
+        std::fs::write(&path, "src, dest\n0, 1\n1, 2\n2, 0\n").unwrap();
+        let sampler = NetworkSampler::from_csv(&path).unwrap();
+        std::fs::write(&path, "0,1\n1,x\n").unwrap();
+        let err = NetworkSampler::from_csv(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sampler.adjacency().len(), 3);
+        assert_eq!(sampler.contacts(), 2.0);
+        assert!(err.contains("line 2"));
+    }
+
+    #[test]
+    fn barabasi_albert_network_has_heavy_tail() {
+        let mut pop = vec![SeirAgent::<()>::default(); 2000];
+        let mut sampler = BarabasiAlbertSampler::new(3, &mut seeded_rng(42u64));
+        sampler.init(&mut pop);
+
+        let histogram = sampler.degree_histogram();
+        assert_eq!(histogram.iter().sum::<usize>(), 2000);
+        assert!(histogram[..3].iter().all(|&n| n == 0));
+        assert!(histogram.len() > 60);
+        assert_approx_eq!(sampler.contacts(), 6.0, 0.01);
+        for (i, neighbors) in sampler.adjacency().iter().enumerate() {
+            assert!(neighbors
+                .iter()
+                .all(|&j| sampler.adjacency()[j].contains(&i)));
+        }
+    }
+
+    #[test]
+    fn barabasi_albert_small_population_is_complete_graph() {
+        for n in 0..5 {
+            let mut pop = vec![SeirAgent::<()>::default(); n];
+            let mut sampler = BarabasiAlbertSampler::new(4, &mut seeded_rng(42u64));
+            sampler.init(&mut pop);
+            assert!(sampler.adjacency().iter().all(|v| v.len() == n - 1));
+        }
+
+        let mut pop = vec![SeirAgent::<()>::default(); 3];
+        pop[0].set_epimodel(SEIR::Infectious(()));
+        let mut sampler = BarabasiAlbertSampler::new(4, &mut seeded_rng(42u64));
+        sampler.init(&mut pop);
+        let mut pairs = sampler.sample_infection_pairs(&pop, &mut seeded_rng(1u64));
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn overdispersed_sampler_has_excess_variance() {
+        let mut pop = vec![SeirAgent::<()>::default(); 20_000];
+        for ag in pop.iter_mut().take(500) {
+            ag.set_epimodel(SEIR::Infectious(()));
+        }
+        let sampler = OverdispersedSampler::new(4.0, 1.0, 0.2);
+        let mut secondary = vec![0.0; 500];
+        for (i, _) in sampler.sample_infection_pairs(&pop, &mut seeded_rng(42u64)) {
+            secondary[i] += 1.0;
+        }
+        let mean = secondary.iter().sum::<Real>() / 500.0;
+        let var = secondary.iter().map(|x| (x - mean).powi(2)).sum::<Real>() / 499.0;
+        assert!((mean - 4.0).abs() < 1.0, "mean: {}", mean);
+        assert!(var > 3.0 * mean, "mean: {}, var: {}", mean, var);
+    }
+
+    #[test]
+    fn scheduled_sampler_selects_active_sampler() {
+        let schedule = vec![
+            (10, SimpleSampler::new(2.0, 0.1)),
+            (0, SimpleSampler::new(4.0, 0.1)),
+            (20, SimpleSampler::new(1.0, 0.1)),
+        ];
+        let mut sampler = ScheduledSampler::new(schedule);
+        assert_eq!(sampler.active_at(0).contacts(), 4.0);
+        assert_eq!(sampler.active_at(15).contacts(), 2.0);
+        assert_eq!(sampler.active_at(100).contacts(), 1.0);
+
+        let pop = vec![SeirAgent::<()>::default(); 10];
+        sampler.sample_infection_pairs_at(15, &pop, &mut seeded_rng(1u64));
+        assert_eq!(sampler.contacts(), 2.0);
+        sampler.set_contacts(4.0);
+        assert_eq!(sampler.active_at(0).contacts(), 8.0);
+        assert_eq!(sampler.active_at(20).contacts(), 2.0);
+    }
+
+    #[test]
+    fn scheduled_sampler_sets_contacts_during_lockdown() {
+        let schedule = vec![
+            (0, SimpleSampler::new(0.0, 0.1)),
+            (10, SimpleSampler::new(4.0, 0.1)),
+        ];
+        let mut sampler = ScheduledSampler::new(schedule);
+        assert_eq!(sampler.contacts(), 0.0);
+        sampler.set_contacts(3.0);
+        assert_eq!(sampler.active_at(0).contacts(), 3.0);
+        assert_eq!(sampler.active_at(10).contacts(), 3.0);
+    }
+
+    #[test]
+    fn periodic_modulation_sampler_applies_weekly_factors() {
+        type Pop = Vec<SeirAgent<()>>;
+        let factors = [1.0, 1.0, 1.0, 1.0, 1.0, 0.5, 0.0];
+        let mut sampler = PeriodicModulationSampler::new(SimpleSampler::new(4.0, 0.1), factors);
+        assert_approx_eq!(sampler.prob_infection_at(5), 0.05);
+        assert_approx_eq!(sampler.prob_infection_at(12), 0.05);
+        assert_eq!(sampler.prob_infection_at(13), 0.0);
+
+        let mut pop = vec![SeirAgent::<()>::default(); 100];
+        for ag in pop.iter_mut().take(10) {
+            ag.set_epimodel(SEIR::Infectious(()));
+        }
+        let hazard = sampler.infection_hazard(&pop, 50);
+        for _ in 0..5 {
+            PopulationSampler::<Pop>::advance_step(&mut sampler);
+        }
+        assert_approx_eq!(sampler.prob_infection(), 0.05);
+        assert!(sampler.infection_hazard(&pop, 50) < hazard);
+
+        PopulationSampler::<Pop>::advance_step(&mut sampler);
+        assert_eq!(sampler.expected_infection_pairs(&pop), 0.0);
+        assert!(sampler
+            .sample_infection_pairs(&pop, &mut seeded_rng(1u64))
+            .is_empty());
+        assert_eq!(sampler.base_prob_infection(), 0.1);
+        assert_eq!(sampler.into_inner().prob_infection(), 0.1);
+    }
+
+    #[test]
+    fn seasonal_sampler_follows_sinusoidal_forcing() {
+        type Pop = Vec<SeirAgent<()>>;
+        let mut sampler = SeasonalSampler::new(SimpleSampler::new(4.0, 0.1), 0.5, 4.0, 0.0);
+        let mut probs = vec![];
+        for _ in 0..5 {
+            probs.push(sampler.prob_infection());
+            PopulationSampler::<Pop>::advance_step(&mut sampler);
+        }
+        for (p, expected) in probs.iter().zip([0.1, 0.15, 0.1, 0.05, 0.1].iter()) {
+            assert_approx_eq!(p, expected);
+        }
+        assert_approx_eq!(sampler.sampler().prob_infection(), 0.15);
+        assert_eq!(sampler.base_prob_infection(), 0.1);
+
+        let sampler = SeasonalSampler::new(SimpleSampler::new(4.0, 0.8), 2.0, 4.0, 0.0);
+        assert_eq!(sampler.prob_infection_at(1), 1.0);
+        assert_eq!(sampler.prob_infection_at(3), 0.0);
+    }
+
+    #[test]
+    fn boxed_sampler_dispatches_to_inner_sampler() {
+        type Pop = Vec<SeirAgent<()>>;
+        let mut pop: Pop = vec![SeirAgent::default(); 4];
+        pop[0].set_epimodel(SEIR::Infectious(()));
+        let edges = vec![(0, 1), (1, 2), (2, 3)];
+        let mut samplers: Vec<BoxedSampler<Pop>> = vec![
+            BoxedSampler::new(SimpleSampler::new(4.0, 0.1)),
+            BoxedSampler::new(NetworkSampler::from_edges(4, edges).unwrap()),
+        ];
+        for sampler in samplers.iter_mut() {
+            sampler.init(&mut pop);
+            sampler.set_prob_infection(1.0);
+            assert_eq!(sampler.prob_infection(), 1.0);
+        }
+        assert_eq!(samplers[0].contacts(), 4.0);
+        assert_eq!(samplers[1].contacts(), 1.5);
+
+        let pairs = samplers[1].sample_infection_pairs(&pop, &mut seeded_rng(42u64));
+        assert_eq!(pairs, vec![(0, 1)]);
+        assert_eq!(samplers[1].infection_hazard(&pop, 1), 1.0);
+        assert_eq!(samplers[1].infection_hazard(&pop, 2), 0.0);
+    }
+
+    #[test]
+    fn simple_sampler_serde_round_trip() {
+        let sampler: AnySampler = SimpleSampler::new(4.0, 0.1).into();
+        let parsed = round_trip(&sampler);
+        assert_eq!(parsed, sampler);
+        assert_eq!(parsed.contacts(), 4.0);
+        assert_eq!(parsed.prob_infection(), 0.1);
+    }
+
+    #[test]
+    fn contact_matrix_sampler_rebuilds_age_groups_on_init() {
+        let mut pop: Vec<SeirAgent<()>> = (0..100)
+            .map(|i| {
+                let mut ag = SeirAgent::default();
+                ag.set_age(if i < 30 { 5 } else { 45 });
+                ag
+            })
+            .collect();
+        let matrix = array![[2.0, 1.0], [1.0, 3.0]];
+        let mut sampler: AnySampler = ContactMatrixSampler::new(10, matrix, 0.2).into();
+        sampler.init(&mut pop);
+
+        let mut parsed = round_trip(&sampler);
+        assert_eq!(parsed.contacts(), sampler.contacts());
+        assert_eq!(parsed.prob_infection(), 0.2);
+        match &parsed {
+            AnySampler::ContactMatrix(s) => assert!(s.age_groups.is_empty()),
+            _ => panic!("wrong variant"),
+        }
+
+        parsed.init(&mut pop);
+        assert_eq!(parsed, sampler);
+    }
+
+    #[test]
+    fn any_sampler_forwards_analytic_expected_pairs() {
+        let params = EpiParamsLocal::default();
+        let mut pop: Vec<SeirAgent<()>> = (0..1000)
+            .map(|i| {
+                let mut ag = SeirAgent::default();
+                ag.set_age(if i < 300 { 5 } else { 45 });
+                if i % 100 == 0 {
+                    ag.set_epimodel(SEIR::Infectious(()));
+                }
+                ag
+            })
+            .collect();
+        let matrix = array![[2.0, 1.0], [1.0, 3.0]];
+        let mut samplers: Vec<AnySampler> = vec![
+            SimpleSampler::new(4.0, 0.1).into(),
+            ContactMatrixSampler::new(10, matrix, 0.1).into(),
+        ];
+        for sampler in samplers.iter_mut() {
+            sampler.init(&mut pop);
+            let expected = match &*sampler {
+                AnySampler::Simple(s) => s.expected_infection_pairs(&pop),
+                AnySampler::ContactMatrix(s) => s.expected_infection_pairs(&pop),
+            };
+            assert_eq!(sampler.expected_infection_pairs(&pop), expected);
+
+            // Calibration is reproducible, since it does not rely on sampling
+            let prob = sampler.calibrate_prob_infection_for_r0(&pop, &params, 2.0);
+            sampler.set_prob_infection(0.5);
+            assert_eq!(
+                sampler.calibrate_prob_infection_for_r0(&pop, &params, 2.0),
+                prob
+            );
+        }
+    }
+
+    #[test]
+    fn calibrate_prob_infection_matches_r0() {
+        let params = EpiParamsLocal::default();
+        let period = params.infectious_period();
+        let mut pop = vec![SEIRm::default(); 100_000];
+        for st in pop[..1000].iter_mut() {
+            *st = SEIR::Infectious(());
+        }
+
+        let target = SimpleSampler::from_r0(2.0, 10.0, period);
+        assert_approx_eq!(target.prob_infection() * 10.0 * period, 2.0);
+        let mut sampler = SimpleSampler::new(10.0, 0.5);
+        let prob = sampler.calibrate_prob_infection_for_r0(&pop, &params, 2.0);
+        assert_eq!(prob, sampler.prob_infection());
+        assert!((prob / target.prob_infection() - 1.0).abs() < 0.1);
+        let mut other = SimpleSampler::new(10.0, 0.9);
+        assert_eq!(
+            other.calibrate_prob_infection_for_r0(&pop, &params, 2.0),
+            prob
+        );
+
+        // Analytic expectation agrees with the sampled number of pairs
+        let rng = &mut seeded_rng(42u64);
+        let expected = sampler.expected_infection_pairs(&pop);
+        let sampled = sampler.sample_infection_pairs(&pop, rng).len() as Real;
+        assert!((sampled - expected).abs() < 4.0 * expected.sqrt());
+
+        let mut sampler = SimpleSampler::new(0.1, 0.5);
+        assert_eq!(
+            sampler.calibrate_prob_infection_for_r0(&pop, &params, 5.0),
+            1.0
+        );
+        assert_eq!(
+            SimpleSampler::from_r0(5.0, 0.1, period).prob_infection(),
+            1.0
+        );
     }
 }
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::Real;
+
+/// Endogenous behavior change in response to recent deaths.
+///
+/// Each day, the per capita death incidence is smoothed by an exponential
+/// moving average with a time scale of memory days. The contact rate of the
+/// sampler is then set to baseline / (1 + sensitivity * signal), in which
+/// baseline is the contact rate when the response was installed. A
+/// sensitivity of zero disables the response.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BehavioralResponse {
+    /// Reduction of contacts per unit of smoothed daily deaths per capita.
+    pub sensitivity: Real,
+    /// Time scale (in days) of the perceived death incidence.
+    pub memory: Real,
+}
+
+impl Default for BehavioralResponse {
+    fn default() -> Self {
+        BehavioralResponse {
+            sensitivity: 0.0,
+            memory: 7.0,
+        }
+    }
+}
+
+/// Smoothed death signal used to execute a behavioral response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BehaviorState {
+    response: BehavioralResponse,
+    baseline: Real,
+    signal: Real,
+    n_dead: usize,
+}
+
+impl BehaviorState {
+    pub fn new(response: BehavioralResponse, baseline: Real, n_dead: usize) -> Self {
+        BehaviorState {
+            response,
+            baseline,
+            signal: 0.0,
+            n_dead,
+        }
+    }
+
+    /// Contact rate in the absence of a response.
+    pub fn baseline(&self) -> Real {
+        self.baseline
+    }
+
+    /// Register the current number of deaths in a population of size n and
+    /// return the new contact rate.
+    pub fn update(&mut self, n_dead: usize, n: usize) -> Real {
+        let incidence = n_dead.saturating_sub(self.n_dead) as Real / n as Real;
+        let rate = 1.0 / self.response.memory.max(1.0);
+        self.n_dead = n_dead;
+        self.signal += rate * (incidence - self.signal);
+        return self.baseline / (1.0 + self.response.sensitivity * self.signal);
+    }
+}
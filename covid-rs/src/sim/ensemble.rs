@@ -0,0 +1,398 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use super::{RandomUpdate, Simulation};
+use crate::{
+    epidemic::{EpiModel, EpiModelPopulationExt},
+    params::{EpiParamsLocalT, LocalBind},
+    prelude::{PopulationSampler, Real, NAN},
+    utils::{P2Quantile, Sampling},
+};
+
+/// Minimum attack rate for a member to be considered a major outbreak.
+pub const MAJOR_OUTBREAK_THRESHOLD: Real = 0.1;
+
+/// Results of independent replicates of a simulation, used to summarize the
+/// stochastic variability of the outcomes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ensemble {
+    /// Normalized epicurves indexed by member, compartment and time.
+    curves: Vec<Vec<Vec<Real>>>,
+    /// Daily incidence of each member.
+    incidence: Vec<Vec<usize>>,
+    /// Final attack rate of each member.
+    attack_rates: Vec<Real>,
+}
+
+/// Summary of an ensemble. See Ensemble::report().
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleReport {
+    pub n_members: usize,
+    pub quantiles: Vec<Real>,
+    /// Quantile bands of the normalized epicurves, indexed by compartment,
+    /// quantile and time.
+    pub bands: Vec<Vec<Vec<Real>>>,
+    pub attack_rate_median: Real,
+    /// 95% interval of the attack rate.
+    pub attack_rate_ci: (Real, Real),
+    /// Fraction of members with attack rate above MAJOR_OUTBREAK_THRESHOLD.
+    pub major_outbreak_probability: Real,
+    /// Median day of maximum incidence.
+    pub median_peak_day: Real,
+}
+
+/// Mean and 95% bands of the normalized epicurves of an ensemble, indexed by
+/// compartment and time. See Ensemble::run().
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleBands {
+    pub n_members: usize,
+    pub mean: Vec<Vec<Real>>,
+    /// 2.5% quantile.
+    pub lower: Vec<Vec<Real>>,
+    /// 97.5% quantile.
+    pub upper: Vec<Vec<Real>>,
+}
+
+impl Ensemble {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Number of members.
+    pub fn len(&self) -> usize {
+        self.attack_rates.len()
+    }
+
+    /// True if ensemble has no members.
+    pub fn is_empty(&self) -> bool {
+        self.attack_rates.is_empty()
+    }
+
+    /// Add the results of a finished simulation as a new member.
+    pub fn push<W, S, PS>(&mut self, sim: &Simulation<W, S, PS>) -> &mut Self
+    where
+        PS: PopulationSampler<Vec<S>>,
+        W: LocalBind<S>,
+        W::Local: EpiParamsLocalT,
+        S: EpiModel + RandomUpdate<W::Local> + Debug,
+    {
+        let curves = (0..S::CARDINALITY)
+            .map(|i| sim.get_epicurve(i, true).unwrap_or_default())
+            .collect();
+        self.curves.push(curves);
+        self.incidence.push(sim.infections_per_iter().clone());
+        self.attack_rates.push(sim.population().attack_ratio());
+        return self;
+    }
+
+    /// Summarize ensemble with the given quantiles (in the 0-1 range) for the
+    /// epicurve bands.
+    ///
+    /// Bands are computed over the time span common to all members. Statistics
+    /// of an empty ensemble are NaN.
+    pub fn report(&self, quantiles: &[Real]) -> EnsembleReport {
+        let n_compartments = self.curves.iter().map(|c| c.len()).max().unwrap_or(0);
+        let n_steps = self
+            .curves
+            .iter()
+            .flatten()
+            .map(|c| c.len())
+            .min()
+            .unwrap_or(0);
+
+        let mut bands = vec![vec![Vec::with_capacity(n_steps); quantiles.len()]; n_compartments];
+        for (c, band) in bands.iter_mut().enumerate() {
+            for t in 0..n_steps {
+                let values = sorted(self.curves.iter().map(|m| m[c][t]));
+                for (q, &p) in quantiles.iter().enumerate() {
+                    band[q].push(quantile(&values, p));
+                }
+            }
+        }
+
+        let peak_days = self.incidence.iter().map(|curve| peak_day(curve));
+        return EnsembleReport::new(quantiles, bands, &self.attack_rates, peak_days);
+    }
+
+    /// Run n_members simulations for the given number of steps and summarize
+    /// them without storing the full epicurves.
+    ///
+    /// Member i is created by calling factory(base_seed + i), which should
+    /// return a seeded simulation with its initial condition. Members run one
+    /// at a time and epicurve bands are estimated by streaming P² quantile
+    /// accumulators, so memory grows as O(steps * quantiles), rather than
+    /// O(steps * n_members). Only scalar summaries (attack rates and peak
+    /// days) are stored for each member and those statistics are exact.
+    pub fn run_streaming<W, S, PS, F>(
+        n_members: usize,
+        steps: usize,
+        base_seed: u64,
+        quantiles: &[Real],
+        factory: F,
+    ) -> EnsembleReport
+    where
+        PS: PopulationSampler<Vec<S>>,
+        W: LocalBind<S>,
+        W::Local: EpiParamsLocalT,
+        S: EpiModel + RandomUpdate<W::Local> + Debug,
+        F: Fn(u64) -> Simulation<W, S, PS>,
+    {
+        let mut accumulators: Vec<Vec<Vec<P2Quantile>>> = vec![];
+        let mut attack_rates = Vec::with_capacity(n_members);
+        let mut peak_days = Vec::with_capacity(n_members);
+
+        for i in 0..n_members {
+            let mut sim = factory(base_seed + i as u64);
+            sim.run(steps);
+
+            for c in 0..S::CARDINALITY {
+                let curve = sim.get_epicurve(c, true).unwrap_or_default();
+                if accumulators.len() <= c {
+                    let acc = quantiles.iter().map(|&p| P2Quantile::new(p)).collect();
+                    accumulators.push(vec![acc; curve.len()]);
+                }
+                let n_steps = accumulators[c].len().min(curve.len());
+                accumulators[c].truncate(n_steps);
+                for (acc, &x) in accumulators[c].iter_mut().zip(curve.iter()) {
+                    acc.iter_mut().for_each(|q| q.add(x));
+                }
+            }
+            attack_rates.push(sim.population().attack_ratio());
+            peak_days.push(peak_day(sim.infections_per_iter()));
+        }
+
+        let bands = accumulators
+            .iter()
+            .map(|curve| {
+                (0..quantiles.len())
+                    .map(|q| curve.iter().map(|acc| acc[q].quantile()).collect())
+                    .collect()
+            })
+            .collect();
+        return EnsembleReport::new(quantiles, bands, &attack_rates, peak_days.into_iter());
+    }
+
+    /// Run n_members simulations in parallel for the given number of steps and
+    /// compute the mean and 95% bands of the normalized epicurves.
+    ///
+    /// Each member is created by factory() and then seeded with base_seed + i,
+    /// overriding any seed set by the factory, so results are reproducible and
+    /// do not depend on the order in which members are executed. Statistics
+    /// are computed over the time span common to all members.
+    pub fn run<W, S, PS, F>(
+        n_members: usize,
+        steps: usize,
+        base_seed: u64,
+        factory: F,
+    ) -> EnsembleBands
+    where
+        PS: PopulationSampler<Vec<S>>,
+        W: LocalBind<S>,
+        W::Local: EpiParamsLocalT,
+        S: EpiModel + RandomUpdate<W::Local> + Debug,
+        F: Fn() -> Simulation<W, S, PS> + Sync,
+    {
+        let curves: Vec<Vec<Vec<Real>>> = (0..n_members)
+            .into_par_iter()
+            .map(|i| {
+                let mut sim = factory();
+                sim.seed(base_seed + i as u64);
+                sim.run(steps);
+                return (0..S::CARDINALITY)
+                    .map(|c| sim.get_epicurve(c, true).unwrap_or_default())
+                    .collect();
+            })
+            .collect();
+
+        let n_steps = curves.iter().flatten().map(|c| c.len()).min().unwrap_or(0);
+        let n_compartments = if n_members == 0 { 0 } else { S::CARDINALITY };
+        let mut bands = EnsembleBands {
+            n_members,
+            mean: vec![Vec::with_capacity(n_steps); n_compartments],
+            lower: vec![Vec::with_capacity(n_steps); n_compartments],
+            upper: vec![Vec::with_capacity(n_steps); n_compartments],
+        };
+        for c in 0..n_compartments {
+            for t in 0..n_steps {
+                let values = sorted(curves.iter().map(|m| m[c][t]));
+                bands.mean[c].push(values.iter().sum::<Real>() / n_members as Real);
+                bands.lower[c].push(quantile(&values, 0.025));
+                bands.upper[c].push(quantile(&values, 0.975));
+            }
+        }
+        return bands;
+    }
+}
+
+impl EnsembleReport {
+    /// Create report from epicurve bands and the scalar summaries of each
+    /// member.
+    fn new(
+        quantiles: &[Real],
+        bands: Vec<Vec<Vec<Real>>>,
+        attack_rates: &[Real],
+        peak_days: impl Iterator<Item = Real>,
+    ) -> Self {
+        let n_members = attack_rates.len();
+        let attack_rates = sorted(attack_rates.iter().cloned());
+        let n_major = attack_rates
+            .iter()
+            .filter(|&&x| x > MAJOR_OUTBREAK_THRESHOLD)
+            .count();
+        let peak_days = sorted(peak_days);
+
+        return EnsembleReport {
+            n_members,
+            quantiles: quantiles.to_vec(),
+            bands,
+            attack_rate_median: quantile(&attack_rates, 0.5),
+            attack_rate_ci: (
+                quantile(&attack_rates, 0.025),
+                quantile(&attack_rates, 0.975),
+            ),
+            major_outbreak_probability: n_major as Real / n_members as Real,
+            median_peak_day: quantile(&peak_days, 0.5),
+        };
+    }
+}
+
+/// Day of maximum incidence, or NaN for empty curves.
+fn peak_day(incidence: &[usize]) -> Real {
+    let peak = (0..incidence.len()).max_by_key(|&t| incidence[t]);
+    return peak.map_or(NAN, |t| t as Real);
+}
+
+/// Sort data in increasing order. NaN values (e.g., from members with empty
+/// populations) are placed at the end instead of panicking.
+fn sorted(data: impl Iterator<Item = Real>) -> Vec<Real> {
+    let mut data: Vec<Real> = data.collect();
+    data.sort_by(|a, b| a.total_cmp(b));
+    return data;
+}
+
+/// Quantile of sorted data using linear interpolation between points.
+fn quantile(sorted: &[Real], p: Real) -> Real {
+    if sorted.is_empty() {
+        return NAN;
+    }
+    let pos = p.clamp(0.0, 1.0) * (sorted.len() - 1) as Real;
+    let (i, frac) = (pos.floor() as usize, pos.fract());
+    match sorted.get(i + 1) {
+        Some(next) => sorted[i] + frac * (next - sorted[i]),
+        None => sorted[i],
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::SeirAgent, params::EpiParamsLocal, utils::seeded_rng};
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn quantiles_interpolate_sorted_data() {
+        let data = [1.0, 2.0, 3.0, 4.0];
+        assert_approx_eq!(quantile(&data, 0.0), 1.0);
+        assert_approx_eq!(quantile(&data, 0.5), 2.5);
+        assert_approx_eq!(quantile(&data, 1.0), 4.0);
+        assert!(quantile(&[], 0.5).is_nan());
+
+        let data = sorted([3.0, NAN, 1.0].iter().cloned());
+        assert_eq!(data[..2], [1.0, 3.0]);
+        assert!(data[2].is_nan());
+        assert_approx_eq!(quantile(&data, 0.25), 2.0);
+    }
+
+    #[test]
+    fn report_median_attack_rate_within_ci() {
+        let mut ensemble = Ensemble::new();
+        for seed in 0..10 {
+            let population = vec![SeirAgent::<()>::default(); 500];
+            let mut sim = Simulation::new_simple(EpiParamsLocal::default(), population, 4.0, 0.1);
+            sim.seed(seed);
+            sim.contaminate_at_random(2, &mut seeded_rng(seed));
+            sim.run(60);
+            ensemble.push(&sim);
+        }
+
+        let report = ensemble.report(&[0.05, 0.5, 0.95]);
+        let (lo, hi) = report.attack_rate_ci;
+        assert_eq!(report.n_members, 10);
+        assert!(lo <= report.attack_rate_median && report.attack_rate_median <= hi);
+        assert!(report.major_outbreak_probability > 0.0);
+        assert_eq!(report.bands.len(), 5);
+        assert_eq!(report.bands[0].len(), 3);
+        assert!(report.bands[0][0][10] <= report.bands[0][2][10]);
+
+        let data = toml::to_string(&report).unwrap();
+        let parsed: EnsembleReport = toml::from_str(&data).unwrap();
+        assert_eq!(parsed.n_members, 10);
+    }
+
+    #[test]
+    fn streaming_quantiles_match_full_storage() {
+        let factory = |seed: u64| {
+            let population = vec![SeirAgent::<()>::default(); 1000];
+            let mut sim = Simulation::new_simple(EpiParamsLocal::default(), population, 4.0, 0.1);
+            sim.seed(seed);
+            sim.contaminate_at_random(20, &mut seeded_rng(seed));
+            return sim;
+        };
+        let quantiles = [0.25, 0.5, 0.75];
+
+        let mut ensemble = Ensemble::new();
+        for seed in 100..300 {
+            ensemble.push(factory(seed).run(60));
+        }
+        let full = ensemble.report(&quantiles);
+        let streaming = Ensemble::run_streaming(200, 60, 100, &quantiles, factory);
+
+        assert_eq!(streaming.n_members, 200);
+        assert_eq!(streaming.attack_rate_median, full.attack_rate_median);
+        assert_eq!(streaming.median_peak_day, full.median_peak_day);
+        assert_eq!(streaming.bands.len(), full.bands.len());
+        for (a, b) in streaming
+            .bands
+            .iter()
+            .flatten()
+            .zip(full.bands.iter().flatten())
+        {
+            assert_eq!(a.len(), 61);
+            for (x, y) in a.iter().zip(b.iter()) {
+                assert_approx_eq!(x, y, 0.02);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_run_is_reproducible() {
+        let factory = || {
+            let population = vec![SeirAgent::<()>::default(); 500];
+            let mut sim = Simulation::new_simple(EpiParamsLocal::default(), population, 4.0, 0.1);
+            sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+            return sim;
+        };
+        let bands = Ensemble::run(20, 40, 7, factory);
+        assert_eq!(bands, Ensemble::run(20, 40, 7, factory));
+        assert_eq!(bands.n_members, 20);
+        assert_eq!(bands.mean.len(), 5);
+        assert_eq!(bands.mean[0].len(), 41);
+        for c in 0..5 {
+            for t in 0..41 {
+                assert!(bands.lower[c][t] <= bands.mean[c][t] + 1e-9);
+                assert!(bands.mean[c][t] <= bands.upper[c][t] + 1e-9);
+            }
+        }
+
+        let mut sim = factory();
+        sim.seed(7);
+        sim.run(40);
+        let single = Ensemble::run(1, 40, 7, factory);
+        assert_eq!(single.mean[0], sim.get_epicurve(0, true).unwrap());
+    }
+}
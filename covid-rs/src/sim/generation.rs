@@ -0,0 +1,70 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::Id;
+use crate::{
+    prelude::{Real, Time},
+    utils::poisson,
+};
+
+/// Onward-infection times scheduled from an explicit generation interval
+/// distribution. See Simulation::set_generation_interval().
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GenerationSchedule {
+    r0: Real,
+    cumulative: Vec<Real>,
+    scheduled: Vec<bool>,
+    events: BTreeMap<Time, Vec<Id>>,
+}
+
+impl GenerationSchedule {
+    /// Create schedule from a (possibly unnormalized) distribution in which
+    /// distribution[0] is the weight of a generation interval of one day.
+    pub fn new(r0: Real, distribution: &[Real], n: usize) -> Self {
+        let total: Real = distribution.iter().sum();
+        let mut acc = 0.0;
+        let cumulative = distribution
+            .iter()
+            .map(|w| {
+                acc += w / total;
+                acc
+            })
+            .collect();
+
+        GenerationSchedule {
+            r0,
+            cumulative,
+            scheduled: vec![false; n],
+            events: BTreeMap::new(),
+        }
+    }
+
+    /// True if onward infections of agent were already scheduled.
+    pub fn is_scheduled(&self, id: Id) -> bool {
+        self.scheduled.get(id).cloned().unwrap_or(false)
+    }
+
+    /// Schedule onward infections of an agent infected at time t0. The number
+    /// of infections is Poisson-distributed with mean r0.
+    pub fn schedule<R: Rng>(&mut self, id: Id, t0: Time, rng: &mut R) {
+        if id >= self.scheduled.len() {
+            self.scheduled.resize(id + 1, false);
+        }
+        self.scheduled[id] = true;
+
+        for _ in 0..poisson(self.r0, rng) {
+            let u: Real = rng.gen();
+            let k = self.cumulative.iter().take_while(|&&c| c < u).count();
+            let delay = k.min(self.cumulative.len() - 1) as Time + 1;
+            self.events.entry(t0 + delay).or_default().push(id);
+        }
+    }
+
+    /// Remove and return the infectors of all events scheduled up to time t.
+    pub fn take_events(&mut self, t: Time) -> Vec<Id> {
+        let later = self.events.split_off(&(t + 1));
+        let events = std::mem::replace(&mut self.events, later);
+        return events.into_values().flatten().collect();
+    }
+}
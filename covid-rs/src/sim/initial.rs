@@ -0,0 +1,235 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::{HasAge, HasEpiModel, HasPosition, Population};
+use crate::{
+    epidemic::{EpiModel, EpiModelPopulationExt},
+    prelude::{AgeDistribution10, Real},
+};
+
+/// A strategy to set the initial epidemiological state of a population.
+///
+/// Initial conditions can be composed using tuples: `(a, b).apply(pop, rng)`
+/// applies a and then b.
+pub trait InitialCondition<P: Population> {
+    /// Modify population in place.
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R);
+}
+
+/// Contaminate n susceptible agents chosen uniformly at random.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomSeed(pub usize);
+
+/// Contaminate n susceptible agents chosen at random with probabilities
+/// proportional to the weight of their age group (0-9, 10-19, ..., 80+).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgeWeightedSeed(pub usize, pub AgeDistribution10);
+
+/// Contaminate the n susceptible agents nearest to center within the given
+/// radius. See EpiModelPopulationExt::contaminate_cluster().
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClusterSeed(pub (Real, Real), pub Real, pub usize);
+
+/// Assign each epidemiological state to the given fraction of the
+/// population. Agents are picked at random and the remaining ones are left
+/// untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompartmentFractions<M>(pub Vec<(M, Real)>);
+
+impl<P, A, B> InitialCondition<P> for (A, B)
+where
+    P: Population,
+    A: InitialCondition<P>,
+    B: InitialCondition<P>,
+{
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R) {
+        self.0.apply(population, rng);
+        self.1.apply(population, rng);
+    }
+}
+
+impl<P> InitialCondition<P> for RandomSeed
+where
+    P: Population,
+    P::State: EpiModel,
+    <P::State as EpiModel>::Clinical: Default,
+{
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R) {
+        let mut susceptibles = vec![];
+        population.each_susceptible(|id, _| susceptibles.push(id));
+        susceptibles.shuffle(rng);
+
+        let infectious = P::State::new_infectious();
+        for id in susceptibles.into_iter().take(self.0) {
+            population.map_agent_mut(id, |st| st.transfer_contamination_from(&infectious));
+        }
+    }
+}
+
+impl<P> InitialCondition<P> for AgeWeightedSeed
+where
+    P: Population,
+    P::State: EpiModel + HasAge,
+    <P::State as EpiModel>::Clinical: Default,
+{
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R) {
+        let weights = &self.1;
+
+        // Weighted sampling without replacement (Efraimidis & Spirakis)
+        let mut keys = vec![];
+        population.each_agent(&mut |id, st: &P::State| {
+            let w = weights[(st.age() as usize / 10).min(weights.len() - 1)];
+            if st.is_susceptible() && w > 0.0 {
+                keys.push((rng.gen::<Real>().powf(1.0 / w), id));
+            }
+        });
+        keys.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+        let infectious = P::State::new_infectious();
+        for (_, id) in keys.into_iter().take(self.0) {
+            population.map_agent_mut(id, |st| st.transfer_contamination_from(&infectious));
+        }
+    }
+}
+
+impl<P> InitialCondition<P> for ClusterSeed
+where
+    P: Population,
+    P::State: EpiModel + HasPosition,
+    <P::State as EpiModel>::Clinical: Default,
+{
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R) {
+        let ClusterSeed(center, radius, n) = *self;
+        population.contaminate_cluster(center, radius, n, rng);
+    }
+}
+
+impl<P, M> InitialCondition<P> for CompartmentFractions<M>
+where
+    P: Population,
+    P::State: HasEpiModel<Model = M>,
+    M: EpiModel,
+{
+    fn apply<R: Rng>(&self, population: &mut P, rng: &mut R) {
+        let n = population.count();
+        let mut ids: Vec<usize> = (0..n).collect();
+        ids.shuffle(rng);
+
+        let mut ids = ids.into_iter();
+        for (state, fraction) in self.0.iter() {
+            let m = (fraction * n as Real).round() as usize;
+            for id in ids.by_ref().take(m) {
+                population.map_agent_mut(id, |st| {
+                    st.set_epimodel(state.clone());
+                });
+            }
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{SIRm, SeirAgent, SEIR, SIR},
+        sim::HasAgePopulationExt,
+        utils::seeded_rng,
+    };
+
+    type Agent = SeirAgent<()>;
+
+    #[derive(Debug, Clone, Default)]
+    struct SpatialAgent {
+        model: SIRm,
+        position: (Real, Real),
+    }
+
+    impl HasEpiModel for SpatialAgent {
+        type Model = SIRm;
+
+        fn epimodel(&self) -> &SIRm {
+            &self.model
+        }
+
+        fn epimodel_mut(&mut self) -> &mut SIRm {
+            &mut self.model
+        }
+    }
+
+    impl HasPosition for SpatialAgent {
+        fn position(&self) -> (Real, Real) {
+            self.position
+        }
+
+        fn set_position(&mut self, value: (Real, Real)) -> &mut Self {
+            self.position = value;
+            return self;
+        }
+    }
+
+    #[test]
+    fn random_seed() {
+        let mut pop = vec![Agent::default(); 100];
+        RandomSeed(10).apply(&mut pop, &mut seeded_rng(42u64));
+        assert_eq!(pop.n_contaminated(), 10);
+
+        RandomSeed(200).apply(&mut pop, &mut seeded_rng(42u64));
+        assert_eq!(pop.n_contaminated(), 100);
+    }
+
+    #[test]
+    fn age_weighted_seed() {
+        let mut pop = vec![Agent::default(); 100];
+        pop.set_ages(5);
+        for ag in pop.iter_mut().step_by(2) {
+            ag.set_age(85);
+        }
+        let mut weights = [0.0; 9];
+        weights[8] = 1.0;
+        AgeWeightedSeed(20, weights).apply(&mut pop, &mut seeded_rng(42u64));
+
+        assert_eq!(pop.n_contaminated(), 20);
+        pop.each_contaminated(|_, ag| assert_eq!(ag.age(), 85));
+    }
+
+    #[test]
+    fn cluster_seed() {
+        let mut pop: Vec<SpatialAgent> = vec![];
+        for i in 0..400 {
+            let mut ag = SpatialAgent::default();
+            ag.set_position(((i % 20) as Real, (i / 20) as Real));
+            pop.push(ag);
+        }
+        ClusterSeed((5.0, 5.0), 2.5, 10).apply(&mut pop, &mut seeded_rng(42u64));
+
+        assert_eq!(pop.n_contaminated(), 10);
+        pop.each_contaminated(|_, ag| assert!(ag.distance_to((5.0, 5.0)) <= 2.5));
+    }
+
+    #[test]
+    fn compartment_fractions() {
+        let mut pop = vec![Agent::default(); 100];
+        let fractions = CompartmentFractions(vec![
+            (SEIR::Exposed(()), 0.1),
+            (SEIR::Infectious(()), 0.05),
+            (SEIR::Recovered(()), 0.25),
+        ]);
+        fractions.apply(&mut pop, &mut seeded_rng(42u64));
+
+        let mut counts = [0; 4];
+        pop.iter().for_each(|ag| counts[ag.index()] += 1);
+        assert_eq!(counts, [60, 10, 5, 25]);
+    }
+
+    #[test]
+    fn composed_conditions() {
+        let mut pop = vec![SpatialAgent::default(); 100];
+        let fractions = CompartmentFractions(vec![(SIR::Recovered(()), 0.5)]);
+        (fractions, RandomSeed(10)).apply(&mut pop, &mut seeded_rng(42u64));
+        assert_eq!(pop.n_recovered(), 50);
+        assert_eq!(pop.n_contaminated(), 60);
+    }
+}
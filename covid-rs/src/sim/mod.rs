@@ -1,14 +1,23 @@
 #[macro_use]
 mod macros;
 
+mod behavior;
 mod builder;
+mod ensemble;
+mod generation;
+mod initial;
 mod simulation;
 mod population;
 mod state;
+mod tracing;
+pub use behavior::BehavioralResponse;
 pub use builder::*;
+pub use ensemble::*;
+pub use initial::*;
 pub use simulation::*;
 pub use population::*;
 pub use state::*;
+pub use tracing::ContactTracing;
 
 /// Type alias describing agent handles.
 pub type Id = usize;
@@ -1,4 +1,5 @@
 use super::{Agent, DeterministicUpdate, Id, RandomUpdate, World};
+use crate::prelude::Real;
 use rand::prelude::Rng;
 use std::collections::HashSet;
 
@@ -90,6 +91,13 @@ pub trait Population {
     /// and mutable reference to State.
     fn each_agent_mut(&mut self, f: impl FnMut(Id, &mut Self::State));
 
+    /// Sum f(state) over all agents in population.
+    fn weighted_sum(&self, f: impl Fn(&Self::State) -> Real) -> Real {
+        let mut total = 0.0;
+        self.each_agent(&mut |_, st| total += f(st));
+        return total;
+    }
+
     /// Select a random id using random number generator.
     fn random_id<R: Rng>(&self, rng: &mut R) -> Id {
         rng.gen_range(0..self.count())
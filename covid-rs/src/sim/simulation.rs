@@ -1,17 +1,105 @@
 use super::{
+    behavior::{BehaviorState, BehavioralResponse},
+    generation::GenerationSchedule,
+    initial::InitialCondition,
     population::{OwnsStateSlice, Population},
-    state::RandomUpdate,
+    state::{HasAge, RandomUpdate},
+    tracing::{ContactTracing, TracingState},
+    Id,
 };
 use crate::{
     epidemic::*,
-    params::{EpiParamsFull, EpiParamsLocalT, FromLocalParams, LocalBind},
+    models::{BoosterPolicy, SimpleAgent, SimpleAgentPopulationExt, VaccinationCampaign},
+    params::{
+        Compartment, EpiParamsCached, EpiParamsFull, EpiParamsLocalT, FromLocalParams, LocalBind,
+    },
     prelude::*,
-    trackers::{EpiTracker, Tracker},
+    trackers::{CsvOptions, EpiTracker, FlowTracker, Tracker},
+    utils::{curve, poisson, EwmaAcc, Sampling, SimRng},
 };
 use getset::{Getters, MutGetters};
 use log::{debug, trace};
-use rand::prelude::{SeedableRng, SmallRng};
-use std::{cell::RefCell, fmt::Debug};
+use ndarray::Array2;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    prelude::{Rng, RngCore, SeedableRng, SliceRandom, SmallRng},
+};
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{cell::RefCell, fmt::Debug, fs, io, path::Path};
+
+/// Diagnostics about the quality of a calibration run. See
+/// Simulation::calibrate_sampler_from_cases().
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    /// Mean relative error between the produced cases and the target curve.
+    pub mean_relative_error: Real,
+    /// Number of steps in which contacts were clamped at the maximum value.
+    pub max_contacts_steps: usize,
+    /// True if contacts were clamped at the maximum value in consecutive
+    /// steps, i.e., the target stayed out of reach even after saturating
+    /// contacts. The fit should not be trusted in this case.
+    pub max_contacts_hit: bool,
+    /// Probability of infection of the sampler after calibration.
+    pub final_prob_infection: Real,
+}
+
+/// Summary of a simulation run, computed from the history of epicurves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    /// Maximum number of agents in each compartment.
+    pub peaks: Vec<usize>,
+    /// Row of the history in which each peak first occurred. Row 0 is the
+    /// initial state, so compartments that never grow peak at step 0.
+    pub peak_steps: Vec<usize>,
+    /// Number of agents that left the susceptible compartment by the end of
+    /// the simulation.
+    pub final_size: usize,
+    /// Number of dead agents at the end of the simulation.
+    pub deaths: usize,
+}
+
+/// Serializable state of a simulation. See Simulation::checkpoint().
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationSnapshot<W, S, PS> {
+    population: Vec<S>,
+    infections_per_agent: Vec<u16>,
+    infections_per_iter: Vec<usize>,
+    infection_times: Vec<Option<Time>>,
+    contagious_per_iter: Vec<usize>,
+    susceptible_replenishment: bool,
+    infections_by_source: Vec<usize>,
+    exposure_times: Option<Vec<Option<Time>>>,
+    tracing: Option<TracingState>,
+    behavior: Option<BehaviorState>,
+    generation: Option<GenerationSchedule>,
+    flows: Option<Array2<usize>>,
+    incidence: Option<FlowTracker>,
+    transmission_log: Option<Vec<(Id, Id, usize)>>,
+    per_agent_rng: bool,
+    reseeding: Option<(usize, S)>,
+    imported_per_iter: Vec<usize>,
+    params: W,
+    sampler: PS,
+    reporter: EpiTracker<Vec<S>>,
+    rng: SimRng,
+}
+
+/// Parameters that control the exit of an agent from a given compartment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionInspection {
+    /// Compartment whose exit is inspected.
+    pub compartment: Compartment,
+    /// Mean time spent in the compartment.
+    pub period: Real,
+    /// Daily probability of leaving the compartment.
+    pub transition_prob: Real,
+    /// Epidemiological params bound to the agent.
+    pub params: EpiParamsFull<Real>,
+}
+
+/// Function registered with Simulation::on_step().
+type StepUpdate<W, S> = Box<dyn FnMut(usize, &mut W, &mut Vec<S>)>;
 
 /// Simulation stores a population of agents and some objects responsible for
 /// controlling the dynamics of those Agents.
@@ -23,6 +111,25 @@ pub struct Simulation<W, S, PS> {
     infections_per_agent: Vec<u16>,
     #[getset(get = "pub")]
     infections_per_iter: Vec<usize>,
+    #[getset(get = "pub")]
+    infection_times: Vec<Option<Time>>,
+    contagious_per_iter: Vec<usize>,
+    #[getset(get = "pub")]
+    susceptible_replenishment: bool,
+    #[getset(get = "pub")]
+    infections_by_source: Vec<usize>,
+    exposure_times: Option<Vec<Option<Time>>>,
+    tracing: Option<TracingState>,
+    behavior: Option<BehaviorState>,
+    generation: Option<GenerationSchedule>,
+    flows: Option<Array2<usize>>,
+    incidence: Option<FlowTracker>,
+    transmission_log: Option<Vec<(Id, Id, usize)>>,
+    per_agent_rng: bool,
+    parallel_update: Option<fn(&mut Self)>,
+    reseeding: Option<(usize, S)>,
+    #[getset(get = "pub")]
+    imported_per_iter: Vec<usize>,
     #[getset(get = "pub", get_mut = "pub")]
     params: RefCell<W>,
 
@@ -31,13 +138,15 @@ pub struct Simulation<W, S, PS> {
     reporter: EpiTracker<Vec<S>>,
     world_update: Vec<Box<dyn FnMut(&mut W, &Vec<S>)>>,
     population_update: Vec<Box<dyn FnMut(&W, &mut Vec<S>)>>,
-    rng: RefCell<SmallRng>,
+    step_update: Vec<(usize, StepUpdate<W, S>)>,
+    rng: RefCell<SimRng>,
 }
 
 impl<'a, W, S, PS> Simulation<W, S, PS>
 where
     PS: PopulationSampler<Vec<S>>,
     W: LocalBind<S>,
+    W::Local: EpiParamsLocalT,
     S: EpiModel + RandomUpdate<W::Local> + Debug,
 {
     /// Create new simulation from population and sampler.
@@ -46,12 +155,28 @@ where
             reporter: EpiTracker::new(&population),
             infections_per_agent: vec![0].repeat(population.len()),
             infections_per_iter: vec![],
+            infection_times: vec![None; population.len()],
+            contagious_per_iter: vec![],
+            susceptible_replenishment: false,
+            infections_by_source: vec![0; S::CARDINALITY],
+            exposure_times: None,
+            tracing: None,
+            behavior: None,
+            generation: None,
+            flows: None,
+            incidence: None,
+            transmission_log: None,
+            per_agent_rng: false,
+            parallel_update: None,
+            reseeding: None,
+            imported_per_iter: vec![],
             population,
             params: RefCell::new(params),
             sampler,
             world_update: vec![],
             population_update: vec![],
-            rng: RefCell::new(SmallRng::from_entropy()),
+            step_update: vec![],
+            rng: RefCell::new(SimRng::from_entropy()),
         }
     }
 
@@ -66,35 +191,170 @@ where
             population: self.population.clone(),
             infections_per_agent: self.infections_per_agent.clone(),
             infections_per_iter: self.infections_per_iter.clone(),
+            infection_times: self.infection_times.clone(),
+            contagious_per_iter: self.contagious_per_iter.clone(),
+            susceptible_replenishment: self.susceptible_replenishment,
+            infections_by_source: self.infections_by_source.clone(),
+            exposure_times: self.exposure_times.clone(),
+            tracing: self.tracing.clone(),
+            behavior: self.behavior.clone(),
+            generation: self.generation.clone(),
+            flows: self.flows.clone(),
+            incidence: self.incidence.clone(),
+            transmission_log: self.transmission_log.clone(),
+            per_agent_rng: self.per_agent_rng,
+            parallel_update: self.parallel_update,
+            reseeding: self.reseeding.clone(),
+            imported_per_iter: self.imported_per_iter.clone(),
             params: self.params.clone(),
             sampler: self.sampler.clone(),
             reporter: self.reporter.copy(),
             world_update: vec![],
             population_update: vec![],
+            step_update: vec![],
             rng: self.rng.clone(),
         }
     }
 
+    /// Branch simulation into an independent copy that shares the same state,
+    /// random number generator and history. Useful to run counterfactual
+    /// scenarios from a common starting point.
+    ///
+    /// Update functions cannot be cloned and must be registered again in each
+    /// branch, which is also the natural place to apply interventions.
+    pub fn branch(&self) -> Self
+    where
+        W: Clone,
+        PS: Clone,
+    {
+        return self.copy();
+    }
+
+    /// Save the full state of simulation, including the history of epicurves
+    /// and the state of the random number generator, so it can be resumed
+    /// later with restore().
+    ///
+    /// Update functions and user defined trackers cannot be serialized and are
+    /// not stored in the snapshot. They must be registered again in the
+    /// restored simulation. Parallel updates must also be enabled again, but
+    /// this does not change results.
+    pub fn checkpoint(&self) -> SimulationSnapshot<W, S, PS>
+    where
+        W: Clone,
+        PS: Clone,
+    {
+        SimulationSnapshot {
+            population: self.population.clone(),
+            infections_per_agent: self.infections_per_agent.clone(),
+            infections_per_iter: self.infections_per_iter.clone(),
+            infection_times: self.infection_times.clone(),
+            contagious_per_iter: self.contagious_per_iter.clone(),
+            susceptible_replenishment: self.susceptible_replenishment,
+            infections_by_source: self.infections_by_source.clone(),
+            exposure_times: self.exposure_times.clone(),
+            tracing: self.tracing.clone(),
+            behavior: self.behavior.clone(),
+            generation: self.generation.clone(),
+            flows: self.flows.clone(),
+            incidence: self.incidence.clone(),
+            transmission_log: self.transmission_log.clone(),
+            per_agent_rng: self.per_agent_rng,
+            reseeding: self.reseeding.clone(),
+            imported_per_iter: self.imported_per_iter.clone(),
+            params: self.params.borrow().clone(),
+            sampler: self.sampler.clone(),
+            reporter: self.reporter.copy(),
+            rng: self.rng.borrow().clone(),
+        }
+    }
+
+    /// Create simulation from a snapshot created by checkpoint().
+    pub fn restore(snapshot: SimulationSnapshot<W, S, PS>) -> Self {
+        Simulation {
+            population: snapshot.population,
+            infections_per_agent: snapshot.infections_per_agent,
+            infections_per_iter: snapshot.infections_per_iter,
+            infection_times: snapshot.infection_times,
+            contagious_per_iter: snapshot.contagious_per_iter,
+            susceptible_replenishment: snapshot.susceptible_replenishment,
+            infections_by_source: snapshot.infections_by_source,
+            exposure_times: snapshot.exposure_times,
+            tracing: snapshot.tracing,
+            behavior: snapshot.behavior,
+            generation: snapshot.generation,
+            flows: snapshot.flows,
+            incidence: snapshot.incidence,
+            transmission_log: snapshot.transmission_log,
+            per_agent_rng: snapshot.per_agent_rng,
+            parallel_update: None,
+            reseeding: snapshot.reseeding,
+            imported_per_iter: snapshot.imported_per_iter,
+            params: RefCell::new(snapshot.params),
+            sampler: snapshot.sampler,
+            reporter: snapshot.reporter,
+            world_update: vec![],
+            population_update: vec![],
+            step_update: vec![],
+            rng: RefCell::new(snapshot.rng),
+        }
+    }
+
     /// Run simulation for the given number of steps and return the number of
     /// new cases.
     pub fn steps(&mut self, n_steps: usize) -> usize {
         let mut cases = 0;
         for _ in 0..n_steps {
-            // Default updates
-            self.update_agents();
-            cases += self.update_pairs();
+            cases += self.step(None);
+        }
+        return cases;
+    }
 
-            // Arbitrary updates
-            let mut params = self.params.borrow_mut();
-            for f in self.population_update.iter_mut() {
-                f(&params, &mut self.population);
-            }
-            for f in self.world_update.iter_mut() {
-                f(&mut params, &self.population);
-            }
-            self.reporter.track(&self.population);
+    /// Run a single simulation step and return the (infector, infectee) pairs
+    /// of all infections that occurred during the step.
+    pub fn step_collecting_infections(&mut self) -> Vec<(Id, Id)> {
+        let mut infections = vec![];
+        self.step(Some(&mut infections));
+        return infections;
+    }
+
+    /// Run a single step, optionally collecting infection pairs.
+    fn step(&mut self, infections: Option<&mut Vec<(Id, Id)>>) -> usize {
+        let step = self.infections_per_iter.len();
+        self.params.get_mut().bind_step(step);
+        if let Some(incidence) = &mut self.incidence {
+            incidence.step();
         }
 
+        // Default updates
+        self.update_agents();
+        let contagious = self.population.iter().filter(|ag| ag.is_contagious());
+        self.contagious_per_iter.push(contagious.count());
+        let cases = if self.generation.is_some() {
+            self.update_scheduled(infections)
+        } else {
+            self.update_pairs(infections)
+        };
+        self.sampler.advance_step();
+        self.update_tracing();
+        self.update_behavior();
+
+        // Arbitrary updates
+        let mut params = self.params.borrow_mut();
+        for f in self.population_update.iter_mut() {
+            f(&params, &mut self.population);
+        }
+        for f in self.world_update.iter_mut() {
+            f(&mut params, &self.population);
+        }
+        for (every, f) in self.step_update.iter_mut() {
+            if step.is_multiple_of(*every) {
+                f(step, &mut params, &mut self.population);
+            }
+        }
+        drop(params);
+        let imported = self.update_reseeding(step as Time);
+        self.imported_per_iter.push(imported);
+        self.reporter.track(&self.population);
         return cases;
     }
 
@@ -106,45 +366,537 @@ where
         return self;
     }
 
+    /// Run simulation until cond(self) is true or max_steps steps are
+    /// executed and return the number of steps actually run.
+    ///
+    /// The condition is checked before each step, hence no step is executed
+    /// if it already holds. See extinct() and prevalence_above() for common
+    /// stopping conditions.
+    pub fn run_until(&mut self, cond: impl Fn(&Self) -> bool, max_steps: usize) -> usize {
+        for n in 0..max_steps {
+            if cond(self) {
+                return n;
+            }
+            self.step(None);
+        }
+        return max_steps;
+    }
+
+    /// True if no agent has an ongoing infection, i.e., all contaminated
+    /// agents are recovered or dead. Exposed agents count as infected, so the
+    /// epidemic is not considered extinct while latent infections remain.
+    pub fn extinct(&self) -> bool {
+        return !self
+            .population
+            .iter()
+            .any(|ag| ag.is_contaminated() && !ag.is_recovered() && !ag.is_dead());
+    }
+
+    /// True if the fraction of contagious agents is above frac.
+    pub fn prevalence_above(&self, frac: Real) -> bool {
+        let n = self
+            .population
+            .iter()
+            .filter(|ag| ag.is_contagious())
+            .count();
+        return n as Real > frac * self.population.len() as Real;
+    }
+
     /// Self-update agents. Resolve the natural evolution of all agents
     fn update_agents(&mut self) {
+        if let Some(update) = self.parallel_update {
+            return update(self);
+        }
         let rng = &mut *self.rng.borrow_mut();
+        let base_seed = if self.per_agent_rng {
+            Some(rng.next_u64())
+        } else {
+            None
+        };
         let mut params = self.params.borrow_mut();
-        for obj in self.population.iter_mut() {
+        for (id, obj) in self.population.iter_mut().enumerate() {
             params.bind_to_object(obj);
-            obj.random_update(params.local(), rng);
+            let transition = match base_seed {
+                Some(seed) => obj.random_update_tracked(params.local(), &mut agent_rng(seed, id)),
+                None => obj.random_update_tracked(params.local(), rng),
+            };
+            if let Some((from, to)) = transition {
+                record_flow(&mut self.flows, &mut self.incidence, from, to);
+            }
         }
     }
 
     /// Simulate agent interactions, allowing new infections to occur.
-    fn update_pairs(&mut self) -> usize {
-        let rng = &mut *self.rng.borrow_mut();
+    fn update_pairs(&mut self, mut infections: Option<&mut Vec<(Id, Id)>>) -> usize {
+        let step = self.infections_per_iter.len() as Time;
+        let pairs = {
+            let rng = &mut *self.rng.borrow_mut();
+            let mut pairs =
+                self.sampler
+                    .sample_infection_pairs_at(step as usize, &self.population, rng);
+            pairs.extend(self.sample_reinfection_pairs(rng));
+            pairs
+        };
+
         let mut cases = 0usize;
+        for (i, j) in pairs {
+            cases += self.realize_contact(i, j, step, false, infections.as_deref_mut()) as usize;
+        }
+        self.infections_per_iter.push(cases);
+        return cases;
+    }
+
+    /// Realize a contact of agent i with agent j at the given step and return
+    /// true if it infected j.
+    ///
+    /// Contacts with isolated agents are skipped. Otherwise, the contact is
+    /// registered for exposure and contact tracing and j is infected subject
+    /// to its susceptibility or, if j is not susceptible, to its reinfection
+    /// odds. New infections are recorded in all trackers and replenished if
+    /// susceptible replenishment is enabled.
+    ///
+    /// Scheduled contacts come from the generation interval, which overrides
+    /// the natural history, and do not require i to be contagious.
+    fn realize_contact(
+        &mut self,
+        i: Id,
+        j: Id,
+        step: Time,
+        scheduled: bool,
+        infections: Option<&mut Vec<(Id, Id)>>,
+    ) -> bool {
+        if i == j || self.population[i].is_isolated() || self.population[j].is_isolated() {
+            return false;
+        }
+        if let Some(tracing) = &self.tracing {
+            if tracing.is_isolated(i, step) || tracing.is_isolated(j, step) {
+                return false;
+            }
+        }
+        if let Some(times) = &mut self.exposure_times {
+            times[j] = Some(step);
+        }
+        let rng = &mut *self.rng.borrow_mut();
+        let mut infected = false;
+        let mut fresh = None;
+        if let Some((src, dest)) = self.population.get_pair_mut(i, j) {
+            let replenish = self.susceptible_replenishment && dest.is_susceptible();
+            let before = replenish.then(|| dest.clone());
+            let susceptibility = dest.susceptibility_to(src);
+            let resisted = if dest.is_susceptible() {
+                susceptibility <= 0.0 || (susceptibility < 1.0 && !rng.gen_bool(susceptibility))
+            } else {
+                let mut params = self.params.borrow_mut();
+                params.bind_to_object(dest);
+                let odds =
+                    dest.reinfection_odds() * params.local().relative_susceptibility_recovered();
+                !(odds > 0.0 && rng.gen_bool(odds.min(1.0)))
+            };
+            let from = dest.index();
+            infected = !resisted
+                && if scheduled {
+                    dest.transfer_contamination_from(src)
+                } else if dest.is_susceptible() {
+                    dest.contaminate_from(src)
+                } else {
+                    src.is_contagious() && dest.transfer_contamination_from(src)
+                };
+            if infected {
+                record_flow(&mut self.flows, &mut self.incidence, from, dest.index());
+                self.infections_per_agent[i] += 1;
+                self.infections_by_source[src.index()] += 1;
+                self.infection_times[j].get_or_insert(step);
+                if let Some(log) = &mut self.transmission_log {
+                    log.push((i, j, step as usize));
+                }
+                if let Some(pairs) = infections {
+                    pairs.push((i, j));
+                }
+                fresh = before;
+            }
+        }
+        if let Some(tracing) = &mut self.tracing {
+            tracing.register_contact(i, j, infected, step);
+        }
+        if let Some(agent) = fresh {
+            self.population.push(agent);
+            self.infections_per_agent.push(0);
+            self.infection_times.push(None);
+            if let Some(times) = &mut self.exposure_times {
+                times.push(None);
+            }
+            if let Some(tracing) = &mut self.tracing {
+                tracing.push_agent();
+            }
+        }
+        return infected;
+    }
 
-        for (i, j) in self.sampler.sample_infection_pairs(&self.population, rng) {
-            if i == j {
-                continue;
+    /// Sample contacts between contagious and recovered agents that may lead
+    /// to reinfection.
+    ///
+    /// Samplers only pair contagious agents with susceptible ones, so these
+    /// contacts are drawn assuming homogeneous mixing, as in the default
+    /// PopulationSampler::infection_hazard(). Only recovered agents with
+    /// positive reinfection odds and relative_susceptibility_recovered are
+    /// considered. The reinfection draw happens when the pair is realized.
+    fn sample_reinfection_pairs(&self, rng: &mut impl Rng) -> Vec<(Id, Id)> {
+        let mut params = self.params.borrow_mut();
+        let mut candidates = vec![];
+        for (j, ag) in self.population.iter().enumerate() {
+            if !ag.is_susceptible() && ag.reinfection_odds() > 0.0 {
+                params.bind_to_object(ag);
+                if params.local().relative_susceptibility_recovered() > 0.0 {
+                    candidates.push(j);
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return vec![];
+        }
+
+        let prob = self.sampler.prob_infection();
+        let weights: Vec<Real> = self
+            .population
+            .iter()
+            .map(|ag| (prob * ag.contagion_odds()).min(1.0))
+            .collect();
+        let sources = match WeightedIndex::new(&weights) {
+            Ok(sources) => sources,
+            Err(_) => return vec![],
+        };
+        let pressure: Real = weights.iter().sum();
+        let n = self.population.len() as Real;
+        let hazard = 1.0 - (-pressure * self.sampler.contacts() / n).exp();
+        let mut pairs = vec![];
+        for j in candidates {
+            if rng.gen_bool(hazard.clamp(0.0, 1.0)) {
+                pairs.push((sources.sample(rng), j));
             }
-            if let Some((src, dest)) = self.population.get_pair_mut(i, j) {
-                if dest.contaminate_from(src) {
-                    cases += 1;
-                    self.infections_per_agent[i] += 1;
+        }
+        return pairs;
+    }
+
+    /// Realize onward infections scheduled from the generation interval.
+    fn update_scheduled(&mut self, mut infections: Option<&mut Vec<(Id, Id)>>) -> usize {
+        let step = self.infections_per_iter.len() as Time;
+        let n = self.population.len();
+        let events = {
+            let rng = &mut *self.rng.borrow_mut();
+            let schedule = self.generation.as_mut().unwrap();
+            for (id, agent) in self.population.iter().enumerate() {
+                if agent.is_contaminated() && !schedule.is_scheduled(id) {
+                    let t0 = self.infection_times[id].unwrap_or(step);
+                    schedule.schedule(id, t0, rng);
                 }
             }
+            schedule.take_events(step)
+        };
+
+        let mut cases = 0usize;
+        for i in events {
+            let j = self.rng.borrow_mut().gen_range(0..n);
+            cases += self.realize_contact(i, j, step, true, infections.as_deref_mut()) as usize;
         }
         self.infections_per_iter.push(cases);
         return cases;
     }
 
+    /// Detect cases and trace contacts if contact tracing is enabled.
+    ///
+    /// Samplers only produce pairs that may result in infection, so the
+    /// remaining contacts of contagious agents are logged here, assuming
+    /// homogeneous mixing at the contact rate of the sampler. The isolation
+    /// flag of agents is updated from the tracing state, so samplers skip
+    /// isolated agents in the next step.
+    fn update_tracing(&mut self) {
+        if let Some(tracing) = &mut self.tracing {
+            let rng = &mut *self.rng.borrow_mut();
+            let now = self.infections_per_iter.len() as Time;
+            let step = now - 1;
+            let n = self.population.len();
+            let contacts = self.sampler.contacts();
+            for (i, agent) in self.population.iter().enumerate() {
+                if !agent.is_contagious() || tracing.is_isolated(i, step) {
+                    continue;
+                }
+                for _ in 0..poisson(contacts, rng) {
+                    let j = rng.gen_range(0..n);
+                    if j != i && !tracing.is_isolated(j, step) {
+                        tracing.register_contact(i, j, false, step);
+                    }
+                }
+            }
+            let log = self.transmission_log.as_deref().unwrap_or_default();
+            tracing.update(&self.population, log, now, rng);
+            for (id, agent) in self.population.iter_mut().enumerate() {
+                agent.isolate(tracing.is_isolated(id, now));
+            }
+        }
+    }
+
+    /// Contaminate susceptible agents until the number of contagious agents
+    /// reaches the floor set by with_reseeding() and return the number of
+    /// imported cases. Imported agents are infected at the given step and
+    /// their transitions are recorded in flows and incidence, but not in
+    /// infections_per_iter().
+    fn update_reseeding(&mut self, step: Time) -> usize {
+        let (min_active, source) = match &self.reseeding {
+            Some(data) => data,
+            None => return 0,
+        };
+        let active = self
+            .population
+            .iter()
+            .filter(|ag| ag.is_contagious())
+            .count();
+        if active >= *min_active {
+            return 0;
+        }
+        let mut susceptible: Vec<Id> = (0..self.population.len())
+            .filter(|&id| self.population[id].is_susceptible())
+            .collect();
+        susceptible.shuffle(&mut *self.rng.borrow_mut());
+        susceptible.truncate(min_active - active);
+        for &id in susceptible.iter() {
+            let agent = &mut self.population[id];
+            let from = agent.index();
+            agent.transfer_contamination_from(source);
+            agent.force_infectious(false);
+            record_flow(&mut self.flows, &mut self.incidence, from, agent.index());
+            self.infection_times[id].get_or_insert(step);
+        }
+        return susceptible.len();
+    }
+
+    /// Adjust contacts to recent deaths if a behavioral response is enabled.
+    fn update_behavior(&mut self) {
+        if let Some(behavior) = &mut self.behavior {
+            let n = self.population.len();
+            let contacts = behavior.update(self.population.n_dead(), n);
+            self.sampler.set_contacts(contacts);
+        }
+    }
+
+    /// Set the initial state of population from the given initial condition.
+    pub fn with_initial_condition(&mut self, cond: &impl InitialCondition<Vec<S>>) -> &mut Self {
+        cond.apply(&mut self.population, &mut *self.rng.borrow_mut());
+        return self;
+    }
+
+    /// Start recording the last day each agent had a contact with an
+    /// infectious individual. See days_since_exposure().
+    pub fn track_exposures(&mut self) -> &mut Self {
+        if self.exposure_times.is_none() {
+            self.exposure_times = Some(vec![None; self.population.len()]);
+        }
+        return self;
+    }
+
+    /// Number of days since the last contact of agent with an infectious
+    /// individual, as sampled by the sampler.
+    ///
+    /// Return None if agent had no contacts or if exposures are not tracked.
+    pub fn days_since_exposure(&self, id: Id) -> Option<Time> {
+        let now = self.infections_per_iter.len() as Time;
+        let last = (*self.exposure_times.as_ref()?.get(id)?)?;
+        return Some(now - last);
+    }
+
+    /// Register a tracker that receives the population at the end of each
+    /// step, e.g., a CsvSink that streams epicurves to a file. Trackers are
+    /// not copied by copy() or branch().
+    pub fn add_tracker(&mut self, tracker: impl Tracker<Vec<S>> + 'static) -> &mut Self {
+        self.reporter.add_reporter(tracker);
+        return self;
+    }
+
+    /// Start accumulating the number of agents that traverse each edge between
+    /// compartments. See sankey_flows().
+    pub fn track_flows(&mut self) -> &mut Self {
+        if self.flows.is_none() {
+            self.flows = Some(Array2::zeros((S::CARDINALITY, S::CARDINALITY)));
+        }
+        return self;
+    }
+
+    /// Start recording the number of transitions between each pair of
+    /// compartments at each step. See incidence().
+    pub fn track_incidence(&mut self) -> &mut Self {
+        if self.incidence.is_none() {
+            self.incidence = Some(FlowTracker::new(S::CARDINALITY));
+        }
+        return self;
+    }
+
+    /// Daily incidence of each transition, if tracked. Steps before the call
+    /// to track_incidence() are not recorded.
+    pub fn incidence(&self) -> Option<&FlowTracker> {
+        self.incidence.as_ref()
+    }
+
+    /// Return the total number of transitions between each pair of
+    /// compartments as (from, to, count) triples, suitable for building a
+    /// Sankey diagram. Compartments are identified by their index and edges
+    /// without any transition are omitted.
+    ///
+    /// Return an empty vector if flows are not tracked.
+    pub fn sankey_flows(&self) -> Vec<(usize, usize, usize)> {
+        let mut data = vec![];
+        if let Some(flows) = &self.flows {
+            for ((from, to), &count) in flows.indexed_iter() {
+                if count > 0 {
+                    data.push((from, to, count));
+                }
+            }
+        }
+        return data;
+    }
+
+    /// Enable contact tracing with the given policy. Isolated agents do not
+    /// participate in any contacts. See ContactTracing for details.
+    ///
+    /// Forward tracing uses the transmission log, so this also starts
+    /// tracking transmissions.
+    pub fn set_contact_tracing(&mut self, policy: ContactTracing) -> &mut Self {
+        self.tracing = Some(TracingState::new(policy, self.population.len()));
+        return self.track_transmissions();
+    }
+
+    /// Reduce contacts in response to recent deaths. The current contact rate
+    /// of the sampler is used as baseline, so later changes to the sampler
+    /// contacts are overridden. See BehavioralResponse for details.
+    pub fn set_behavioral_response(&mut self, response: BehavioralResponse) -> &mut Self {
+        if let Some(behavior) = self.behavior.take() {
+            self.sampler.set_contacts(behavior.baseline());
+        }
+        if response.sensitivity != 0.0 {
+            let baseline = self.sampler.contacts();
+            let n_dead = self.population.n_dead();
+            self.behavior = Some(BehaviorState::new(response, baseline, n_dead));
+        }
+        return self;
+    }
+
+    /// Drive transmission by an explicit generation interval distribution,
+    /// rather than by the sampler and the compartmental dwell times.
+    ///
+    /// When an agent is infected, it schedules a Poisson number of onward
+    /// infections with mean r0, each occurring after a delay sampled from
+    /// distribution, in which distribution[0] is the (unnormalized) weight of
+    /// a delay of one day. At the scheduled time, a random agent is contacted
+    /// and infected as in sampler-driven transmission, i.e., subject to its
+    /// susceptibility or reinfection odds. The natural history of agents is
+    /// not affected. Scheduled contacts are skipped if either agent is isolated
+    /// by contact tracing.
+    ///
+    /// Panics if distribution is empty, has negative or non-finite weights or
+    /// if all weights are zero.
+    pub fn set_generation_interval(&mut self, r0: Real, distribution: &[Real]) -> &mut Self {
+        assert!(
+            distribution.iter().all(|w| w.is_finite() && *w >= 0.0),
+            "generation interval weights must be finite and non-negative"
+        );
+        assert!(
+            distribution.iter().sum::<Real>() > 0.0,
+            "generation interval must have a positive total weight"
+        );
+        let n = self.population.len();
+        self.generation = Some(GenerationSchedule::new(r0, distribution, n));
+        return self;
+    }
+
+    /// True if agent is currently isolated, either by contact tracing or by
+    /// its own isolation flag. Isolation by contact tracing expires after the
+    /// isolation_days of the tracing policy.
+    pub fn is_isolated(&self, id: Id) -> bool {
+        let now = self.infections_per_iter.len() as Time;
+        return self.population.get(id).is_some_and(|ag| ag.is_isolated())
+            || self
+                .tracing
+                .as_ref()
+                .is_some_and(|t| t.is_isolated(id, now));
+    }
+
+    /// Number of agents currently isolated by contact tracing.
+    pub fn n_isolated(&self) -> usize {
+        let now = self.infections_per_iter.len() as Time;
+        return self.tracing.as_ref().map_or(0, |t| t.n_isolated(now));
+    }
+
+    /// If true, each infected susceptible is immediately replaced by a new
+    /// susceptible agent (a copy of its state before infection). This keeps
+    /// the susceptible pool from depleting and is useful to measure the force
+    /// of infection. Notice the population grows with each new infection, but
+    /// not with reinfections of recovered agents.
+    ///
+    /// New agents are appended to the population, so the sampler must support
+    /// population growth (see PopulationSampler::supports_population_growth).
+    /// Panics otherwise.
+    pub fn set_susceptible_replenishment(&mut self, value: bool) -> &mut Self {
+        assert!(
+            !value || self.sampler.supports_population_growth(),
+            "sampler does not support susceptible replenishment"
+        );
+        self.susceptible_replenishment = value;
+        return self;
+    }
+
+    /// Keep at least min_active contagious agents while there are susceptibles
+    /// left, which prevents stochastic extinction early in the epidemic.
+    ///
+    /// At the end of each step, random susceptible agents are made infectious
+    /// to restore the floor. These are counted as imported cases in
+    /// imported_per_iter() and are not included in infections_per_iter().
+    /// Their infection time is recorded, so they are not mistaken for initial
+    /// seeds by realized_r0_first_generation(). Reseeding is disabled by
+    /// default.
+    pub fn with_reseeding(&mut self, min_active: usize) -> &mut Self
+    where
+        S::Clinical: Default,
+    {
+        self.reseeding = Some((min_active, S::new_infectious()));
+        return self;
+    }
+
     /// Set seed for random number generator
     pub fn seed(&mut self, seed: u64) -> &mut Self {
-        self.rng.replace(SmallRng::seed_from_u64(seed));
+        self.rng.replace(SimRng::seed_from_u64(seed));
         return self;
     }
 
-    /// Set seed for random number generator
+    /// Register a function that is executed at the end of every step whose
+    /// index is a multiple of every, starting with step 0. It receives the step
+    /// index and can modify both params and population, which is useful to
+    /// implement interventions such as vaccination campaigns or lockdowns.
+    ///
+    /// Step functions run after other update functions and before trackers.
+    pub fn on_step(
+        &mut self,
+        every: usize,
+        f: impl FnMut(usize, &mut W, &mut Vec<S>) + 'static,
+    ) -> &mut Self {
+        assert!(every > 0, "every must be positive");
+        self.step_update.push((every, Box::new(f)));
+        return self;
+    }
+
+    /// If true, each agent is updated with its own random number generator,
+    /// seeded from a base seed drawn from the simulation generator at each
+    /// step and from the agent id. This makes results independent of the
+    /// order in which agents are updated. See set_parallel().
+    pub fn set_per_agent_rng(&mut self, value: bool) -> &mut Self {
+        self.per_agent_rng = value;
+        if !value {
+            self.parallel_update = None;
+        }
+        return self;
+    }
+
+    /// Seed random number generator from the given generator, which is not
+    /// modified.
     pub fn seed_from(&mut self, rng: &SmallRng) -> &mut Self {
-        self.rng.replace(rng.clone());
+        self.rng.replace(SimRng::from_rng(rng.clone()).unwrap());
         return self;
     }
 
@@ -194,88 +946,429 @@ where
         return Some(row.iter().map(|x| *x as Real * factor).collect());
     }
 
+    /// Return the full history of epicurves as an array in which each row is
+    /// the epistate at a given iteration. The first row is the initial state,
+    /// hence the shape is (n_steps + 1, S::CARDINALITY).
+    pub fn history_array(&self, normalize: bool) -> Array2<Real> {
+        let factor = self.normalization_factor(normalize);
+        let table = self.reporter.epicurves();
+        let shape = (table.nrows(), table.ncols());
+        return Array2::from_shape_fn(shape, |(i, j)| {
+            table.get(i, j).unwrap_or(0) as Real * factor
+        });
+    }
+
+    /// Return the peak prevalence of each compartment and when it occurred,
+    /// together with the final size and total deaths. See Summary.
+    pub fn summary(&self) -> Summary {
+        let table = self.reporter.epicurves();
+        let mut peaks = vec![0; table.ncols()];
+        let mut peak_steps = vec![0; table.ncols()];
+        for i in 0..table.nrows() {
+            for (j, (peak, peak_step)) in peaks.iter_mut().zip(&mut peak_steps).enumerate() {
+                let value = table.get(i, j).unwrap_or(0);
+                if value > *peak {
+                    *peak = value;
+                    *peak_step = i;
+                }
+            }
+        }
+        let tip = self.reporter.tip();
+        let deaths = if S::D == ABSENT { 0 } else { tip[S::D] };
+        return Summary {
+            peaks,
+            peak_steps,
+            final_size: self.population.len() - tip[S::S],
+            deaths,
+        };
+    }
+
     /// Render the epicurve for the current simulation
     pub fn render_epicurve_csv(&self, head: &str) -> String {
+        self.render_epicurve_csv_with(head, &Default::default())
+    }
+
+    /// Like render_epicurve_csv(), but with custom formatting options.
+    pub fn render_epicurve_csv_with(&self, head: &str, opts: &CsvOptions) -> String {
         let mut head = head.to_string();
         let mut infections = vec![0];
         infections.extend(self.infections_per_iter.iter());
-        head.push_str(",cases");
+        head.push(opts.separator);
+        head.push_str("cases");
         return self
             .reporter
             .epicurves()
             .with_column(infections.iter().cloned(), true)
-            .render_csv(&head, ',');
+            .render_csv_with(&head, opts);
     }
 
-    /// Used internally to normalize (or not) results
-    fn normalization_factor(&self, normalize: bool) -> Real {
-        if normalize {
-            1.0 / self.count() as Real
-        } else {
-            1.0
-        }
+    /// Estimate the effective reproduction number at each iteration from the
+    /// incidence of new cases, deconvolved with the given generation interval
+    /// distribution.
+    ///
+    /// See utils::curve::renewal_rt() for details.
+    pub fn renewal_rt(&self, generation_interval: &[Real]) -> Vec<Real> {
+        let incidence: Vec<Real> = self
+            .infections_per_iter
+            .iter()
+            .map(|&x| x as Real)
+            .collect();
+        return curve::renewal_rt(&incidence, generation_interval);
     }
 
-    /// Initialize simulation and calibrate sampler from a curve of cases.
+    /// Return the exponentially weighted moving average of the number of new
+    /// cases at each iteration, with the given decay factor alpha.
     ///
-    /// This is a somewhat simplistic view on model calibration. We just run
-    /// the simulation normally but at each step we recalibrate the sampler
-    /// to produce the same number of infections as expected from the epidemic
-    /// curve.
-    pub fn calibrate_sampler_from_cases(&mut self, cases: &[Real]) -> &mut Self
-    where
-        S::Clinical: Default,
-    {
-        // TODO: create calibrator struct
-        let alpha = 0.5;
-        let min_contacts = 0.0;
-        let max_contacts = 10.0;
-        let min_scale = 1.0 / 1.5;
-        let max_scale = 1.33;
-        let e_ratio = 0.25;
-        let r = 0.85;
+    /// See utils::EwmaAcc for details.
+    pub fn smoothed_cases(&self, alpha: Real) -> Vec<Real> {
+        let mut acc = EwmaAcc::new(alpha);
+        let mut smoothed = Vec::with_capacity(self.infections_per_iter.len());
+        for &cases in self.infections_per_iter.iter() {
+            acc.track(&(cases as Real));
+            smoothed.push(acc.mean());
+        }
+        return smoothed;
+    }
 
-        let mut n_iter = 0;
-        let mut excess = 0.0;
-        let mut acc_cases = 0.0;
-        let mut acc_target = 0.0;
-        let mut c_mean = self.sampler.contacts();
+    /// Simulate the curve of reported cases, with random reporting delays and
+    /// binomial under-reporting. Useful to generate synthetic datasets.
+    ///
+    /// See utils::curve::observe_with_delay() for details.
+    pub fn observed_with_delay<R: Rng>(
+        &self,
+        mean_delay: Real,
+        cv: Real,
+        reporting_fraction: Real,
+        rng: &mut R,
+    ) -> Vec<Real> {
+        return curve::observe_with_delay(
+            &self.infections_per_iter,
+            mean_delay,
+            cv,
+            reporting_fraction,
+            rng,
+        );
+    }
 
-        for raw_target in cases {
-            n_iter += 1;
-            acc_target += raw_target;
+    /// Number of new infections per contagious agent at each iteration. This
+    /// isolates the intensity of transmission from prevalence.
+    ///
+    /// Contagious agents are counted at the start of each iteration. Days
+    /// without contagious agents are NaN.
+    pub fn transmission_efficiency(&self) -> Vec<Real> {
+        let it = self
+            .infections_per_iter
+            .iter()
+            .zip(&self.contagious_per_iter);
+        return it
+            .map(|(&cases, &n)| {
+                if n > 0 {
+                    cases as Real / n as Real
+                } else {
+                    NAN
+                }
+            })
+            .collect();
+    }
 
-            let target = (raw_target + e_ratio * excess).max(0.0);
-            let estimate = self.sampler.expected_infection_pairs(&self.population);
-            let grow = ((target + alpha) / (estimate + alpha)).clamp(min_scale, max_scale);
+    /// Start logging each transmission event as a (source, target, step)
+    /// triple. See transmission_log().
+    pub fn track_transmissions(&mut self) -> &mut Self {
+        if self.transmission_log.is_none() {
+            self.transmission_log = Some(vec![]);
+        }
+        return self;
+    }
 
-            // Calibrate contacts. Other implementations might calibrate different
-            // coefficients, but we do not have any way to generalize it yet.
-            let c1 = self.sampler.contacts();
-            let c2 = (c1 * grow).clamp(min_contacts, max_contacts);
-            c_mean = c_mean * r + c2 * (1.0 - r);
-            self.sampler.set_contacts(c2);
+    /// Return the list of (source, target, step) transmission events, if
+    /// tracked. Events are sorted by step.
+    pub fn transmission_log(&self) -> Option<&[(Id, Id, usize)]> {
+        self.transmission_log.as_deref()
+    }
 
-            // Run and register the number of cases
+    /// Return the number of steps between the infection of the source and
+    /// each onward transmission in the transmission log.
+    ///
+    /// Sources are dated by their first infection time, hence transmissions
+    /// from initial seeds, whose infection time is unknown, are skipped.
+    /// Return an empty vector if transmissions are not tracked.
+    pub fn generation_intervals(&self) -> Vec<usize> {
+        let log = self.transmission_log.as_deref().unwrap_or_default();
+        return log
+            .iter()
+            .filter_map(|&(i, _, step)| {
+                let t0 = self.infection_times[i]? as usize;
+                step.checked_sub(t0)
+            })
+            .collect();
+    }
+
+    /// Return the number of agents first infected at each iteration.
+    ///
+    /// This is reconstructed from the infection time of each agent and thus
+    /// only counts first infections. Agents contaminated outside the regular
+    /// simulation steps (e.g., initial seeds) are not included.
+    pub fn infection_cohorts(&self) -> Vec<usize> {
+        let mut cohorts = vec![0; self.infections_per_iter.len()];
+        for &t in self.infection_times.iter().flatten() {
+            cohorts[t as usize] += 1;
+        }
+        return cohorts;
+    }
+
+    /// Return the first iteration in which the cumulative number of new cases
+    /// exceeds threshold, i.e., the day an outbreak would be declared.
+    ///
+    /// Return None if the threshold is never crossed.
+    pub fn epidemic_start_day(&self, threshold: usize) -> Option<usize> {
+        let mut total = 0;
+        for (day, &cases) in self.infections_per_iter.iter().enumerate() {
+            total += cases;
+            if total > threshold {
+                return Some(day);
+            }
+        }
+        return None;
+    }
+
+    /// Return the first iteration after which the fraction of contagious agents
+    /// stays below threshold until the end of the run, i.e., the effective end
+    /// of the epidemic.
+    ///
+    /// Return None if prevalence is still above threshold at the end of the
+    /// run.
+    pub fn epidemic_duration(&self, threshold: Real) -> Option<usize> {
+        let n = self.population.len() as Real;
+        let current = self
+            .population
+            .iter()
+            .filter(|ag| ag.is_contagious())
+            .count();
+        if current as Real / n >= threshold {
+            return None;
+        }
+        let last = self
+            .contagious_per_iter
+            .iter()
+            .rposition(|&k| k as Real / n >= threshold);
+        return Some(last.map_or(0, |day| day + 1));
+    }
+
+    /// Probability that the given agent is infected in the next step, as
+    /// computed by the sampler from the current state of population. Isolated
+    /// agents have zero hazard.
+    pub fn agent_infection_hazard(&self, id: Id) -> Real {
+        if self.is_isolated(id) {
+            return 0.0;
+        }
+        return self.sampler.infection_hazard(&self.population, id);
+    }
+
+    /// Project the number of new infections in the next simulation step.
+    ///
+    /// Agents are advanced one step on a copy of the population using the
+    /// bound params and the expected number of infection pairs is then
+    /// computed by the sampler. The state of the simulation (including the
+    /// random number generator) is not modified.
+    pub fn expected_incidence_next_day(&self) -> Real {
+        let mut rng = SmallRng::from_rng(self.rng.borrow().clone()).unwrap();
+        let mut params = self.params.borrow_mut();
+        let mut population = self.population.clone();
+        for obj in population.iter_mut() {
+            params.bind_to_object(obj);
+            obj.random_update(params.local(), &mut rng);
+        }
+        return self.sampler.expected_infection_pairs(&population);
+    }
+
+    /// Return the fraction of infections caused by agents in each compartment,
+    /// indexed by the compartment index of the infector at transmission time.
+    ///
+    /// All values are NaN if no infections occurred during the simulation.
+    pub fn transmission_by_source_compartment(&self) -> Vec<Real> {
+        let total: usize = self.infections_by_source.iter().sum();
+        return self
+            .infections_by_source
+            .iter()
+            .map(|&n| n as Real / total as Real)
+            .collect();
+    }
+
+    /// Mean number of secondary infections caused by the initially seeded
+    /// agents, i.e., agents contaminated outside the regular simulation steps.
+    ///
+    /// This is the cleanest empirical estimate of R0, since it is measured
+    /// before susceptible depletion matters. Seeds that are still contagious
+    /// have only partial counts, so the simulation should run until the first
+    /// generation has recovered. Return NaN if there are no seeds.
+    pub fn realized_r0_first_generation(&self) -> Real {
+        let (mut n, mut acc) = (0, 0);
+        for (i, ag) in self.population.iter().enumerate() {
+            if ag.is_contaminated() && self.infection_times[i].is_none() {
+                n += 1;
+                acc += self.infections_per_agent[i] as usize;
+            }
+        }
+        return acc as Real / n as Real;
+    }
+
+    /// Mean susceptibility of the population, accounting for partial immunity
+    /// (e.g., leaky vaccines). This is the fraction that multiplies R0 to
+    /// obtain the effective reproduction number and is never larger than the
+    /// raw susceptible fraction.
+    pub fn effective_susceptible_fraction(&self) -> Real {
+        let total: Real = self.population.iter().map(|ag| ag.susceptibility()).sum();
+        return total / self.population.len() as Real;
+    }
+
+    /// Herd immunity threshold 1 - 1/R0, with R0 estimated by
+    /// realized_r0_first_generation(). Return 0 if R0 <= 1 and NaN if there
+    /// are no seeds.
+    pub fn herd_immunity_threshold(&self) -> Real {
+        let r0 = self.realized_r0_first_generation();
+        if r0.is_nan() {
+            return NAN;
+        }
+        return (1.0 - 1.0 / r0).max(0.0);
+    }
+
+    /// Fraction of the population infected beyond the herd immunity threshold.
+    /// This is caused by infections in flight when Rt drops below one and is
+    /// the part of the burden that could be prevented by mitigation.
+    ///
+    /// Return 0 if the epidemic did not reach the threshold.
+    pub fn overshoot(&self) -> Real {
+        let excess = self.population.attack_ratio() - self.herd_immunity_threshold();
+        return if excess > 0.0 { excess } else { 0.0 };
+    }
+
+    /// Gini coefficient of the attack rates of each 10-year age band,
+    /// weighted by the population of each band.
+    ///
+    /// This measures how unequally the epidemic burden falls across ages: 0
+    /// means all bands have the same attack rate and values close to 1 mean
+    /// the burden is concentrated in a few small bands. Return 0 if there
+    /// are no infections.
+    pub fn burden_inequality(&self) -> Real
+    where
+        S: HasAge,
+    {
+        let mut counts = [[0usize; 2]; 9];
+        for ag in self.population.iter() {
+            let row = &mut counts[(ag.age() as usize / 10).min(8)];
+            row[0] += 1;
+            row[1] += ag.is_contaminated() as usize;
+        }
+        let bands: Vec<(Real, Real)> = counts
+            .iter()
+            .filter(|[n, _]| *n > 0)
+            .map(|&[n, k]| (n as Real, k as Real / n as Real))
+            .collect();
+
+        let total: Real = bands.iter().map(|(w, _)| w).sum();
+        let mean = bands.iter().map(|(w, a)| w * a).sum::<Real>() / total;
+        if mean.is_nan() || mean <= 0.0 {
+            return 0.0;
+        }
+        let mut acc = 0.0;
+        for (wi, ai) in bands.iter() {
+            for (wj, aj) in bands.iter() {
+                acc += wi * wj * (ai - aj).abs();
+            }
+        }
+        return acc / (2.0 * total * total * mean);
+    }
+
+    /// Used internally to normalize (or not) results
+    fn normalization_factor(&self, normalize: bool) -> Real {
+        if normalize {
+            1.0 / self.count() as Real
+        } else {
+            1.0
+        }
+    }
+
+    /// Initialize simulation and calibrate sampler from a curve of cases.
+    ///
+    /// This is a somewhat simplistic view on model calibration. We just run
+    /// the simulation normally but at each step we recalibrate the sampler
+    /// to produce the same number of infections as expected from the epidemic
+    /// curve.
+    ///
+    /// Return a report that can be used to check if calibration was able to
+    /// track the target curve.
+    pub fn calibrate_sampler_from_cases(&mut self, cases: &[Real]) -> CalibrationReport
+    where
+        S::Clinical: Default,
+    {
+        // TODO: create calibrator struct
+        let alpha = 0.5;
+        let min_contacts = 0.0;
+        let max_contacts = 10.0;
+        let min_scale = 1.0 / 1.5;
+        let max_scale = 1.33;
+        let e_ratio = 0.25;
+        let r = 0.85;
+
+        let mut n_iter = 0;
+        let mut excess = 0.0;
+        let mut acc_cases = 0.0;
+        let mut acc_target = 0.0;
+        let mut acc_error = 0.0;
+        let mut max_hits = 0;
+        let mut consecutive_hits = false;
+        let mut last_hit = false;
+        let mut c_mean = self.sampler.contacts();
+
+        for raw_target in cases {
+            n_iter += 1;
+            acc_target += raw_target;
+
+            let target = (raw_target + e_ratio * excess).max(0.0);
+            let estimate = self.sampler.expected_infection_pairs(&self.population);
+            let grow = ((target + alpha) / (estimate + alpha)).clamp(min_scale, max_scale);
+
+            // Calibrate contacts. Other implementations might calibrate different
+            // coefficients, but we do not have any way to generalize it yet.
+            let c1 = self.sampler.contacts();
+            let c2 = (c1 * grow).clamp(min_contacts, max_contacts);
+            let hit = c1 * grow > max_contacts;
+            if hit {
+                max_hits += 1;
+            }
+            consecutive_hits |= hit && last_hit;
+            last_hit = hit;
+            c_mean = c_mean * r + c2 * (1.0 - r);
+            self.sampler.set_contacts(c2);
+
+            // Run and register the number of cases
             let n_cases = self.steps(1);
+            let mut step_cases = n_cases as Real;
             acc_cases += n_cases as Real;
             excess = acc_target - acc_cases as Real;
 
             // If excess is very large (very negative), we might want to create
             // artificial infections to quickstart a infection
             if excess > 0.25 * (acc_target + alpha) {
-                let n = (excess * 0.25) as usize;
+                let n = ((excess * 0.25) as usize).min(self.population.n_susceptible());
                 self.population
                     .contaminate_at_random(n, &mut *self.rng.borrow_mut());
                 acc_cases += n as Real;
+                step_cases += n as Real;
                 excess = acc_target - acc_cases as Real;
             }
+            acc_error += (step_cases - raw_target).abs() / raw_target.max(1.0);
 
             trace!(target: "calibrate_sample_cases", "iter {}, coeff: {:.2} ({:.2})\n  - target: {} ({}); cases: {} (~ {:.1}); excess: {}", n_iter, c2, c_mean, raw_target, target, n_cases, estimate, excess);
         }
         self.sampler.set_contacts(c_mean);
         debug!(target: "calibrate_sample_cases", "final contacts: {}, {} iterations", c_mean, n_iter);
-        return self;
+        return CalibrationReport {
+            mean_relative_error: acc_error / n_iter.max(1) as Real,
+            max_contacts_steps: max_hits,
+            max_contacts_hit: consecutive_hits,
+            final_prob_infection: self.sampler.prob_infection(),
+        };
     }
 
     /// Get epidemiological params for given agent
@@ -291,11 +1384,286 @@ where
         params.bind_to_object(ag);
         Some(FromLocalParams::from_local_params(params.local()))
     }
+
+    /// Inspect the parameters used by agent when leaving the compartment with
+    /// the given index (the same index used by EpiModel::index()).
+    ///
+    /// Return None if agent does not exist or if the compartment does not have
+    /// a timed exit.
+    pub fn inspect_transition(
+        &self,
+        id: Id,
+        from_compartment: usize,
+    ) -> Option<TransitionInspection>
+    where
+        S: EpiModel,
+        W::Local: EpiParamsLocalT,
+    {
+        let name = S::CSV_HEADER.split(',').nth(from_compartment)?;
+        let compartment = Compartment::from_state_name(name)?;
+        let ag = self.population.get(id)?;
+        let mut params = self.params.borrow_mut();
+        params.bind_to_object(ag);
+        let local = params.local();
+        return Some(TransitionInspection {
+            compartment,
+            period: local.transition_period(compartment),
+            transition_prob: local.transition_prob(compartment),
+            params: FromLocalParams::from_local_params(local),
+        });
+    }
+}
+
+impl<W, S, PS, P, T> Simulation<W, S, PS>
+where
+    PS: PopulationSampler<Vec<S>>,
+    W: LocalBind<S, World = EpiParamsCached<P, T>> + 'static,
+    W::Local: EpiParamsLocalT,
+    S: EpiModel + RandomUpdate<W::Local> + Debug + 'static,
+{
+    /// Scale the case fatality ratio over time. Each (day, factor) entry sets
+    /// the scale from the given day onwards, until the next entry. Days before
+    /// the first entry use the baseline CFR.
+    ///
+    /// The scale is applied to the world params at each step. An empty
+    /// schedule keeps the CFR constant.
+    pub fn set_cfr_schedule(&mut self, schedule: Vec<(usize, Real)>) -> &mut Self {
+        if schedule.is_empty() {
+            return self;
+        }
+        let mut schedule = schedule;
+        schedule.sort_by_key(|(day, _)| *day);
+        let scale_at = move |t: usize| {
+            schedule
+                .iter()
+                .take_while(|(day, _)| *day <= t)
+                .last()
+                .map_or(1.0, |(_, scale)| *scale)
+        };
+
+        let mut day = self.infections_per_iter.len();
+        let params = self.params.get_mut();
+        params.world_mut().set_case_fatality_scale(scale_at(day));
+        self.world_update.push(Box::new(move |params, _| {
+            day += 1;
+            params.world_mut().set_case_fatality_scale(scale_at(day));
+        }));
+        return self;
+    }
+
+    /// Limit the capacity of the healthcare system.
+    ///
+    /// Occupancy is updated at the end of each step from the number of agents
+    /// in the severe (H) and critical (C) compartments, which occupy beds and
+    /// ICUs, respectively. Models without those compartments never occupy any
+    /// resources.
+    pub fn set_healthcare_capacity(&mut self, capacity: HealthcareCapacity) -> &mut Self {
+        let position = |name| S::CSV_HEADER.split(',').position(|s| s == name);
+        let (severe, critical) = (position("H"), position("C"));
+        let occupancy = move |population: &Vec<S>| {
+            let (mut beds, mut icus) = (0, 0);
+            for ag in population.iter() {
+                let idx = Some(ag.index());
+                beds += (idx == severe) as usize;
+                icus += (idx == critical) as usize;
+            }
+            return (beds, icus);
+        };
+
+        let mut capacity = capacity;
+        let (beds, icus) = occupancy(&self.population);
+        capacity.set_occupancy(beds, icus);
+        let params = self.params.get_mut();
+        params.world_mut().set_healthcare(Some(capacity));
+        self.world_update.push(Box::new(move |params, population| {
+            if let Some(mut healthcare) = params.world().healthcare() {
+                let (beds, icus) = occupancy(population);
+                healthcare.set_occupancy(beds, icus);
+                params.world_mut().set_healthcare(Some(healthcare));
+            }
+        }));
+        return self;
+    }
+}
+
+impl<W, S, PS> Simulation<W, S, PS>
+where
+    PS: PopulationSampler<Vec<S>>,
+    W: LocalBind<S> + Clone + Send + Sync,
+    W::Local: EpiParamsLocalT,
+    S: EpiModel + RandomUpdate<W::Local> + Debug + Send,
+{
+    /// Update agents in parallel.
+    ///
+    /// Parallel updates use per-agent random number generators (see
+    /// set_per_agent_rng()), hence results are reproducible and identical to a
+    /// sequential run with per-agent generators, regardless of thread
+    /// scheduling. Disabling parallel updates keeps per-agent generators.
+    pub fn set_parallel(&mut self, value: bool) -> &mut Self {
+        if value {
+            self.per_agent_rng = true;
+            self.parallel_update = Some(Self::update_agents_parallel);
+        } else {
+            self.parallel_update = None;
+        }
+        return self;
+    }
+
+    fn update_agents_parallel(&mut self) {
+        let base_seed = self.rng.borrow_mut().next_u64();
+        let params = self.params.borrow().clone();
+        let transitions: Vec<(usize, usize)> = self
+            .population
+            .par_iter_mut()
+            .enumerate()
+            .map_init(
+                || params.clone(),
+                |params, (id, obj)| {
+                    params.bind_to_object(obj);
+                    obj.random_update_tracked(params.local(), &mut agent_rng(base_seed, id))
+                },
+            )
+            .flatten()
+            .collect();
+        for (from, to) in transitions {
+            record_flow(&mut self.flows, &mut self.incidence, from, to);
+        }
+    }
+}
+
+/// Random number generator used to update the given agent in a step with
+/// per-agent generators.
+fn agent_rng(base_seed: u64, id: Id) -> SmallRng {
+    SmallRng::seed_from_u64(base_seed ^ id as u64)
+}
+
+/// Random number generator and options stored in a scenario bundle.
+#[derive(Serialize, Deserialize)]
+struct BundleConfig<S> {
+    rng: SimRng,
+    susceptible_replenishment: bool,
+    per_agent_rng: bool,
+    exposure_times: Option<Vec<Option<Time>>>,
+    tracing: Option<TracingState>,
+    behavior: Option<BehaviorState>,
+    generation: Option<GenerationSchedule>,
+    flows: Option<Array2<usize>>,
+    incidence: Option<FlowTracker>,
+    transmission_log: Option<Vec<(Id, Id, usize)>>,
+    reseeding: Option<(usize, S)>,
+}
+
+/// Population stored in a scenario bundle.
+#[derive(Serialize, Deserialize)]
+struct BundlePopulation<T> {
+    population: T,
+}
+
+impl<W, S, PS> Simulation<W, S, PS>
+where
+    PS: PopulationSampler<Vec<S>> + Serialize + DeserializeOwned,
+    W: LocalBind<S> + Serialize + DeserializeOwned,
+    W::Local: EpiParamsLocalT,
+    S: EpiModel + RandomUpdate<W::Local> + Debug + Serialize + DeserializeOwned,
+{
+    /// Write params, sampler and initial population as TOML files to the given
+    /// directory, together with the state of the random number generator and
+    /// the simulation options (contact tracing, tracked quantities, etc). The
+    /// bundle can be loaded with from_bundle() and the new simulation produces
+    /// exactly the same results as this one.
+    ///
+    /// The bundle describes a scenario and must be exported before the first
+    /// step. Use checkpoint() to save a simulation in the middle of a run.
+    /// Trackers and user defined update functions are not stored.
+    pub fn export_bundle(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        if !self.infections_per_iter.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bundle must be exported before the simulation runs",
+            ));
+        }
+        let dir = path.as_ref();
+        let config = BundleConfig {
+            rng: self.rng.borrow().clone(),
+            susceptible_replenishment: self.susceptible_replenishment,
+            per_agent_rng: self.per_agent_rng,
+            exposure_times: self.exposure_times.clone(),
+            tracing: self.tracing.clone(),
+            behavior: self.behavior.clone(),
+            generation: self.generation.clone(),
+            flows: self.flows.clone(),
+            incidence: self.incidence.clone(),
+            transmission_log: self.transmission_log.clone(),
+            reseeding: self.reseeding.clone(),
+        };
+        let population = BundlePopulation {
+            population: &self.population,
+        };
+        fs::create_dir_all(dir)?;
+        let data = serde_json::to_string_pretty(&config)?;
+        fs::write(dir.join("bundle.json"), data)?;
+        write_toml(&dir.join("params.toml"), &*self.params.borrow())?;
+        write_toml(&dir.join("sampler.toml"), &self.sampler)?;
+        write_toml(&dir.join("population.toml"), &population)?;
+        return Ok(());
+    }
+
+    /// Load simulation from a bundle created by export_bundle().
+    pub fn from_bundle(path: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = path.as_ref();
+        let data = fs::read_to_string(dir.join("bundle.json"))?;
+        let config: BundleConfig<S> = serde_json::from_str(&data)?;
+        let params: W = read_toml(&dir.join("params.toml"))?;
+        let sampler: PS = read_toml(&dir.join("sampler.toml"))?;
+        let data: BundlePopulation<Vec<S>> = read_toml(&dir.join("population.toml"))?;
+
+        let mut sim = Self::new(params, data.population, sampler);
+        sim.rng.replace(config.rng);
+        sim.susceptible_replenishment = config.susceptible_replenishment;
+        sim.per_agent_rng = config.per_agent_rng;
+        sim.exposure_times = config.exposure_times;
+        sim.tracing = config.tracing;
+        sim.behavior = config.behavior;
+        sim.generation = config.generation;
+        sim.flows = config.flows;
+        sim.incidence = config.incidence;
+        sim.transmission_log = config.transmission_log;
+        sim.reseeding = config.reseeding;
+        return Ok(sim);
+    }
+}
+
+fn write_toml(path: &Path, data: &impl Serialize) -> io::Result<()> {
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+    let value = toml::Value::try_from(data).map_err(invalid)?;
+    return fs::write(path, value.to_string());
+}
+
+fn read_toml<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let data = fs::read_to_string(path)?;
+    return toml::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+}
+
+fn record_flow(
+    flows: &mut Option<Array2<usize>>,
+    incidence: &mut Option<FlowTracker>,
+    from: usize,
+    to: usize,
+) {
+    if let Some(flows) = flows {
+        if from != to {
+            flows[[from, to]] += 1;
+        }
+    }
+    if let Some(incidence) = incidence {
+        incidence.record(from, to);
+    }
 }
 
 impl<W, S> Simulation<W, S, SimpleSampler>
 where
     W: LocalBind<S>,
+    W::Local: EpiParamsLocalT,
     S: RandomUpdate<W::Local> + EpiModel + Debug,
 {
     /// Create a new simulation from a simple sampler
@@ -310,10 +1678,79 @@ where
     }
 }
 
+impl<W, M, V, PS> Simulation<W, SimpleAgent<M, V>, PS>
+where
+    M: EpiModel,
+    V: Clone + PartialEq,
+{
+    /// Apply vaccination campaign at the end of each simulation step.
+    pub fn set_vaccination_campaign<F, C>(
+        &mut self,
+        campaign: VaccinationCampaign<V, F>,
+    ) -> &mut Self
+    where
+        W: 'static,
+        M: 'static,
+        V: 'static,
+        F: FnMut(&SimpleAgent<M, V>) -> C + 'static,
+        C: Ord,
+    {
+        let mut campaign = campaign;
+        self.population_update.push(Box::new(move |_, population| {
+            let doses = campaign.apply(population);
+            trace!("applied {} vaccine doses", doses);
+        }));
+        return self;
+    }
+}
+
+impl<W, M, V, PS> Simulation<W, SimpleAgent<M, V>, PS>
+where
+    SimpleAgent<M, V>: EpiModel,
+    V: Default + PartialEq,
+{
+    /// Return the attack rates among (unvaccinated, vaccinated) agents. Agents
+    /// are unvaccinated if their vaccine is the default value (e.g., false or
+    /// a LeakyVaccine with zero efficacy).
+    ///
+    /// The realized vaccine effectiveness is simply 1 - vaccinated/unvaccinated.
+    /// Either value is NaN if the corresponding group is empty.
+    pub fn attack_rate_by_vaccine(&self) -> (Real, Real) {
+        let unvaccinated = V::default();
+        let mut counts = [[0usize; 2]; 2];
+        for ag in self.population.iter() {
+            let row = &mut counts[(*ag.vaccine() != unvaccinated) as usize];
+            row[0] += 1;
+            row[1] += ag.is_contaminated() as usize;
+        }
+        let ratio = |[n, k]: [usize; 2]| k as Real / n as Real;
+        return (ratio(counts[0]), ratio(counts[1]));
+    }
+}
+
+impl<W, M, PS> Simulation<W, SimpleAgent<M, bool>, PS>
+where
+    SimpleAgent<M, bool>: EpiModel,
+{
+    /// Apply booster policy at the end of each simulation step.
+    pub fn set_booster_policy(&mut self, policy: BoosterPolicy) -> &mut Self
+    where
+        W: 'static,
+        M: 'static,
+    {
+        self.population_update.push(Box::new(move |_, population| {
+            let doses = population.apply_booster_policy(&policy);
+            trace!("applied {} booster doses", doses);
+        }));
+        return self;
+    }
+}
+
 impl<W, S, PS> OwnsStateSlice for Simulation<W, S, PS>
 where
     PS: PopulationSampler<Vec<S>> + Default,
     W: LocalBind<S> + Default,
+    W::Local: EpiParamsLocalT,
     S: EpiModel + RandomUpdate<W::Local> + Debug,
 {
     type Elem = S;
@@ -335,3 +1772,1409 @@ where
         self.population.as_mut_slice()
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        epidemic::Variant,
+        models::{
+            BoosterPriority, CrossImmunity, LeakyVaccine, SEICHARm, SEIRm, SIRm, SeirAgent,
+            SimpleAgentPopulationExt, SEIR,
+        },
+        params::{
+            EpiParamsBindVaccine, EpiParamsFull, EpiParamsGlobal, EpiParamsLocal, EpiParamsT,
+        },
+        sampler::{ContactMatrixSampler, ScheduledSampler, SeasonalSampler},
+        sim::{HasAge, HasEpiModel},
+        utils::seeded_rng,
+    };
+
+    use assert_approx_eq::assert_approx_eq;
+
+    type Agent = SeirAgent<bool>;
+    type Sim = Simulation<EpiParamsBindVaccine<AgeParam>, Agent, SimpleSampler>;
+
+    fn new_simulation(n: usize) -> Sim {
+        let population = vec![Agent::default(); n];
+        Simulation::new_simple(Default::default(), population, 4.0, 0.1)
+    }
+
+    #[test]
+    fn attack_rate_by_vaccine_with_protective_vaccine() {
+        let mut population = vec![SeirAgent::<LeakyVaccine>::default(); 4000];
+        for (i, ag) in population.iter_mut().enumerate() {
+            if i % 2 == 0 {
+                ag.vaccinate(&LeakyVaccine { efficacy: 0.9 });
+            } else if i % 100 == 1 {
+                ag.set_epimodel(SEIR::Infectious(()));
+            }
+        }
+        let params = EpiParamsLocal::default();
+        let mut sim = Simulation::new_simple(params, population, 4.0, 0.2);
+        sim.seed(42).run(120);
+
+        let (unvaccinated, vaccinated) = sim.attack_rate_by_vaccine();
+        assert!(unvaccinated > 0.3, "unvaccinated: {}", unvaccinated);
+        assert!(vaccinated > 0.0);
+        assert!(
+            1.0 - vaccinated / unvaccinated > 0.7,
+            "unvaccinated: {}, vaccinated: {}",
+            unvaccinated,
+            vaccinated
+        );
+    }
+
+    #[test]
+    fn cross_immunity_reduces_peak_of_new_variant() {
+        let peak = |fraction: Real, against: Variant| {
+            let mut population = vec![SimpleAgent::<SEIR<Variant>, CrossImmunity>::default(); 5000];
+            population.set_cross_immune_fraction(fraction, against, 0.8, &mut seeded_rng(1u64));
+            for ag in population.iter_mut().step_by(250) {
+                ag.set_epimodel(SEIR::Infectious(Variant::VoC));
+            }
+            let params = EpiParamsLocal::default();
+            let mut sim = Simulation::new_simple(params, population, 4.0, 0.1);
+            sim.seed(42).run(100);
+            let curve = sim.get_epicurve(SEIR::<Variant>::I, false).unwrap();
+            return curve.into_iter().fold(0.0, Real::max);
+        };
+
+        let baseline = peak(0.0, Variant::VoC);
+        assert!(peak(0.3, Variant::VoC) < baseline);
+        assert!(peak(0.6, Variant::VoC) < peak(0.3, Variant::VoC));
+        assert_eq!(peak(0.6, Variant::Baseline), baseline);
+    }
+
+    #[test]
+    fn burden_inequality_of_concentrated_and_uniform_epidemics() {
+        let mut sim = new_simulation(900);
+        for (i, ag) in sim.population_mut().iter_mut().enumerate() {
+            ag.set_age((i % 90) as Age);
+        }
+        assert_eq!(sim.burden_inequality(), 0.0);
+
+        let mut uniform = sim.copy();
+        for (i, ag) in uniform.population_mut().iter_mut().enumerate() {
+            if (i / 90) % 2 == 0 {
+                ag.set_epimodel(SEIR::Recovered(()));
+            }
+        }
+        assert_approx_eq!(uniform.burden_inequality(), 0.0);
+
+        for ag in sim.population_mut().iter_mut() {
+            if ag.age() >= 80 {
+                ag.set_epimodel(SEIR::Recovered(()));
+            }
+        }
+        assert_approx_eq!(sim.burden_inequality(), 8.0 / 9.0);
+    }
+
+    #[test]
+    fn vaccination_campaign_prioritizes_elderly() {
+        let mut sim = new_simulation(100);
+        for (i, ag) in sim.population_mut().iter_mut().enumerate() {
+            ag.set_age(i as Age);
+        }
+        sim.population_mut()[99].set_epimodel(SEIR::Dead(()));
+        let campaign = VaccinationCampaign::new(true, 10, 35, |ag: &Agent| ag.age());
+        assert_eq!(campaign.remaining_supply(), 35);
+        sim.set_vaccination_campaign(campaign);
+
+        sim.run(1);
+        let vaccinated = |sim: &Sim| -> Vec<usize> {
+            let population = sim.population().iter().enumerate();
+            return population
+                .filter(|(_, ag)| *ag.vaccine())
+                .map(|(i, _)| i)
+                .collect();
+        };
+        assert_eq!(vaccinated(&sim), (89..99).collect::<Vec<_>>());
+        sim.run(2);
+        assert_eq!(vaccinated(&sim), (69..99).collect::<Vec<_>>());
+        sim.run(10);
+        assert_eq!(vaccinated(&sim), (64..99).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn booster_policy_respects_interval_and_capacity() {
+        let mut sim = new_simulation(100);
+        for (i, ag) in sim.population_mut().iter_mut().enumerate() {
+            ag.set_age(i as Age);
+            ag.vaccinate(&true);
+        }
+        sim.set_booster_policy(BoosterPolicy {
+            interval_days: 30,
+            priority: BoosterPriority::Oldest,
+            daily_doses: 5,
+        });
+        sim.run(40);
+
+        // Agents are eligible from day 31 on, so only the 50 oldest agents
+        // received a booster, 5 per day.
+        for (i, ag) in sim.population().iter().enumerate() {
+            let expected = if i >= 50 { (i as Time - 50) / 5 } else { 40 };
+            assert_eq!(ag.vaccine_t(), expected);
+        }
+
+        let mut sim = new_simulation(100);
+        sim.population_mut()[..50]
+            .iter_mut()
+            .for_each(|ag| ag.vaccinate(&true));
+        sim.set_booster_policy(BoosterPolicy {
+            interval_days: 30,
+            priority: BoosterPriority::LongestSinceDose,
+            daily_doses: 1000,
+        });
+        sim.run(100);
+        for ag in sim.population()[..50].iter() {
+            assert!(ag.vaccine_t() <= 30);
+        }
+        for ag in sim.population()[50..].iter() {
+            assert_eq!(ag.vaccine_t(), 100);
+        }
+    }
+
+    #[test]
+    fn calibration_reports_unreachable_target() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        for ag in sim.population_mut().iter_mut().step_by(100) {
+            ag.set_epimodel(SEIR::Infectious(()));
+        }
+        let report = sim.calibrate_sampler_from_cases(&[3.0; 10]);
+        assert_eq!(report.max_contacts_steps, 0);
+        assert!(!report.max_contacts_hit);
+        assert_eq!(report.final_prob_infection, 0.1);
+
+        let mut sim = new_simulation(100);
+        sim.seed(42);
+        let report = sim.calibrate_sampler_from_cases(&[1000.0; 20]);
+        assert!(report.max_contacts_hit);
+        assert!(report.max_contacts_steps > 1);
+        assert!(report.mean_relative_error > 0.5);
+        assert_eq!(report.final_prob_infection, 0.1);
+        assert!(sim.sampler().contacts() <= 10.0);
+    }
+
+    #[test]
+    fn infection_cohorts_match_incidence() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.run(30);
+        assert_eq!(&sim.infection_cohorts(), sim.infections_per_iter());
+        assert!(sim.infections_per_iter().iter().sum::<usize>() > 0);
+    }
+
+    #[test]
+    fn contact_tracing_isolates_contacts_without_transmission() {
+        let mut sim = new_simulation(1000);
+        sim.sampler_mut().set_prob_infection(0.0);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        sim.set_contact_tracing(ContactTracing {
+            detect_probability: 1.0,
+            trace_probability: 1.0,
+            trace_delay: 1,
+            ..ContactTracing::default()
+        });
+        sim.run(5);
+        assert_eq!(sim.infections_per_iter().iter().sum::<usize>(), 0);
+        assert!(sim.n_isolated() > 20, "isolated: {}", sim.n_isolated());
+    }
+
+    #[test]
+    fn isolated_agents_are_skipped_by_samplers() {
+        let mut sim = new_simulation(1000);
+        for ag in sim.population_mut()[..50].iter_mut() {
+            ag.set_epimodel(SEIR::Infectious(()));
+        }
+        let expected = sim.sampler().expected_infection_pairs(sim.population());
+        assert!(expected > 0.0);
+        for ag in sim.population_mut().iter_mut() {
+            if ag.is_contagious() {
+                assert!(ag.isolate(true));
+            }
+        }
+        let rng = &mut seeded_rng(1u64);
+        assert!(sim
+            .sampler()
+            .sample_infection_pairs(sim.population(), rng)
+            .is_empty());
+        assert_eq!(
+            sim.sampler().expected_infection_pairs(sim.population()),
+            0.0
+        );
+        let id = sim
+            .population()
+            .iter()
+            .position(|ag| ag.is_susceptible())
+            .unwrap();
+        assert_eq!(sim.agent_infection_hazard(id), 0.0);
+        assert_eq!(sim.run(10).infections_per_iter().iter().sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn symptomatic_cases_are_isolated_after_delay_and_infectees_traced() {
+        let mut sim = new_simulation(2000);
+        sim.seed(42);
+        for ag in sim.population_mut()[..20].iter_mut() {
+            ag.set_epimodel(SEIR::Infectious(()));
+        }
+        sim.set_contact_tracing(ContactTracing {
+            detect_probability: 1.0,
+            detect_symptomatic: true,
+            detection_delay: 2,
+            trace_probability: 1.0,
+            trace_delay: 1,
+            isolate_traced: false,
+            ..ContactTracing::default()
+        });
+        sim.run(2);
+        assert!((0..20).all(|id| !sim.is_isolated(id)));
+        sim.run(1);
+        assert!((0..20)
+            .filter(|&id| sim.population()[id].is_contagious())
+            .all(|id| sim.is_isolated(id) && sim.population()[id].is_isolated()));
+
+        sim.run(1);
+        let log = sim.transmission_log().unwrap();
+        let infectees: Vec<Id> = log
+            .iter()
+            .filter(|(i, _, _)| *i < 20)
+            .map(|e| e.1)
+            .collect();
+        assert!(!infectees.is_empty());
+        assert!(infectees.iter().all(|&j| sim.is_isolated(j)));
+        assert!(infectees
+            .iter()
+            .any(|&j| !sim.population()[j].is_symptomatic()));
+    }
+
+    #[test]
+    fn backward_tracing_suppresses_epidemic() {
+        let attack_rate = |tracing: Option<ContactTracing>| {
+            let mut total = 0.0;
+            for seed in 0..5 {
+                let mut sim = new_simulation(2000);
+                sim.seed(seed);
+                sim.contaminate_at_random(10, &mut seeded_rng(seed));
+                if let Some(policy) = tracing {
+                    sim.set_contact_tracing(policy);
+                }
+                sim.run(150);
+                total += sim.population().attack_ratio();
+            }
+            total / 5.0
+        };
+        let forward = ContactTracing {
+            detect_probability: 0.1,
+            detect_symptomatic: false,
+            detection_delay: 0,
+            trace_probability: 0.3,
+            trace_delay: 2,
+            isolate_traced: true,
+            backward: false,
+            isolation_days: 14,
+            memory: 14,
+        };
+        let backward = ContactTracing {
+            backward: true,
+            ..forward
+        };
+
+        let none = attack_rate(None);
+        let forward = attack_rate(Some(forward));
+        let backward = attack_rate(Some(backward));
+        assert!(forward < none, "forward: {}, none: {}", forward, none);
+        assert!(
+            backward < forward,
+            "backward: {}, forward: {}",
+            backward,
+            forward
+        );
+    }
+
+    #[test]
+    fn observed_curve_peaks_after_true_curve() {
+        let mut sim = new_simulation(5000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        sim.run(120);
+
+        let argmax = |xs: &[Real]| {
+            (0..xs.len())
+                .max_by(|&i, &j| xs[i].partial_cmp(&xs[j]).unwrap())
+                .unwrap()
+        };
+        let incidence: Vec<Real> = sim.infections_per_iter.iter().map(|&x| x as Real).collect();
+        let observed = sim.observed_with_delay(7.0, 0.3, 0.5, &mut seeded_rng(42u64));
+        let shift = argmax(&observed) as Real - argmax(&incidence) as Real;
+        assert!((shift - 7.0).abs() <= 2.0, "peak shift: {}", shift);
+
+        let total: Real = observed.iter().sum();
+        let expected = 0.5 * incidence.iter().sum::<Real>();
+        assert!((total / expected - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn step_collecting_infections_matches_incidence() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        sim.run(10);
+        for _ in 0..10 {
+            let pairs = sim.step_collecting_infections();
+            assert_eq!(pairs.len(), *sim.infections_per_iter.last().unwrap());
+            for (i, j) in pairs {
+                assert!(sim.population[i].is_contaminated());
+                assert!(sim.population[j].is_contaminated());
+            }
+        }
+        assert!(sim.infections_per_iter[10..].iter().sum::<usize>() > 0);
+    }
+
+    #[test]
+    fn generation_intervals_follow_supplied_distribution() {
+        let gi = [0.1, 0.2, 0.4, 0.2, 0.1];
+        let mut sim = new_simulation(20_000);
+        sim.seed(42);
+        sim.contaminate_at_random(20, &mut seeded_rng(42u64));
+        sim.set_generation_interval(1.5, &gi);
+
+        // Only count infectors whose onward infections all fall within the
+        // simulated period, to avoid right-censoring
+        let mut counts = [0.0; 5];
+        for _ in 0..25 {
+            for (i, j) in sim.step_collecting_infections() {
+                let t0 = sim.infection_times[i].unwrap_or(0);
+                let t1 = sim.infection_times[j].unwrap();
+                if t0 + (gi.len() as Time) < 25 {
+                    counts[(t1 - t0) as usize - 1] += 1.0;
+                }
+            }
+        }
+        let total: Real = counts.iter().sum();
+        assert!(total > 200.0);
+        for (x, p) in counts.iter().zip(gi.iter()) {
+            assert_approx_eq!(x / total, p, 0.03);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "generation interval must have a positive total weight")]
+    fn generation_interval_rejects_empty_distribution() {
+        new_simulation(10).set_generation_interval(1.5, &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "generation interval must have a positive total weight")]
+    fn generation_interval_rejects_zero_distribution() {
+        new_simulation(10).set_generation_interval(1.5, &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn scheduled_transmission_tracks_exposures_and_replenishes() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        sim.set_generation_interval(2.0, &[0.5, 0.5])
+            .track_exposures()
+            .set_susceptible_replenishment(true)
+            .run(10);
+
+        let cases: usize = sim.infections_per_iter().iter().sum();
+        assert!(cases > 10);
+        assert_eq!(sim.count(), 1000 + cases);
+        let exposed = (0..sim.count()).filter(|&id| sim.days_since_exposure(id).is_some());
+        assert!(exposed.count() >= cases);
+    }
+
+    #[test]
+    fn infection_hazard_depends_on_contact_band() {
+        let mut population = vec![Agent::default(); 200];
+        for (i, ag) in population.iter_mut().enumerate() {
+            ag.set_age(if i % 2 == 0 { 20 } else { 70 });
+            if i % 10 < 2 {
+                ag.set_epimodel(SEIR::Infectious(()));
+            }
+        }
+        let matrix = ndarray::arr2(&[[8.0, 1.0], [1.0, 1.0]]);
+        let mut sampler = ContactMatrixSampler::new(50, matrix, 0.1);
+        sampler.init(&mut population);
+        let sim = Simulation::new(EpiParamsLocal::default(), population, sampler);
+
+        let young = sim.agent_infection_hazard(2);
+        let old = sim.agent_infection_hazard(3);
+        assert!(young > old && old > 0.0, "young: {}, old: {}", young, old);
+        assert_eq!(sim.agent_infection_hazard(0), 0.0);
+
+        // 20 young and 20 old infectious agents make 8 and 1 contacts with the
+        // young band of 100 agents
+        assert_approx_eq!(
+            young,
+            1.0 - (-0.1 * (20.0 * 8.0 + 20.0) / 100.0 as Real).exp()
+        );
+    }
+
+    #[test]
+    fn history_array_matches_epistates() {
+        let mut sim = new_simulation(500);
+        sim.seed(42);
+        sim.contaminate_at_random(5, &mut seeded_rng(42u64));
+        sim.run(30);
+
+        let history = sim.history_array(true);
+        assert_eq!(history.dim(), (31, SEIRm::CARDINALITY));
+        let state = sim.get_epistate(20, true).unwrap();
+        assert_eq!(history.row(20).to_vec(), state);
+        assert_eq!(sim.history_array(false)[(20, 1)], state[1] * 500.0);
+    }
+
+    #[test]
+    fn branches_diverge_only_with_different_interventions() {
+        let population = vec![Agent::default(); 1000];
+        let mut sim: Sim = Simulation::new_simple(Default::default(), population, 10.0, 0.1);
+        sim.seed(42);
+        sim.contaminate_at_random(5, &mut seeded_rng(42u64));
+        sim.run(10);
+
+        let mut a = sim.branch();
+        let mut b = sim.branch();
+        let mut lockdown = sim.branch();
+        lockdown.sampler_mut().set_contacts(1.0);
+        for branch in [&mut a, &mut b, &mut lockdown].iter_mut() {
+            branch.run(30);
+        }
+
+        assert_eq!(a.infections_per_iter, b.infections_per_iter);
+        assert_eq!(a.history_array(false), b.history_array(false));
+        assert_eq!(
+            a.infections_per_iter[..10],
+            lockdown.infections_per_iter[..10]
+        );
+        assert!(lockdown.population().attack_ratio() < a.population().attack_ratio());
+    }
+
+    #[test]
+    fn min_infectious_days_delays_recovery() {
+        let min_days_infectious = |min_days: Time| {
+            let mut sim = new_simulation(500);
+            sim.seed(42);
+            let mut params = sim.params().borrow_mut();
+            LocalBind::<Agent>::world_mut(&mut *params).set_min_infectious_days(min_days);
+            drop(params);
+            for ag in sim.population_mut().iter_mut() {
+                ag.set_epimodel(SEIR::Infectious(()));
+            }
+
+            let mut days = vec![0; 500];
+            for _ in 0..30 {
+                sim.run(1);
+                for (i, ag) in sim.population()[..500].iter().enumerate() {
+                    days[i] += ag.epimodel().is_infectious() as usize;
+                }
+            }
+            (0..500)
+                .filter(|&i| !sim.population()[i].epimodel().is_infectious())
+                .map(|i| days[i])
+                .min()
+                .unwrap()
+        };
+        assert_eq!(min_days_infectious(0), 0);
+        assert_eq!(min_days_infectious(2), 2);
+    }
+
+    #[test]
+    fn sankey_flows_are_conserved() {
+        let counts = |sim: &Sim| {
+            let mut data = vec![0; Agent::CARDINALITY];
+            sim.population().iter().for_each(|ag| data[ag.index()] += 1);
+            data
+        };
+        let mut sim = new_simulation(2000);
+        sim.seed(42);
+        sim.track_flows();
+        for (i, ag) in sim.population_mut().iter_mut().enumerate() {
+            ag.set_age(80);
+            if i < 20 {
+                ag.set_epimodel(SEIR::Infectious(()));
+            }
+        }
+        let initial = counts(&sim);
+        sim.run(120);
+        let last = counts(&sim);
+        let flows = sim.sankey_flows();
+        assert!(!flows.is_empty());
+
+        let into_dead: usize = flows.iter().filter(|f| f.1 == Agent::D).map(|f| f.2).sum();
+        assert!(last[Agent::D] > 0);
+        assert_eq!(into_dead, last[Agent::D]);
+
+        for k in 0..Agent::CARDINALITY {
+            let inflow: usize = flows.iter().filter(|f| f.1 == k).map(|f| f.2).sum();
+            let outflow: usize = flows.iter().filter(|f| f.0 == k).map(|f| f.2).sum();
+            assert_eq!(inflow, outflow + last[k] - initial[k]);
+        }
+    }
+
+    #[test]
+    fn recovered_agents_are_reinfected_with_relative_susceptibility() {
+        let reinfections = |relative: Real| {
+            let mut sim = new_simulation(1000);
+            sim.seed(42);
+            let mut params = sim.params().borrow_mut();
+            LocalBind::<Agent>::world_mut(&mut *params)
+                .set_relative_susceptibility_recovered(relative);
+            drop(params);
+            for (i, ag) in sim.population_mut().iter_mut().enumerate() {
+                let model = if i % 10 == 0 {
+                    SEIR::Infectious(())
+                } else {
+                    SEIR::Recovered(())
+                };
+                ag.set_epimodel(model);
+            }
+            sim.set_susceptible_replenishment(true).run(1);
+
+            // Reinfections do not deplete susceptibles and are not replenished
+            assert_eq!(sim.count(), 1000);
+            let exposed = |ag: &Agent| ag.epimodel().is_exposed();
+            sim.population().iter().filter(|ag| exposed(ag)).count()
+        };
+        assert_eq!(reinfections(0.0), 0);
+        let half = reinfections(0.5);
+        let full = reinfections(1.0);
+        assert!(half > 0);
+        assert!(half < full);
+    }
+
+    #[test]
+    fn contact_matrix_reinfections_are_not_double_counted() {
+        let n = 2000;
+        let (mut total, mut expected) = (0, 0.0);
+        for seed in 0..10 {
+            let mut population = vec![Agent::default(); n];
+            for (i, ag) in population.iter_mut().enumerate() {
+                let model = if i % 10 == 0 {
+                    SEIR::Infectious(())
+                } else {
+                    SEIR::Recovered(())
+                };
+                ag.set_epimodel(model);
+            }
+            let mut params: EpiParamsGlobal<Real> = EpiParamsFull::default().cached();
+            params.set_relative_susceptibility_recovered(1.0);
+            let mut sampler = ContactMatrixSampler::new(255, ndarray::arr2(&[[4.0]]), 0.1);
+            sampler.init(&mut population);
+            let mut sim = Simulation::new(params, population, sampler);
+            sim.seed(seed).track_flows().run(1);
+            let flows = sim.sankey_flows();
+            let reinfected = flows.iter().filter(|f| (f.0, f.1) == (SEIRm::R, SEIRm::E));
+            total += reinfected.map(|f| f.2).sum::<usize>();
+
+            // Reinfections only come from sample_reinfection_pairs(), with
+            // hazard 1 - exp(-prob * contacts * n_contagious / n) for each
+            // agent recovered after the update of agents
+            let contagious = sim.contagious_per_iter[0];
+            let dead = sim.population().n_dead();
+            let hazard = 1.0 - (-0.1 * 4.0 * contagious as Real / n as Real).exp();
+            expected += (n - contagious - dead) as Real * hazard;
+        }
+        assert_approx_eq!(total as Real, expected, 0.1 * expected);
+    }
+
+    #[test]
+    fn transmission_efficiency_declines_with_susceptibles() {
+        let mut sim = new_simulation(5000);
+        sim.seed(42);
+        sim.contaminate_at_random(100, &mut seeded_rng(42u64));
+        sim.run(90);
+
+        let efficiency = sim.transmission_efficiency();
+        let mean = |xs: &[Real]| xs.iter().sum::<Real>() / xs.len() as Real;
+        let early = mean(&efficiency[5..15]);
+        assert_approx_eq!(early, 0.4, 0.07);
+        for &x in &efficiency[5..15] {
+            assert_approx_eq!(x, early, 0.2);
+        }
+
+        let susceptible = sim.population().susceptible_ratio();
+        assert!(susceptible < 0.6);
+        assert!(mean(&efficiency[60..80]) < 0.4 * (susceptible + 0.1));
+    }
+
+    #[test]
+    fn overshoot_is_reduced_by_mitigation_at_threshold() {
+        let params = EpiParamsLocal::default();
+        let p = params.infectious_transition_prob();
+        let prob = 0.1;
+        let contacts = 3.0 * p / (prob * (1.0 - p));
+        let new_sim = |seed: u64| {
+            let population = vec![SIRm::default(); 5000];
+            let mut sim = Simulation::new_simple(params, population, contacts, prob);
+            sim.seed(seed);
+            sim.contaminate_at_random(50, &mut seeded_rng(seed + 100));
+            return sim;
+        };
+
+        // Average over a few replicas, since the threshold is estimated from
+        // the first generation of each run
+        let n_runs = 4;
+        let (mut threshold, mut fast, mut mitigated) = (0.0, 0.0, 0.0);
+        for seed in 0..n_runs {
+            let mut sim = new_sim(seed);
+            sim.run(60);
+            threshold += sim.herd_immunity_threshold() / n_runs as Real;
+            fast += sim.overshoot() / n_runs as Real;
+
+            let mut sim = new_sim(seed);
+            while sim.population().attack_ratio() < 0.55 {
+                sim.run(1);
+            }
+            sim.sampler_mut().set_contacts(0.1 * contacts);
+            sim.run(60);
+            mitigated += sim.overshoot() / n_runs as Real;
+        }
+        assert_approx_eq!(threshold, 2.0 / 3.0, 0.1);
+        assert!(fast > 0.15);
+        assert!(mitigated < 0.5 * fast);
+
+        let population = vec![SIRm::default(); 100];
+        let sim = Simulation::new_simple(params, population, contacts, prob);
+        assert!(sim.herd_immunity_threshold().is_nan());
+        assert_eq!(sim.overshoot(), 0.0);
+    }
+
+    #[test]
+    fn behavioral_response_produces_smaller_waves() {
+        let run = |sensitivity: Real, seed: u64| {
+            let population = vec![SEICHARm::default(); 5000];
+            let params = EpiParamsLocal::default();
+            let mut sim = Simulation::new_simple(params, population, 4.0, 0.1);
+            sim.seed(seed);
+            sim.contaminate_at_random(20, &mut seeded_rng(seed));
+            sim.set_behavioral_response(BehavioralResponse {
+                sensitivity,
+                memory: 7.0,
+            });
+            let mut contacts = vec![];
+            for _ in 0..150 {
+                sim.run(1);
+                contacts.push(sim.sampler().contacts());
+            }
+            return (sim, contacts);
+        };
+        // Count episodes in which contacts drop well below the baseline after
+        // having relaxed back to it. Each episode ends a wave of infections.
+        let episodes = |contacts: &[Real]| {
+            let (mut n, mut relaxed) = (0, true);
+            for &x in contacts {
+                if relaxed && x < 0.8 * 4.0 {
+                    n += 1;
+                    relaxed = false;
+                } else if !relaxed && x > 0.95 * 4.0 {
+                    relaxed = true;
+                }
+            }
+            return n;
+        };
+
+        // Small populations are noisy, so average over a few runs
+        let n_runs = 4;
+        let (mut n_episodes, mut control_attack, mut response_attack) = (0, 0.0, 0.0);
+        for seed in 0..n_runs {
+            let (control, contacts) = run(0.0, seed);
+            assert_eq!(episodes(&contacts), 0);
+            control_attack += control.population().attack_ratio();
+
+            let (response, contacts) = run(2e4, seed);
+            n_episodes += episodes(&contacts);
+            response_attack += response.population().attack_ratio();
+        }
+        assert!(
+            n_episodes >= 2 * n_runs as usize,
+            "episodes: {}",
+            n_episodes
+        );
+        assert!(response_attack < 0.75 * control_attack);
+    }
+
+    #[test]
+    fn params_from_observables_reproduce_doubling_time() {
+        let (params, rate) = EpiParamsFull::from_observables(4.0, 0.01, 6.0);
+        let prob = 0.1;
+        let local = EpiParamsLocal::new(&params);
+
+        // Pool the incidence of a few small replicas seeded with a handful of
+        // cases each, so susceptible depletion stays small within the window
+        let mut inc = [0.0; 25];
+        for seed in 0..8 {
+            let population = vec![SEIRm::default(); 5000];
+            let mut sim = Simulation::new_simple(local, population, rate / prob, prob);
+            sim.seed(seed);
+            sim.contaminate_at_random(5, &mut seeded_rng(seed));
+            sim.run(25);
+
+            let susceptible = sim.get_epicurve(SEIRm::S, true).unwrap();
+            for (i, &cases) in sim.infections_per_iter().iter().enumerate() {
+                inc[i] += cases as Real / susceptible[i];
+            }
+        }
+        let early: Real = inc[10..15].iter().sum();
+        let late: Real = inc[20..25].iter().sum();
+        let doubling_time = 10.0 * (2.0 as Real).ln() / (late / early).ln();
+        assert_approx_eq!(doubling_time, 4.0, 0.6);
+        assert_approx_eq!(params.epidemic.case_fatality_ratio, 0.01);
+    }
+
+    #[test]
+    fn leaky_vaccine_reduces_effective_susceptible_fraction() {
+        let new_sim = |efficacy: Real| {
+            let mut population = vec![SeirAgent::<LeakyVaccine>::default(); 10_000];
+            population.set_vaccines(LeakyVaccine { efficacy });
+            let params = EpiParamsLocal::default();
+            let mut sim = Simulation::new_simple(params, population, 4.0, 0.1);
+            sim.seed(42);
+            sim.contaminate_at_random(50, &mut seeded_rng(42u64));
+            return sim;
+        };
+
+        let mut vaccinated = new_sim(0.6);
+        let raw = vaccinated.population().susceptible_ratio();
+        assert_approx_eq!(vaccinated.effective_susceptible_fraction(), 0.4 * raw);
+        let mut control = new_sim(0.0);
+        assert_approx_eq!(control.effective_susceptible_fraction(), raw);
+
+        vaccinated.run(100);
+        control.run(100);
+        let raw = vaccinated.population().susceptible_ratio();
+        assert!(vaccinated.effective_susceptible_fraction() < raw);
+        assert!(vaccinated.population().attack_ratio() < 0.5 * control.population().attack_ratio());
+    }
+
+    #[test]
+    fn fully_protective_leaky_vaccine_blocks_infection() {
+        for scheduled in [false, true] {
+            let mut population = vec![SeirAgent::<LeakyVaccine>::default(); 2000];
+            for (i, ag) in population.iter_mut().enumerate() {
+                if i % 2 == 0 {
+                    ag.vaccinate(&LeakyVaccine { efficacy: 1.0 });
+                } else if i % 100 == 1 {
+                    ag.set_epimodel(SEIR::Infectious(()));
+                }
+            }
+            let params = EpiParamsLocal::default();
+            let mut sim = Simulation::new_simple(params, population, 4.0, 0.2);
+            sim.seed(42);
+            if scheduled {
+                sim.set_generation_interval(2.0, &[0.0, 0.5, 0.5]);
+            }
+            sim.run(60);
+
+            let pop = sim.population();
+            let vaccinated = pop.iter().step_by(2).filter(|ag| ag.is_contaminated());
+            let unvaccinated = pop.iter().skip(1).step_by(2);
+            assert_eq!(vaccinated.count(), 0);
+            assert!(unvaccinated.filter(|ag| ag.is_contaminated()).count() > 100);
+        }
+    }
+
+    #[test]
+    fn scheduled_contact_matrix_switches_on_lockdown_day() {
+        let run = |lockdown: Option<usize>| {
+            let mut population = vec![Agent::default(); 10_000];
+            for (i, ag) in population.iter_mut().enumerate() {
+                ag.set_age((i % 80) as u8);
+            }
+            let baseline = ndarray::arr2(&[[3.0, 1.0], [1.0, 3.0]]);
+            let mut schedule = vec![(0, ContactMatrixSampler::new(40, baseline.clone(), 0.1))];
+            if let Some(day) = lockdown {
+                let reduced = ContactMatrixSampler::new(40, baseline * 0.1, 0.1);
+                schedule.push((day, reduced));
+            }
+            population.contaminate_at_random(20, &mut seeded_rng(42u64));
+            let mut sampler = ScheduledSampler::new(schedule);
+            sampler.init(&mut population);
+            let params: EpiParamsBindVaccine<AgeParam> = Default::default();
+            let mut sim = Simulation::new(params, population, sampler);
+            sim.seed(42);
+            sim.run(60);
+            return sim.infections_per_iter().clone();
+        };
+
+        let control = run(None);
+        let lockdown = run(Some(20));
+        assert_eq!(control[..20], lockdown[..20]);
+        let late = |inc: &[usize]| inc[30..].iter().sum::<usize>();
+        assert!(late(&lockdown) < late(&control) / 10);
+    }
+
+    #[test]
+    fn inspect_transition_reports_agent_bound_params() {
+        let mut full: EpiParamsFull<AgeParam> = Default::default();
+        let periods = [2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        full.epidemic
+            .set_infectious_period(AgeParam::Distribution(periods));
+        let params = EpiParamsBindVaccine::from(EpiParamsGlobal::from(full));
+        let mut population = vec![Agent::default(); 2];
+        population[1].set_age(75);
+        population[1].set_epimodel(SEIR::Infectious(()));
+        let sim = Simulation::new_simple(params, population, 4.0, 0.1);
+
+        let inspection = sim
+            .inspect_transition(1, SEIR::<()>::Infectious(()).index())
+            .unwrap();
+        let params = sim.params().borrow();
+        let world = LocalBind::<Agent>::world(&*params);
+        assert_eq!(inspection.compartment, Compartment::Infectious);
+        assert_approx_eq!(inspection.period, 9.0);
+        assert_approx_eq!(
+            inspection.transition_prob,
+            world.infectious_transition_prob(&75)
+        );
+        drop(params);
+
+        let exposed = sim.inspect_transition(1, 1).unwrap();
+        assert_eq!(exposed.compartment, Compartment::Exposed);
+        assert_approx_eq!(
+            exposed.transition_prob,
+            exposed.params.incubation_transition_prob()
+        );
+        assert!(sim.inspect_transition(1, 0).is_none());
+        assert!(sim.inspect_transition(2, 2).is_none());
+    }
+
+    #[test]
+    fn seasonal_sampler_advances_once_per_step() {
+        let mut population = vec![Agent::default(); 100];
+        population.contaminate_at_random(5, &mut seeded_rng(42u64));
+        let sampler = SeasonalSampler::new(SimpleSampler::new(4.0, 0.1), 0.5, 365.0, 0.0);
+        let params: EpiParamsBindVaccine<AgeParam> = Default::default();
+        let mut sim = Simulation::new(params, population, sampler);
+        sim.run(10);
+        let expected = sim.sampler().prob_infection_at(10);
+        assert_approx_eq!(sim.sampler().prob_infection(), expected);
+        assert!(expected > 0.1);
+    }
+
+    #[test]
+    fn epidemic_start_day_of_major_and_fizzled_outbreaks() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        sim.run(60);
+        let day = sim.epidemic_start_day(50).unwrap();
+        assert!(day < 30, "outbreak declared on day {}", day);
+        assert!(sim.infections_per_iter[..day].iter().sum::<usize>() <= 50);
+
+        let population = vec![Agent::default(); 1000];
+        let mut sim: Sim = Simulation::new_simple(Default::default(), population, 0.5, 0.1);
+        sim.seed(42);
+        sim.contaminate_at_random(2, &mut seeded_rng(42u64));
+        sim.run(60);
+        assert_eq!(sim.epidemic_start_day(50), None);
+    }
+
+    #[test]
+    fn epidemic_duration_of_completed_and_ongoing_runs() {
+        let params = EpiParamsLocal::default();
+        let p = params.infectious_transition_prob();
+        let contacts = 3.0 * p / (0.1 * (1.0 - p));
+        let population = vec![SIRm::default(); 5000];
+        let mut sim = Simulation::new_simple(params, population, contacts, 0.1);
+        sim.seed(42);
+        sim.contaminate_at_random(50, &mut seeded_rng(42u64));
+
+        sim.run(20);
+        assert_eq!(sim.epidemic_duration(0.01), None);
+
+        sim.run(280);
+        let prevalence = &sim.contagious_per_iter;
+        let peak =
+            (0..prevalence.len()).fold(0, |k, i| if prevalence[i] > prevalence[k] { i } else { k });
+        let day = sim.epidemic_duration(0.01).unwrap();
+        assert!(day > peak && day < 300, "peak: {}, end: {}", peak, day);
+        assert!(prevalence[day..].iter().all(|&k| k < 50));
+        assert!(prevalence[day - 1] >= 50);
+    }
+
+    #[test]
+    fn expected_incidence_tracks_next_day_cases() {
+        let mut sim = new_simulation(5000);
+        sim.seed(42);
+        sim.contaminate_at_random(20, &mut seeded_rng(1u64));
+        sim.run(5);
+
+        let (mut expected, mut observed) = (0.0, 0.0);
+        for _ in 0..10 {
+            expected += sim.expected_incidence_next_day();
+            observed += sim.steps(1) as Real;
+        }
+        assert!(observed > 100.0);
+        assert!((expected - observed).abs() < 4.0 * observed.sqrt());
+    }
+
+    #[test]
+    fn susceptible_replenishment_prevents_epidemic_peak() {
+        let run = |replenish: bool| {
+            let mut sim = new_simulation(2000);
+            sim.sampler_mut().set_contacts(10.0);
+            sim.seed(42);
+            sim.set_susceptible_replenishment(replenish);
+            sim.contaminate_at_random(5, &mut seeded_rng(1u64));
+            sim.run(40);
+            return sim;
+        };
+
+        let tail = |cases: &[usize]| cases[cases.len() - 5..].iter().sum::<usize>() as Real / 5.0;
+
+        let sim = run(true);
+        let cases = sim.infections_per_iter();
+        assert_eq!(sim.population().n_susceptible(), 1995);
+        assert_eq!(sim.count(), 2000 + cases.iter().sum::<usize>());
+        assert!(tail(cases) > 0.8 * *cases.iter().max().unwrap() as Real);
+
+        let sim = run(false);
+        let cases = sim.infections_per_iter();
+        assert!(tail(cases) < 0.5 * *cases.iter().max().unwrap() as Real);
+    }
+
+    #[test]
+    #[should_panic(expected = "sampler does not support susceptible replenishment")]
+    fn susceptible_replenishment_rejects_indexed_samplers() {
+        let population = vec![Agent::default(); 100];
+        let contacts = ndarray::arr2(&[[3.0, 1.0], [1.0, 3.0]]);
+        let sampler = ContactMatrixSampler::new(40, contacts, 0.1);
+        let mut sim = Simulation::new(EpiParamsLocal::default(), population, sampler);
+        sim.set_susceptible_replenishment(false);
+        sim.set_susceptible_replenishment(true);
+    }
+
+    #[test]
+    fn transmission_by_asymptomatic_sources() {
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.epidemic.set_prob_asymptomatic(0.42);
+        let population = vec![SEICHARm::default(); 2000];
+        let mut sim = Simulation::new_simple(EpiParamsLocal::new(&params), population, 8.0, 0.1);
+        sim.seed(42);
+        sim.contaminate_at_random(20, &mut seeded_rng(1u64));
+        sim.run(60);
+
+        let shares = sim.transmission_by_source_compartment();
+        assert_eq!(shares.len(), SEICHARm::CARDINALITY);
+        assert_approx_eq!(shares.iter().sum::<Real>(), 1.0);
+        assert!(shares[SEICHARm::A] > 0.1 && shares[SEICHARm::A] < 0.4);
+        assert!(shares[SEICHARm::I] > shares[SEICHARm::A]);
+        assert_eq!(shares[SEICHARm::S], 0.0);
+    }
+
+    #[test]
+    fn bundle_round_trip_reproduces_curves() {
+        let population = vec![SEIRm::default(); 500];
+        let mut sim = Simulation::new_simple(EpiParamsLocal::default(), population, 5.0, 0.1);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.track_transmissions()
+            .set_contact_tracing(ContactTracing::default())
+            .with_reseeding(2);
+
+        let dir = std::env::temp_dir().join(format!("covid-bundle-{}", std::process::id()));
+        sim.export_bundle(&dir).unwrap();
+        let mut copy: Simulation<EpiParamsLocal, SEIRm, SimpleSampler> =
+            Simulation::from_bundle(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(copy.population(), sim.population());
+        sim.run(40);
+        copy.run(40);
+        assert_eq!(copy.infections_per_iter(), sim.infections_per_iter());
+        assert_eq!(copy.imported_per_iter(), sim.imported_per_iter());
+        assert_eq!(copy.transmission_log(), sim.transmission_log());
+        assert_eq!(copy.n_isolated(), sim.n_isolated());
+        for n in 1..=40 {
+            assert_eq!(copy.get_epistate(n, false), sim.get_epistate(n, false));
+        }
+        assert!(!sim.transmission_log().unwrap().is_empty());
+
+        let dir = std::env::temp_dir().join(format!("covid-bundle-run-{}", std::process::id()));
+        assert!(sim.export_bundle(&dir).is_err());
+    }
+
+    #[test]
+    fn icu_collapse_increases_deaths() {
+        // Clinical outcomes are made common, so a small population suffices
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.clinical.prob_severe = 0.5;
+        params.clinical.prob_critical = 0.5;
+        params.epidemic.case_fatality_ratio = 0.05;
+
+        let deaths = |capacity: Option<HealthcareCapacity>| {
+            let population = vec![SEICHARm::default(); 4000];
+            let mut sim =
+                Simulation::new_simple(EpiParamsLocal::new(&params), population, 7.0, 0.1);
+            sim.seed(42);
+            if let Some(capacity) = capacity {
+                sim.set_healthcare_capacity(capacity);
+            }
+            sim.contaminate_at_random(20, &mut seeded_rng(1u64));
+            sim.run(100);
+            sim.population().iter().filter(|ag| ag.is_dead()).count()
+        };
+
+        let ample = deaths(Some(HealthcareCapacity::new(10000, 10000)));
+        let scarce = deaths(Some(HealthcareCapacity::new(10000, 2)));
+        assert_eq!(ample, deaths(None));
+        assert!(scarce as Real > 1.5 * ample as Real);
+    }
+
+    #[test]
+    fn declining_cfr_schedule_reduces_late_deaths() {
+        // Fraction of deaths among agents infected well before and after the
+        // switch at day 50. Clinical outcomes are made common, so a small
+        // population suffices.
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.clinical.prob_severe = 0.5;
+        params.clinical.prob_critical = 0.5;
+        params.epidemic.case_fatality_ratio = 0.2;
+        let run = |schedule: Vec<(usize, Real)>| {
+            let population = vec![SEICHARm::default(); 4000];
+            let mut sim =
+                Simulation::new_simple(EpiParamsLocal::new(&params), population, 7.0, 0.1);
+            sim.seed(42);
+            sim.set_cfr_schedule(schedule);
+            sim.contaminate_at_random(20, &mut seeded_rng(1u64));
+            sim.run(100);
+
+            let mut counts = [[0usize; 2]; 2];
+            for (ag, t) in sim.population().iter().zip(sim.infection_times()) {
+                match t {
+                    Some(t) if *t < 30 || *t >= 50 => {
+                        let row = &mut counts[(*t >= 50) as usize];
+                        row[0] += 1;
+                        row[1] += ag.is_dead() as usize;
+                    }
+                    _ => {}
+                }
+            }
+            let ratio = |[n, k]: [usize; 2]| k as Real / n as Real;
+            return (ratio(counts[0]), ratio(counts[1]));
+        };
+
+        let (early, late) = run(vec![(0, 1.0), (50, 0.1)]);
+        let (_, control_late) = run(vec![]);
+        assert!(late < 0.5 * early);
+        assert!(late < 0.5 * control_late);
+    }
+
+    #[test]
+    fn realized_r0_of_first_generation() {
+        let params = EpiParamsLocal::default();
+        let p = params.infectious_transition_prob();
+        let (contacts, prob) = (4.0, 0.1);
+        let n_runs = 50;
+        let mut r0 = 0.0;
+        for seed in 0..n_runs {
+            let population = vec![SIRm::default(); 2000];
+            let mut sim = Simulation::new_simple(params, population, contacts, prob);
+            sim.seed(seed);
+            sim.contaminate_at_random(20, &mut seeded_rng(seed + 100));
+            sim.run(20);
+            r0 += sim.realized_r0_first_generation() / n_runs as Real;
+        }
+
+        // Agents are updated before sampling contacts, so an infectious agent
+        // transmits for a geometric number of steps with mean (1 - p) / p.
+        let expected = contacts * prob * (1.0 - p) / p;
+        assert!((r0 - expected).abs() < 0.15 * expected);
+    }
+
+    #[test]
+    fn days_since_exposure_counts_from_last_contact() {
+        let mut params: EpiParamsFull<Real> = Default::default();
+        params.epidemic.set_infectious_period(1e6);
+        let population = vec![SEIRm::Infectious(()), SEIRm::Susceptible];
+        let mut sim = Simulation::new_simple(EpiParamsLocal::new(&params), population, 0.0, 1.0);
+        sim.seed(42);
+        sim.track_exposures();
+
+        sim.run(5);
+        assert_eq!(sim.days_since_exposure(1), None);
+
+        // Agent 1 contacts the infectious agent on day 5
+        sim.sampler_mut().set_contacts(1.0);
+        sim.run(1);
+        sim.sampler_mut().set_contacts(0.0);
+        sim.run(2);
+
+        assert_eq!(sim.infections_per_iter().len(), 8);
+        assert_eq!(sim.days_since_exposure(1), Some(3));
+        assert_eq!(sim.days_since_exposure(0), None);
+    }
+
+    #[test]
+    fn smoothed_cases_follow_incidence() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.run(30);
+        let cases: Vec<Real> = sim
+            .infections_per_iter()
+            .iter()
+            .map(|&x| x as Real)
+            .collect();
+        assert_eq!(sim.smoothed_cases(1.0), cases);
+
+        let smoothed = sim.smoothed_cases(0.2);
+        assert_eq!(smoothed.len(), 30);
+        assert_eq!(smoothed[0], cases[0]);
+        assert_approx_eq!(smoothed[1], 0.2 * cases[1] + 0.8 * cases[0]);
+    }
+
+    #[test]
+    fn incidence_matches_new_cases_and_flows() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.track_flows().track_incidence();
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.run(30);
+
+        let incidence = sim.incidence().unwrap();
+        assert_eq!(incidence.n_steps(), 30);
+        assert_eq!(
+            &incidence.incidence(SEIRm::S, SEIRm::E),
+            sim.infections_per_iter()
+        );
+        for (from, to, count) in sim.sankey_flows() {
+            assert_eq!(incidence.incidence(from, to).iter().sum::<usize>(), count);
+        }
+
+        let csv = incidence.render_csv(SEIRm::CSV_HEADER);
+        assert!(csv.starts_with("S->E,E->I,I->R"));
+        assert_eq!(csv.lines().count(), 31);
+    }
+
+    #[test]
+    fn transmission_log_dates_generation_intervals() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        sim.track_transmissions();
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.run(30);
+
+        let log = sim.transmission_log().unwrap();
+        assert_eq!(log.len(), sim.infections_per_iter().iter().sum::<usize>());
+        for &(i, j, step) in log {
+            assert_eq!(sim.infection_times()[j], Some(step as Time));
+            assert!(sim.infections_per_agent()[i] > 0);
+        }
+
+        let from_known_sources = log
+            .iter()
+            .filter(|(i, _, _)| sim.infection_times()[*i].is_some())
+            .count();
+        let intervals = sim.generation_intervals();
+        assert_eq!(intervals.len(), from_known_sources);
+        assert!(!intervals.is_empty());
+        assert!(intervals.iter().all(|&dt| dt > 0));
+    }
+
+    #[test]
+    fn summary_reports_peaks_and_final_size() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        let summary = sim.summary();
+        assert_eq!(summary.peaks[Agent::S], 1000);
+        assert_eq!(summary.final_size, 0);
+        sim.run(10);
+        let summary = sim.summary();
+        assert_eq!(summary.peak_steps, vec![0; Agent::CARDINALITY]);
+        assert_eq!(summary.peaks[SEIRm::I], 0);
+
+        sim.contaminate_at_random(10, &mut seeded_rng(1u64));
+        sim.run(120);
+        let summary = sim.summary();
+        let curve = sim.get_epicurve(SEIRm::I, false).unwrap();
+        let peak = curve.iter().cloned().fold(0.0, Real::max);
+        assert_eq!(summary.peaks[SEIRm::I] as Real, peak);
+        assert_eq!(curve[summary.peak_steps[SEIRm::I]], peak);
+        assert!(summary.peak_steps[SEIRm::I] > 10);
+        assert_eq!(summary.final_size, sim.population().n_contaminated());
+        assert_eq!(summary.deaths, sim.population().n_dead());
+    }
+
+    #[test]
+    fn checkpoint_restore_matches_uninterrupted_run() {
+        type Sim = Simulation<EpiParamsLocal, SEIRm, SimpleSampler>;
+        let new_sim = || {
+            let population = vec![SEIRm::default(); 1000];
+            let mut sim: Sim =
+                Simulation::new_simple(EpiParamsLocal::default(), population, 5.0, 0.1);
+            sim.seed(42);
+            sim.track_transmissions();
+            sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+            return sim;
+        };
+        let mut reference = new_sim();
+        reference.run(20);
+
+        let mut sim = new_sim();
+        sim.run(10);
+        let data = serde_json::to_string(&sim.checkpoint()).unwrap();
+        drop(sim);
+        let mut restored = Sim::restore(serde_json::from_str(&data).unwrap());
+        restored.run(10);
+
+        assert_eq!(restored.population(), reference.population());
+        assert_eq!(
+            restored.infections_per_agent,
+            reference.infections_per_agent
+        );
+        assert_eq!(restored.infections_per_iter, reference.infections_per_iter);
+        assert_eq!(restored.transmission_log(), reference.transmission_log());
+        assert_eq!(
+            restored.history_array(false),
+            reference.history_array(false)
+        );
+    }
+
+    #[test]
+    fn run_until_stops_on_condition_or_max_steps() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        assert!(sim.extinct());
+        assert_eq!(sim.run_until(|s| s.extinct(), 100), 0);
+        assert_eq!(sim.run_until(|_| false, 5), 5);
+        assert_eq!(sim.infections_per_iter().len(), 5);
+
+        sim.contaminate_at_random(10, &mut seeded_rng(42u64));
+        assert!(!sim.extinct());
+        let n = sim.run_until(|s| s.prevalence_above(0.05), 200);
+        assert!(n > 0 && n < 200);
+        assert!(sim.prevalence_above(0.05));
+        assert_eq!(sim.infections_per_iter().len(), 5 + n);
+
+        let n = sim.run_until(Simulation::extinct, 1000);
+        assert!(n < 1000);
+        assert!(sim.extinct());
+    }
+
+    #[test]
+    fn reseeding_keeps_contagious_floor_and_counts_imported_cases() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42).track_flows().track_incidence().run(3);
+        assert_eq!(sim.imported_per_iter(), &[0, 0, 0]);
+        assert!(sim.extinct());
+
+        sim.with_reseeding(5).run(1);
+        assert_eq!(sim.imported_per_iter(), &[0, 0, 0, 5]);
+        assert_eq!(sim.infections_per_iter(), &[0, 0, 0, 0]);
+        assert_eq!(sim.sankey_flows(), vec![(SEIRm::S, SEIRm::I, 5)]);
+        let incidence = sim.incidence().unwrap().incidence(SEIRm::S, SEIRm::I);
+        assert_eq!(incidence, vec![0, 0, 0, 5]);
+        let times = sim.infection_times().iter().filter(|t| **t == Some(3));
+        assert_eq!(times.count(), 5);
+        assert!(sim.realized_r0_first_generation().is_nan());
+        assert_eq!(
+            sim.population()
+                .iter()
+                .filter(|ag| ag.is_contagious())
+                .count(),
+            5
+        );
+
+        sim.run(50);
+        let imported: usize = sim.imported_per_iter().iter().sum();
+        let cases: usize = sim.infections_per_iter().iter().sum();
+        let contaminated = sim.population().iter().filter(|ag| ag.is_contaminated());
+        assert_eq!(contaminated.count(), imported + cases);
+        assert_eq!(sim.imported_per_iter().len(), 54);
+    }
+
+    #[test]
+    fn parallel_updates_match_sequential_per_agent_rng() {
+        let run = |parallel: bool| {
+            let mut sim = new_simulation(2000);
+            sim.seed(42);
+            sim.track_incidence();
+            sim.set_per_agent_rng(true);
+            sim.set_parallel(parallel);
+            sim.contaminate_at_random(20, &mut seeded_rng(42u64));
+            sim.run(40);
+            return sim;
+        };
+        let (sequential, parallel) = (run(false), run(true));
+        assert_eq!(sequential.population(), parallel.population());
+        assert_eq!(sequential.infections_per_iter, parallel.infections_per_iter);
+        assert_eq!(sequential.incidence(), parallel.incidence());
+        assert!(sequential.population().n_contaminated() > 20);
+    }
+
+    #[test]
+    fn on_step_vaccinates_one_percent_every_week() {
+        let mut sim = new_simulation(1000);
+        sim.seed(42);
+        let mut steps = vec![];
+        sim.on_step(7, move |step, _, population| {
+            population.distribute_vaccines(population.len() / 100, true, |ag| !ag.vaccine());
+            steps.push(step);
+            assert_eq!(steps.len(), step / 7 + 1);
+        });
+        let n_vaccinated = |sim: &Sim| sim.population().iter().filter(|ag| *ag.vaccine()).count();
+
+        sim.run(1);
+        assert_eq!(n_vaccinated(&sim), 10);
+        sim.run(6);
+        assert_eq!(n_vaccinated(&sim), 10);
+        sim.run(15);
+        assert_eq!(n_vaccinated(&sim), 40);
+    }
+
+    #[test]
+    fn isolation_blocks_scheduled_transmission_and_expires() {
+        let run = |tracing: Option<ContactTracing>| {
+            let mut sim = new_simulation(5000);
+            sim.seed(42);
+            for ag in sim.population_mut()[..20].iter_mut() {
+                ag.set_epimodel(SEIR::Infectious(()));
+            }
+            sim.set_generation_interval(2.0, &[0.0, 0.0, 1.0]);
+            if let Some(policy) = tracing {
+                sim.set_contact_tracing(policy);
+            }
+            sim.run(1);
+            return sim;
+        };
+        let policy = ContactTracing {
+            detect_probability: 1.0,
+            detect_symptomatic: false,
+            detection_delay: 0,
+            trace_probability: 1.0,
+            trace_delay: 1,
+            isolate_traced: true,
+            backward: false,
+            isolation_days: 5,
+            memory: 14,
+        };
+
+        let sources = |sim: &mut Sim| -> Vec<Id> {
+            let steps = (0..3).map(|_| sim.step_collecting_infections());
+            return steps.flatten().map(|(i, _)| i).collect();
+        };
+        let mut sim = run(Some(policy));
+        let isolated: Vec<Id> = (0..20).filter(|&id| sim.is_isolated(id)).collect();
+        assert!(isolated.len() > 10);
+        assert!(sources(&mut sim).iter().all(|i| !isolated.contains(i)));
+        assert!(sources(&mut run(None)).iter().any(|i| isolated.contains(i)));
+
+        sim.run(1);
+        assert!(isolated.iter().all(|&id| sim.is_isolated(id)));
+        sim.run(1);
+        assert!(isolated.iter().all(|&id| !sim.is_isolated(id)));
+    }
+}
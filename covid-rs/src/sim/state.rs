@@ -1,7 +1,7 @@
 use rand::Rng;
 
 use crate::{
-    prelude::{Age, AgeDistribution10, EpiModel},
+    prelude::{Age, AgeDistribution10, EpiModel, Real, Time},
     utils::random_ages,
 };
 use std::fmt::Debug;
@@ -55,6 +55,22 @@ where
 {
 }
 
+/// A trait for agents that occupy a position in a 2D space. Spatial samplers
+/// and seeding strategies use it to locate agents.
+pub trait HasPosition {
+    /// Agent's (x, y) position.
+    fn position(&self) -> (Real, Real);
+
+    /// Set position with given value.
+    fn set_position(&mut self, value: (Real, Real)) -> &mut Self;
+
+    /// Euclidean distance from agent to the given point.
+    fn distance_to(&self, point: (Real, Real)) -> Real {
+        let (x, y) = self.position();
+        return ((x - point.0).powi(2) + (y - point.1).powi(2)).sqrt();
+    }
+}
+
 /// A trait for objects that have an compartment field with a SIR value.
 pub trait HasEpiModel {
     type Model: EpiModel;
@@ -81,6 +97,17 @@ pub trait HasEpiModel {
     {
         self.epimodel_mut().random_update(params, rng);
     }
+
+    /// Return true if agent is isolated. Agents that do not store isolation
+    /// are never isolated.
+    fn isolated(&self) -> bool {
+        false
+    }
+
+    /// Isolate or release agent and return true if agent stores isolation.
+    fn set_isolated(&mut self, _value: bool) -> bool {
+        false
+    }
 }
 
 /// A trait for objects that can be deterministically updated in the given
@@ -94,6 +121,25 @@ pub trait DeterministicUpdate<W> {
 // this object
 pub trait RandomUpdate<W> {
     fn random_update<R: Rng>(&mut self, world: &W, rng: &mut R);
+
+    /// Minimum number of days in a contagious state before random_update()
+    /// may move the object out of it. Agents that track the time since their
+    /// last transition (e.g., SimpleAgent) hold contagious states until then.
+    fn min_infectious_days(_world: &W) -> Time {
+        0
+    }
+
+    /// Like random_update(), but return the (from, to) compartment indices
+    /// if the update produced a transition.
+    fn random_update_tracked<R: Rng>(&mut self, world: &W, rng: &mut R) -> Option<(usize, usize)>
+    where
+        Self: EpiModel,
+    {
+        let from = self.index();
+        self.random_update(world, rng);
+        let to = self.index();
+        return if from == to { None } else { Some((from, to)) };
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,245 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use super::Id;
+use crate::{epidemic::EpiModel, prelude::Real, prelude::Time};
+
+/// A test-trace-isolate policy.
+///
+/// Each day, contagious agents are detected with probability detect_probability
+/// or, if detect_symptomatic is set, each newly symptomatic agent is detected
+/// with this probability. Detected agents are isolated after detection_delay
+/// days. After further trace_delay days, each agent that had an infectious
+/// contact with the detected agent in the last memory days is traced with
+/// probability trace_probability (forward tracing). This includes the agents
+/// infected by the detected agent in the transmission log and contacts that
+/// did not result in transmission. With backward tracing, the infector of the
+/// detected agent and all of its contacts are also traced, which is effective
+/// at catching the other contacts of superspreaders.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContactTracing {
+    /// Daily probability that a contagious agent is detected or, if
+    /// detect_symptomatic is set, probability that an agent is detected at
+    /// symptom onset.
+    pub detect_probability: Real,
+    /// Detect agents only when they become symptomatic.
+    #[serde(default)]
+    pub detect_symptomatic: bool,
+    /// Days between detection and isolation of detected agents.
+    #[serde(default)]
+    pub detection_delay: Time,
+    /// Probability that each contact of a detected agent is traced.
+    pub trace_probability: Real,
+    /// Days between detection and tracing.
+    pub trace_delay: Time,
+    /// If true, isolate all traced agents. Otherwise only traced agents that
+    /// are contaminated are isolated (i.e., traced agents are tested).
+    pub isolate_traced: bool,
+    /// Also trace the infector of each detected agent and its contacts.
+    pub backward: bool,
+    /// Number of days an isolated agent is removed from all contacts.
+    pub isolation_days: Time,
+    /// Number of days contacts are remembered. Older contacts are not traced.
+    pub memory: Time,
+}
+
+impl Default for ContactTracing {
+    fn default() -> Self {
+        ContactTracing {
+            detect_probability: 0.1,
+            detect_symptomatic: false,
+            detection_delay: 0,
+            trace_probability: 0.5,
+            trace_delay: 2,
+            isolate_traced: true,
+            backward: false,
+            isolation_days: 14,
+            memory: 14,
+        }
+    }
+}
+
+/// Contact and transmission logs used to execute a tracing policy.
+///
+/// The contacts of each agent are stored with the day they occurred and are
+/// forgotten after the memory of the policy, so the log does not grow with the
+/// duration of the simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TracingState {
+    policy: ContactTracing,
+    contacts: Vec<VecDeque<(Time, Id)>>,
+    infector: Vec<Option<Id>>,
+    detected: Vec<bool>,
+    symptomatic: Vec<bool>,
+    isolated_until: Vec<Option<Time>>,
+    pending: VecDeque<(Time, Id)>,
+    queue: VecDeque<(Time, Id)>,
+}
+
+impl TracingState {
+    pub fn new(policy: ContactTracing, n: usize) -> Self {
+        TracingState {
+            policy,
+            contacts: vec![VecDeque::new(); n],
+            infector: vec![None; n],
+            detected: vec![false; n],
+            symptomatic: vec![false; n],
+            isolated_until: vec![None; n],
+            pending: VecDeque::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Register a new agent at the end of population.
+    pub fn push_agent(&mut self) {
+        self.contacts.push(VecDeque::new());
+        self.infector.push(None);
+        self.detected.push(false);
+        self.symptomatic.push(false);
+        self.isolated_until.push(None);
+    }
+
+    /// True if agent is isolated at the given time.
+    pub fn is_isolated(&self, id: Id, now: Time) -> bool {
+        matches!(self.isolated_until[id], Some(t) if t > now)
+    }
+
+    /// Number of isolated agents at the given time.
+    pub fn n_isolated(&self, now: Time) -> usize {
+        (0..self.isolated_until.len())
+            .filter(|&id| self.is_isolated(id, now))
+            .count()
+    }
+
+    /// Log contact from src to dest at the given time. If infected=true, src
+    /// is registered as the infector of dest.
+    pub fn register_contact(&mut self, src: Id, dest: Id, infected: bool, now: Time) {
+        self.forget(src, now);
+        self.contacts[src].push_back((now, dest));
+        if infected {
+            self.infector[dest].get_or_insert(src);
+        }
+    }
+
+    /// Detect new cases, isolate cases detected detection_delay days ago and
+    /// trace contacts of cases isolated trace_delay days ago.
+    ///
+    /// Log is the list of (source, target, step) transmission events, which
+    /// is used to trace the agents infected by each case.
+    pub fn update<S: EpiModel, R: Rng>(
+        &mut self,
+        population: &[S],
+        log: &[(Id, Id, usize)],
+        now: Time,
+        rng: &mut R,
+    ) {
+        let policy = self.policy;
+
+        for (id, agent) in population.iter().enumerate() {
+            let candidate = if policy.detect_symptomatic {
+                let onset = agent.is_symptomatic() && !self.symptomatic[id];
+                self.symptomatic[id] = agent.is_symptomatic();
+                onset
+            } else {
+                agent.is_contagious()
+            };
+            if !self.detected[id] && candidate && rng.gen_bool(policy.detect_probability) {
+                self.detected[id] = true;
+                self.pending.push_back((now + policy.detection_delay, id));
+            }
+        }
+
+        while let Some(&(t, id)) = self.pending.front() {
+            if t > now {
+                break;
+            }
+            self.pending.pop_front();
+            self.isolate(id, now);
+            self.queue.push_back((now + policy.trace_delay, id));
+        }
+
+        while let Some(&(t, id)) = self.queue.front() {
+            if t > now {
+                break;
+            }
+            self.queue.pop_front();
+
+            let mut targets = self.recent_contacts(id, now);
+            for k in self.recent_infections(id, log, now) {
+                if !targets.contains(&k) {
+                    targets.push(k);
+                }
+            }
+            if policy.backward {
+                if let Some(src) = self.infector[id] {
+                    targets.push(src);
+                    targets.extend(self.recent_contacts(src, now));
+                }
+            }
+            for k in targets {
+                if k != id
+                    && rng.gen_bool(policy.trace_probability)
+                    && (policy.isolate_traced || population[k].is_contaminated())
+                {
+                    self.isolate(k, now);
+                }
+            }
+        }
+    }
+
+    /// Contacts of agent within the memory of the policy.
+    fn recent_contacts(&mut self, id: Id, now: Time) -> Vec<Id> {
+        self.forget(id, now);
+        return self.contacts[id].iter().map(|&(_, k)| k).collect();
+    }
+
+    /// Agents infected by agent within the memory of the policy, according to
+    /// the transmission log.
+    fn recent_infections(&self, id: Id, log: &[(Id, Id, usize)], now: Time) -> Vec<Id> {
+        let memory = self.policy.memory;
+        return log
+            .iter()
+            .filter(|&&(src, _, t)| src == id && t as Time + memory >= now)
+            .map(|&(_, dest, _)| dest)
+            .collect();
+    }
+
+    /// Drop contacts of agent that are older than the memory of the policy.
+    fn forget(&mut self, id: Id, now: Time) {
+        let memory = self.policy.memory;
+        let log = &mut self.contacts[id];
+        while matches!(log.front(), Some(&(t, _)) if t + memory < now) {
+            log.pop_front();
+        }
+    }
+
+    fn isolate(&mut self, id: Id, now: Time) {
+        self.isolated_until[id] = Some(now + self.policy.isolation_days);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contacts_are_forgotten_after_memory() {
+        let policy = ContactTracing {
+            memory: 3,
+            ..ContactTracing::default()
+        };
+        let mut state = TracingState::new(policy, 4);
+        state.register_contact(0, 1, false, 0);
+        state.register_contact(0, 2, true, 2);
+        state.register_contact(0, 3, false, 4);
+        assert_eq!(state.recent_contacts(0, 4), vec![2, 3]);
+        assert_eq!(state.recent_contacts(0, 6), vec![3]);
+        assert_eq!(state.contacts[0].len(), 1);
+        assert_eq!(state.infector[2], Some(0));
+    }
+}
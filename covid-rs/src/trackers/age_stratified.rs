@@ -0,0 +1,105 @@
+use super::{table_tracker::TableTracker, tracker::Tracker};
+use crate::{
+    prelude::{Age, AgeBands, EpiModel},
+    sim::{HasAge, Population},
+};
+
+/// Number of 10-year age groups in an AgeStratifiedTracker.
+pub const AGE_GROUPS: usize = 9;
+
+/// Track epicurves separately for each 10-year age group.
+///
+/// Groups follow the same convention as AgeDistribution10: group i contains
+/// ages from 10 * i to 10 * i + 9 and the last group is open-ended, so agents
+/// aged 80 or older are all counted in it.
+#[derive(Clone, Debug)]
+pub struct AgeStratifiedTracker {
+    groups: Vec<TableTracker<usize>>,
+}
+
+impl AgeStratifiedTracker {
+    /// Create tracker for a model with the given number of compartments.
+    pub fn new(cardinality: usize) -> Self {
+        AgeStratifiedTracker {
+            groups: vec![TableTracker::new(cardinality); AGE_GROUPS],
+        }
+    }
+
+    /// Create tracker for epidemic model M.
+    pub fn for_model<M: EpiModel>() -> Self {
+        Self::new(M::CARDINALITY)
+    }
+
+    /// Index of the age group that contains age.
+    pub fn group(age: Age) -> usize {
+        AgeBands::<(), AGE_GROUPS, 10>::band(age)
+    }
+
+    /// Epicurves of the given age group.
+    pub fn table(&self, group: usize) -> Option<&TableTracker<usize>> {
+        self.groups.get(group)
+    }
+
+    /// Number of agents of the given age group in compartment at each step.
+    pub fn curve_for_age_group(&self, group: usize, compartment: usize) -> Option<Vec<usize>> {
+        let table = self.groups.get(group)?;
+        if compartment >= table.ncols() {
+            return None;
+        }
+        return table.col(compartment);
+    }
+}
+
+impl<P> Tracker<P> for AgeStratifiedTracker
+where
+    P: Population,
+    P::State: EpiModel + HasAge,
+{
+    fn track(&mut self, population: &P) {
+        for table in self.groups.iter_mut() {
+            table.step();
+        }
+        let groups = &mut self.groups;
+        population.each_agent(&mut |_, ag: &P::State| {
+            groups[Self::group(ag.age())].incr(ag.index());
+        });
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        epidemic::SEIRLike,
+        models::{SEIRm, SeirAgent, SEIR},
+        sim::HasEpiModel,
+    };
+
+    #[test]
+    fn age_stratified_tracker_counts_each_group() {
+        let mut population = vec![SeirAgent::<()>::default(); 4];
+        population[0].set_age(5);
+        population[1].set_age(15).set_epimodel(SEIR::Infectious(()));
+        population[2].set_age(85).set_epimodel(SEIR::Infectious(()));
+        population[3].set_age(120).set_epimodel(SEIR::Recovered(()));
+
+        let mut tracker = AgeStratifiedTracker::for_model::<SEIRm>();
+        tracker.track(&population);
+        population[0].set_epimodel(SEIR::Exposed(()));
+        tracker.track(&population);
+
+        let (s, e, i, r) = (SEIRm::S, SEIRm::E, SEIRm::I, SEIRm::R);
+        assert_eq!(tracker.curve_for_age_group(0, s), Some(vec![1, 0]));
+        assert_eq!(tracker.curve_for_age_group(0, e), Some(vec![0, 1]));
+        assert_eq!(tracker.curve_for_age_group(1, i), Some(vec![1, 1]));
+        assert_eq!(tracker.curve_for_age_group(8, i), Some(vec![1, 1]));
+        assert_eq!(tracker.curve_for_age_group(8, r), Some(vec![1, 1]));
+        assert_eq!(tracker.curve_for_age_group(4, s), Some(vec![0, 0]));
+        assert_eq!(tracker.curve_for_age_group(9, s), None);
+        assert_eq!(tracker.curve_for_age_group(0, 99), None);
+    }
+}
@@ -0,0 +1,142 @@
+use super::tracker::Tracker;
+use crate::{prelude::EpiModel, sim::Population};
+use getset::CopyGetters;
+use log::error;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Default number of rows written between flushes of a CsvSink.
+pub const CSV_SINK_FLUSH_INTERVAL: usize = 100;
+
+/// Stream epicurves to a CSV file, one row per tracked step.
+///
+/// Each call to track() counts the number of agents in each compartment and
+/// appends a row to the file, so memory usage does not grow with the number
+/// of steps as in a TableTracker. Rows are buffered and flushed after every
+/// flush_interval rows and when the sink is dropped.
+///
+/// Tracking cannot report errors. The first I/O error is logged, writing
+/// stops and the error is returned by finish().
+#[derive(Debug, CopyGetters)]
+pub struct CsvSink {
+    writer: BufWriter<File>,
+    #[getset(get_copy = "pub")]
+    n_rows: usize,
+    #[getset(get_copy = "pub")]
+    flush_interval: usize,
+    error: Option<io::Error>,
+}
+
+impl CsvSink {
+    /// Create file at path, truncating it if it exists, and write header.
+    pub fn create(path: impl AsRef<Path>, header: &str) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", header)?;
+        return Ok(CsvSink {
+            writer,
+            n_rows: 0,
+            flush_interval: CSV_SINK_FLUSH_INTERVAL,
+            error: None,
+        });
+    }
+
+    /// Set the number of rows written between flushes.
+    pub fn set_flush_interval(&mut self, value: usize) -> &mut Self {
+        assert!(value > 0, "flush interval must be positive");
+        self.flush_interval = value;
+        return self;
+    }
+
+    /// Flush remaining rows and close file, returning the first error that
+    /// occurred while tracking.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        return self.writer.flush();
+    }
+
+    fn write_row(&mut self, counts: &[usize]) -> io::Result<()> {
+        let row: Vec<String> = counts.iter().map(|n| n.to_string()).collect();
+        writeln!(self.writer, "{}", row.join(","))?;
+        self.n_rows += 1;
+        if self.n_rows.is_multiple_of(self.flush_interval) {
+            self.writer.flush()?;
+        }
+        return Ok(());
+    }
+}
+
+impl<P> Tracker<P> for CsvSink
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn track(&mut self, population: &P) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut counts = vec![0; P::State::CARDINALITY];
+        population.each_agent(&mut |_, ag: &P::State| counts[ag.index()] += 1);
+        if let Err(err) = self.write_row(&counts) {
+            error!("could not write CSV row: {}", err);
+            self.error = Some(err);
+        }
+    }
+}
+
+impl Drop for CsvSink {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::SIRm,
+        params::{EpiParamsFull, EpiParamsLocal},
+        prelude::Real,
+        sim::Simulation,
+    };
+    use std::{
+        fs,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    #[test]
+    fn csv_sink_streams_one_row_per_step() {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+        let name = format!("covid-csv-sink-{}-{}.csv", std::process::id(), nanos);
+        let path = std::env::temp_dir().join(name);
+        let mut population = vec![SIRm::Susceptible; 99];
+        population.push(SIRm::Infectious(()));
+
+        let params = EpiParamsLocal::new(&EpiParamsFull::<Real>::default());
+        let mut sim = Simulation::new_simple(params, population, 5.0, 0.5);
+        let mut sink = CsvSink::create(&path, SIRm::CSV_HEADER).unwrap();
+        sink.set_flush_interval(3);
+        sim.add_tracker(sink);
+        sim.seed(42);
+        sim.run(10);
+        drop(sim);
+
+        let data = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = data.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "S,I,R,D");
+        for line in &lines[1..] {
+            let total: usize = line.split(',').map(|x| x.parse::<usize>().unwrap()).sum();
+            assert_eq!(total, 100);
+        }
+    }
+}
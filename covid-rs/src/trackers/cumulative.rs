@@ -0,0 +1,101 @@
+use super::tracker::Tracker;
+use crate::{
+    prelude::{EpiModel, Real, NAN},
+    sim::Population,
+};
+use getset::CopyGetters;
+
+/// Track the cumulative number of agents that were ever contaminated.
+///
+/// Agents are marked when they are seen contaminated at some tracked step and
+/// are never unmarked, so recovered and dead agents keep counting, and so do
+/// agents that returned to the susceptible compartment in models such as SIS.
+/// Agents that are contaminated and recover between two tracked steps are not
+/// seen by the tracker.
+#[derive(Clone, Debug, Default, CopyGetters)]
+pub struct CumulativeTracker {
+    ever_contaminated: Vec<bool>,
+    #[getset(get_copy = "pub")]
+    n_contaminated: usize,
+    #[getset(get_copy = "pub")]
+    population_size: usize,
+    curve: Vec<usize>,
+}
+
+impl CumulativeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cumulative number of contaminated agents at each tracked step.
+    pub fn cumulative_curve(&self) -> &[usize] {
+        &self.curve
+    }
+
+    /// Fraction of the population that was ever contaminated, or NaN if no
+    /// population was tracked.
+    pub fn final_attack_rate(&self) -> Real {
+        if self.population_size == 0 {
+            return NAN;
+        }
+        return self.n_contaminated as Real / self.population_size as Real;
+    }
+}
+
+impl<P> Tracker<P> for CumulativeTracker
+where
+    P: Population,
+    P::State: EpiModel,
+{
+    fn track(&mut self, population: &P) {
+        let (ever, n) = (&mut self.ever_contaminated, &mut self.n_contaminated);
+        population.each_agent(&mut |id, ag: &P::State| {
+            if id >= ever.len() {
+                ever.resize(id + 1, false);
+            }
+            if !ever[id] && ag.is_contaminated() {
+                ever[id] = true;
+                *n += 1;
+            }
+        });
+        self.population_size = population.count();
+        self.curve.push(self.n_contaminated);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SEIRm, SISm, SEIR, SIS};
+
+    #[test]
+    fn cumulative_tracker_counts_ever_contaminated() {
+        let mut tracker = CumulativeTracker::new();
+        assert!(tracker.final_attack_rate().is_nan());
+
+        let mut population = vec![SEIRm::Susceptible; 4];
+        tracker.track(&population);
+        population[0] = SEIR::Infectious(());
+        tracker.track(&population);
+        population[0] = SEIR::Recovered(());
+        population[1] = SEIR::Dead(());
+        tracker.track(&population);
+        population[2] = SEIR::Recovered(());
+        population[3] = SEIR::Recovered(());
+        tracker.track(&population);
+        assert_eq!(tracker.cumulative_curve(), &[0, 1, 2, 4]);
+        assert_eq!(tracker.final_attack_rate(), 1.0);
+
+        let mut tracker = CumulativeTracker::new();
+        let mut population = vec![SISm::Infectious(()), SIS::Susceptible];
+        tracker.track(&population);
+        population[0] = SIS::Susceptible;
+        tracker.track(&population);
+        assert_eq!(tracker.cumulative_curve(), &[1, 1]);
+        assert_eq!(tracker.final_attack_rate(), 0.5);
+    }
+}
@@ -1,21 +1,25 @@
 use super::{
-    table_tracker::TableTracker,
+    table_tracker::{CsvOptions, TableTracker},
     tracker::{Tracker, TrackerList},
 };
 use crate::prelude::EpiModel;
 use crate::sim::Population;
 use getset::{CopyGetters, Getters};
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 /// Epicurve reporter that can be extended with an arbitrary list of FnMut()
 /// reporters.
-#[derive(Default, Getters, CopyGetters)]
+///
+/// User defined reporters are not serialized.
+#[derive(Default, Getters, CopyGetters, Serialize, Deserialize)]
 pub struct EpiTracker<P> {
     #[getset(get_copy = "pub")]
     n_iter: usize,
     #[getset(get = "pub")]
     epicurves: TableTracker<usize>,
     #[getset(get = "pub")]
+    #[serde(skip)]
     reporters: TrackerList<P>,
 }
 
@@ -39,6 +43,11 @@ impl<P> EpiTracker<P> {
         self.epicurves.render_csv(head, ',')
     }
 
+    /// Like render_epicurve_csv(), but with custom formatting options.
+    pub fn render_epicurve_csv_with(&self, head: &str, opts: &CsvOptions) -> String {
+        self.epicurves.render_csv_with(head, opts)
+    }
+
     /// Return an array with the last row of epicurves.
     pub fn tip(&self) -> Vec<usize> {
         self.epicurves.tip()
@@ -54,6 +63,13 @@ impl<P> EpiTracker<P> {
         self.epicurves.col(i)
     }
 
+    /// Add a user defined tracker, which is executed after the epicurves are
+    /// updated at each step.
+    pub fn add_reporter(&mut self, tracker: impl Tracker<P> + 'static) -> &mut Self {
+        self.reporters.push((1, Box::new(tracker)));
+        return self;
+    }
+
     /// Return a copy of reporter, ignoring the user defined ones
     pub fn copy(&self) -> Self {
         EpiTracker {
@@ -0,0 +1,93 @@
+use getset::CopyGetters;
+use serde::{Deserialize, Serialize};
+
+/// Record the incidence of each transition between compartments, i.e., the
+/// number of new entries into a compartment from another one at each step.
+///
+/// This complements TableTracker, which stores prevalence. Transitions must be
+/// reported with record() and a new row is started with step(). Simulation
+/// does this automatically after track_incidence() is called.
+#[derive(Clone, Debug, Default, PartialEq, CopyGetters, Serialize, Deserialize)]
+pub struct FlowTracker {
+    #[getset(get_copy = "pub")]
+    cardinality: usize,
+    rows: Vec<Vec<usize>>,
+}
+
+impl FlowTracker {
+    /// Create tracker for a model with the given number of compartments.
+    pub fn new(cardinality: usize) -> Self {
+        FlowTracker {
+            cardinality,
+            rows: vec![],
+        }
+    }
+
+    /// Number of recorded steps.
+    pub fn n_steps(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Start a new step with zero transitions.
+    pub fn step(&mut self) {
+        self.rows.push(vec![0; self.cardinality * self.cardinality]);
+    }
+
+    /// Record a transition in the current step. Transitions to the same
+    /// compartment are ignored.
+    pub fn record(&mut self, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        if self.rows.is_empty() {
+            self.step();
+        }
+        let k = from * self.cardinality + to;
+        self.rows.last_mut().unwrap()[k] += 1;
+    }
+
+    /// Number of from -> to transitions at each step.
+    pub fn incidence(&self, from: usize, to: usize) -> Vec<usize> {
+        let k = from * self.cardinality + to;
+        return self.rows.iter().map(|row| row[k]).collect();
+    }
+
+    /// List of (from, to) transitions that occurred at least once, sorted by
+    /// origin and then by destination.
+    pub fn transitions(&self) -> Vec<(usize, usize)> {
+        let n = self.cardinality;
+        let mut data = vec![];
+        for k in 0..n * n {
+            if self.rows.iter().any(|row| row[k] > 0) {
+                data.push((k / n, k % n));
+            }
+        }
+        return data;
+    }
+
+    /// Render incidence as CSV with one column per transition that occurred
+    /// during the simulation.
+    ///
+    /// Columns are named as "S->E", using the compartment names in header,
+    /// which must be a comma separated list like EpiModel::CSV_HEADER.
+    pub fn render_csv(&self, header: &str) -> String {
+        let names: Vec<&str> = header.split(',').collect();
+        let name = |i: usize| names.get(i).map_or(i.to_string(), |s| s.to_string());
+        let transitions = self.transitions();
+        let head: Vec<String> = transitions
+            .iter()
+            .map(|&(from, to)| format!("{}->{}", name(from), name(to)))
+            .collect();
+
+        let mut data = head.join(",");
+        for row in self.rows.iter() {
+            let values: Vec<String> = transitions
+                .iter()
+                .map(|&(from, to)| row[from * self.cardinality + to].to_string())
+                .collect();
+            data.push('\n');
+            data.push_str(&values.join(","));
+        }
+        return data;
+    }
+}
@@ -0,0 +1,149 @@
+use super::tracker::Tracker;
+use crate::{prelude::EpiModel, sim::Population};
+use getset::CopyGetters;
+use log::error;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::io::{self, Write};
+
+/// Stream epicurves as newline-delimited JSON (NDJSON), one object per step.
+///
+/// Each object has a "step" field with the number of tracked steps and the
+/// number of agents in each compartment, named after the corresponding entry
+/// of the model's CSV_HEADER, e.g., {"step":1,"S":990,"I":10,"R":0,"D":0}.
+/// Models with more compartments than header entries, such as Staged, use
+/// the compartment index as the name of the extra compartments.
+///
+/// Like CsvSink, the first I/O error is logged, writing stops and the error
+/// is returned by finish().
+#[derive(Debug, CopyGetters)]
+pub struct JsonTracker<W: Write> {
+    writer: W,
+    #[getset(get_copy = "pub")]
+    n_rows: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonTracker<W> {
+    /// Create tracker that writes to the given writer.
+    pub fn new(writer: W) -> Self {
+        JsonTracker {
+            writer,
+            n_rows: 0,
+            error: None,
+        }
+    }
+
+    /// Flush writer and return it, or the first error that occurred while
+    /// tracking.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        self.writer.flush()?;
+        return Ok(self.writer);
+    }
+
+    fn write_row(&mut self, names: &[String], counts: &[usize]) -> io::Result<()> {
+        self.n_rows += 1;
+        let row = JsonRow {
+            step: self.n_rows,
+            names,
+            counts,
+        };
+        serde_json::to_writer(&mut self.writer, &row)?;
+        return self.writer.write_all(b"\n");
+    }
+}
+
+/// Name of each compartment of model M, in index order.
+pub fn compartment_names<M: EpiModel>() -> Vec<String> {
+    let header: Vec<&str> = M::CSV_HEADER.split(',').collect();
+    return (0..M::CARDINALITY)
+        .map(|i| match header.get(i) {
+            Some(name) => name.to_string(),
+            None => i.to_string(),
+        })
+        .collect();
+}
+
+/// A single NDJSON row. Serialized as a map to keep compartments in index
+/// order.
+struct JsonRow<'a> {
+    step: usize,
+    names: &'a [String],
+    counts: &'a [usize],
+}
+
+impl<'a> Serialize for JsonRow<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.counts.len() + 1))?;
+        map.serialize_entry("step", &self.step)?;
+        for (name, count) in self.names.iter().zip(self.counts) {
+            map.serialize_entry(name, count)?;
+        }
+        return map.end();
+    }
+}
+
+impl<P, W> Tracker<P> for JsonTracker<W>
+where
+    P: Population,
+    P::State: EpiModel,
+    W: Write,
+{
+    fn track(&mut self, population: &P) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut counts = vec![0; P::State::CARDINALITY];
+        population.each_agent(&mut |_, ag: &P::State| counts[ag.index()] += 1);
+        let names = compartment_names::<P::State>();
+        if let Err(err) = self.write_row(&names, &counts) {
+            error!("could not write JSON row: {}", err);
+            self.error = Some(err);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SEICHARm, SEIRm, Staged, SEICHAR, SEIR};
+    use serde_json::Value;
+
+    #[test]
+    fn json_tracker_names_compartments_by_header() {
+        let mut tracker = JsonTracker::new(vec![]);
+        let population = vec![
+            SEICHARm::Susceptible,
+            SEICHAR::Severe(()),
+            SEICHAR::Severe(()),
+            SEICHAR::Critical(()),
+        ];
+        tracker.track(&population);
+        tracker.track(&population[..1].to_vec());
+        let data = String::from_utf8(tracker.finish().unwrap()).unwrap();
+
+        let rows: Vec<Value> = data
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["step"], 1);
+        assert_eq!(rows[0]["H"], 2);
+        assert_eq!(rows[0]["C"], 1);
+        assert_eq!(rows[1]["S"], 1);
+        assert_eq!(rows[1]["H"], 0);
+        assert!(data.starts_with(r#"{"step":1,"S":1,"E":0,"I":0,"C":1,"H":2,"#));
+
+        let names = compartment_names::<Staged<SEIRm, 3>>();
+        assert_eq!(names, vec!["S", "E", "I", "R", "D", "5", "6"]);
+        let mut tracker = JsonTracker::new(vec![]);
+        tracker.track(&vec![Staged::<SEIRm, 3>::new(SEIR::Infectious(()))]);
+        assert_eq!(tracker.n_rows(), 1);
+    }
+}
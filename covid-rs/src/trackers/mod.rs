@@ -1,8 +1,16 @@
+mod age_stratified;
+mod csv_sink;
+mod cumulative;
 mod epi_tracker;
+mod flow_tracker;
+mod json_tracker;
+mod spatial;
 mod table_tracker;
 mod time_trackers;
 mod tracker;
 mod vaccine;
 pub use {
-    epi_tracker::*, table_tracker::*, table_tracker::*, time_trackers::*, tracker::*, vaccine::*,
+    age_stratified::*, csv_sink::*, cumulative::*, epi_tracker::*, flow_tracker::*,
+    json_tracker::*, spatial::*, table_tracker::*, table_tracker::*, time_trackers::*, tracker::*,
+    vaccine::*,
 };
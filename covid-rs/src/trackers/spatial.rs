@@ -0,0 +1,166 @@
+use super::tracker::Tracker;
+use crate::{
+    prelude::{EpiModel, Real},
+    sim::{HasPosition, Population},
+};
+use getset::{CopyGetters, Getters};
+use std::{fs, io, path::Path};
+
+/// A single snapshot of the population: the (x, y, compartment) triple of
+/// each agent, in population order.
+pub type SpatialFrame = Vec<(Real, Real, usize)>;
+
+/// Record the position and compartment of every agent at regular intervals.
+///
+/// Frames are taken after every `interval` tracked steps, so tracking n steps
+/// produces n / interval frames. Each frame stores one row per agent, which
+/// makes this tracker memory-heavy for large populations and it should only be
+/// used to produce animations of spatial spread.
+#[derive(Clone, Debug, Getters, CopyGetters)]
+pub struct SpatialSnapshotTracker {
+    #[getset(get_copy = "pub")]
+    interval: usize,
+    #[getset(get_copy = "pub")]
+    n_iter: usize,
+    #[getset(get = "pub")]
+    frames: Vec<SpatialFrame>,
+}
+
+impl SpatialSnapshotTracker {
+    /// Create tracker that records a frame every interval steps.
+    pub fn new(interval: usize) -> Self {
+        assert!(interval > 0, "interval must be positive");
+        SpatialSnapshotTracker {
+            interval,
+            n_iter: 0,
+            frames: vec![],
+        }
+    }
+
+    /// Step index of the i-th frame.
+    pub fn frame_step(&self, i: usize) -> usize {
+        (i + 1) * self.interval
+    }
+
+    /// Render the i-th frame as a CSV string with x, y and compartment columns.
+    pub fn render_frame_csv(&self, i: usize) -> Option<String> {
+        let mut data = String::from("x,y,compartment");
+        for (x, y, idx) in self.frames.get(i)? {
+            data.push_str(&format!("\n{},{},{}", x, y, idx));
+        }
+        return Some(data);
+    }
+
+    /// Render all frames as a single long CSV string, with a frame index and
+    /// the corresponding simulation step in the first columns.
+    pub fn render_csv(&self) -> String {
+        let mut data = String::from("frame,step,x,y,compartment");
+        for (i, frame) in self.frames.iter().enumerate() {
+            let step = self.frame_step(i);
+            for (x, y, idx) in frame {
+                data.push_str(&format!("\n{},{},{},{},{}", i, step, x, y, idx));
+            }
+        }
+        return data;
+    }
+
+    /// Write each frame as a separate CSV file named {prefix}-{frame}.csv in
+    /// the given directory. Frame numbers are zero-padded so files sort in
+    /// order.
+    pub fn write_frames(&self, dir: impl AsRef<Path>, prefix: &str) -> io::Result<()> {
+        let width = self.frames.len().max(1).to_string().len();
+        for i in 0..self.frames.len() {
+            let path = dir
+                .as_ref()
+                .join(format!("{}-{:0width$}.csv", prefix, i, width = width));
+            fs::write(path, self.render_frame_csv(i).unwrap())?;
+        }
+        return Ok(());
+    }
+}
+
+impl<P> Tracker<P> for SpatialSnapshotTracker
+where
+    P: Population,
+    P::State: EpiModel + HasPosition,
+{
+    fn track(&mut self, population: &P) {
+        self.n_iter += 1;
+        if !self.n_iter.is_multiple_of(self.interval) {
+            return;
+        }
+        let mut frame = Vec::with_capacity(population.count());
+        population.each_agent(&mut |_, ag: &P::State| {
+            let (x, y) = ag.position();
+            frame.push((x, y, ag.index()));
+        });
+        self.frames.push(frame);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::SIRm, sim::HasEpiModel};
+
+    #[derive(Debug, Clone, Default)]
+    struct SpatialAgent {
+        model: SIRm,
+        position: (Real, Real),
+    }
+
+    impl HasEpiModel for SpatialAgent {
+        type Model = SIRm;
+
+        fn epimodel(&self) -> &SIRm {
+            &self.model
+        }
+
+        fn epimodel_mut(&mut self) -> &mut SIRm {
+            &mut self.model
+        }
+    }
+
+    impl HasPosition for SpatialAgent {
+        fn position(&self) -> (Real, Real) {
+            self.position
+        }
+
+        fn set_position(&mut self, value: (Real, Real)) -> &mut Self {
+            self.position = value;
+            return self;
+        }
+    }
+
+    #[test]
+    fn spatial_snapshot_records_one_frame_per_interval() {
+        let mut pop: Vec<SpatialAgent> = vec![];
+        for i in 0..12 {
+            let mut ag = SpatialAgent::default();
+            ag.set_position(((i % 4) as Real, (i / 4) as Real));
+            pop.push(ag);
+        }
+        pop[5].set_epimodel(SIRm::Infectious(()));
+
+        let mut tracker = SpatialSnapshotTracker::new(3);
+        for _ in 0..10 {
+            tracker.track(&pop);
+        }
+        assert_eq!(tracker.frames().len(), 10 / 3);
+        assert!(tracker.frames().iter().all(|f| f.len() == pop.count()));
+        assert_eq!(tracker.frame_step(2), 9);
+
+        let frame = tracker.render_frame_csv(0).unwrap();
+        assert_eq!(frame.lines().count(), pop.count() + 1);
+        assert!(frame.contains("\n1,1,1\n"));
+        assert!(tracker.render_frame_csv(3).is_none());
+
+        let csv = tracker.render_csv();
+        assert_eq!(csv.lines().count(), 3 * pop.count() + 1);
+        assert!(csv.lines().last().unwrap().starts_with("2,9,"));
+    }
+}
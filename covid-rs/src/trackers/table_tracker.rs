@@ -1,12 +1,52 @@
 use super::tracker::Tracker;
 use crate::{prelude::EpiModel, sim::Population};
 use getset::CopyGetters;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Debug, Display},
     iter,
     ops::Add,
 };
 
+/// Formatting options used to render tables as CSV.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field separator.
+    pub separator: char,
+    /// Number of decimal places used to render floats. None uses the default
+    /// Display formatting. Integers are not affected.
+    pub float_precision: Option<usize>,
+    /// If false, omit the header line.
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            separator: ',',
+            float_precision: None,
+            header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Default options with the given separator.
+    pub fn with_separator(separator: char) -> Self {
+        CsvOptions {
+            separator,
+            ..Default::default()
+        }
+    }
+
+    fn write_value<T: Display>(&self, data: &mut String, value: &T) {
+        match self.float_precision {
+            Some(p) => data.push_str(&format!("{:.*}", p, value)),
+            None => data.push_str(&format!("{}", value)),
+        }
+    }
+}
+
 /// The table tracker stores a table of values that grow row by row typically
 /// storing the epidemiological state of the population. The state must have a
 /// uniform type and usually corresponds to  counts of the number of agents in
@@ -14,7 +54,7 @@ use std::{
 ///
 /// The table can also grow column-wise. Empty values are stored as zeros
 /// coerced from the value `zero = T::from(0_u8)`.
-#[derive(Clone, Debug, Default, CopyGetters)]
+#[derive(Clone, Debug, Default, CopyGetters, Serialize, Deserialize)]
 pub struct TableTracker<T> {
     #[getset(get_copy = "pub")]
     nrows: usize,
@@ -51,14 +91,13 @@ impl<T: Copy> TableTracker<T> {
         T: From<u8>,
     {
         if self.nrows != other.nrows {
-            println!("{}, {}", self.nrows, other.nrows);
             return None;
         }
 
         let mut out = TableTracker {
             ncols: self.ncols + other.ncols,
             nrows: self.nrows,
-            buffer: vec![0_u8.into(); self.buffer.len() + other.buffer.len()],
+            buffer: Vec::with_capacity(self.buffer.len() + other.buffer.len()),
         };
 
         for i in 0..out.nrows {
@@ -176,7 +215,7 @@ impl<T: Copy> TableTracker<T> {
     where
         T: From<u8>,
     {
-        if let Some(row) = self.row(self.nrows - 1) {
+        if let Some(row) = self.nrows.checked_sub(1).and_then(|i| self.row(i)) {
             return row;
         }
         return vec![0_u8.into(); self.ncols];
@@ -187,15 +226,29 @@ impl<T: Copy> TableTracker<T> {
     where
         T: Display,
     {
-        let mut data = head.to_string();
+        self.render_csv_with(head, &CsvOptions::with_separator(sep))
+    }
+
+    /// Render epicurves as CSV data using the given formatting options.
+    pub fn render_csv_with(&self, head: &str, opts: &CsvOptions) -> String
+    where
+        T: Display,
+    {
+        let mut data = if opts.header {
+            head.to_string()
+        } else {
+            String::new()
+        };
 
-        for i in 0..self.nrows - 1 {
-            data.push('\n');
-            data.push_str(&format!("{}", self.buffer[self._idx(i, 0)]));
+        for i in 0..self.nrows {
+            if opts.header || i > 0 {
+                data.push('\n');
+            }
+            opts.write_value(&mut data, &self.buffer[self._idx(i, 0)]);
             for j in 1..self.ncols {
                 let k = self._idx(i, j);
-                data.push(sep);
-                data.push_str(&format!("{}", self.buffer[k]));
+                data.push(opts.separator);
+                opts.write_value(&mut data, &self.buffer[k]);
             }
         }
         return data;
@@ -238,3 +291,81 @@ where
         }
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Real;
+
+    #[test]
+    fn render_csv_with_float_precision() {
+        let mut table = TableTracker::<Real>::new(0);
+        table.step();
+        table.step();
+        table.add_column(vec![0.123456, 1.0].into_iter(), false);
+        table.add_column(vec![2.5, 0.0].into_iter(), false);
+
+        let opts = CsvOptions {
+            separator: '\t',
+            float_precision: Some(2),
+            header: true,
+        };
+        assert_eq!(
+            table.render_csv_with("a\tb", &opts),
+            "a\tb\n0.12\t2.50\n1.00\t0.00"
+        );
+        assert_eq!(table.render_csv("a,b", ','), "a,b\n0.123456,2.5\n1,0");
+
+        let opts = CsvOptions {
+            header: false,
+            ..opts
+        };
+        assert_eq!(
+            table.render_csv_with("a\tb", &opts),
+            "0.12\t2.50\n1.00\t0.00"
+        );
+    }
+
+    #[test]
+    fn render_csv_includes_all_rows() {
+        let mut table = TableTracker::<usize>::new(2);
+        assert_eq!(table.render_csv("a,b", ','), "a,b");
+        assert_eq!(table.tip(), vec![0, 0]);
+
+        for i in 0..3 {
+            table.step();
+            table.incr(i % 2);
+        }
+        let csv = table.render_csv("a,b", ',');
+        assert_eq!(csv.lines().count(), 1 + table.nrows());
+        assert_eq!(csv.lines().last(), Some("1,0"));
+    }
+
+    #[test]
+    fn merge_keeps_columns_of_both_tables() {
+        let mut left = TableTracker::<usize>::new(0);
+        let mut right = TableTracker::<usize>::new(0);
+        for _ in 0..2 {
+            left.step();
+            right.step();
+        }
+        for j in 0..3 {
+            left.add_column(vec![j, 10 + j].into_iter(), false);
+        }
+        for j in 3..5 {
+            right.add_column(vec![j, 10 + j].into_iter(), false);
+        }
+
+        let merged = left.merge(&right).unwrap();
+        assert_eq!(merged.ncols(), 5);
+        assert_eq!(merged.row(0), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(merged.row(1), Some(vec![10, 11, 12, 13, 14]));
+
+        right.step();
+        assert!(left.merge(&right).is_none());
+    }
+}
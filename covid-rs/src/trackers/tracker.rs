@@ -1,4 +1,8 @@
-use crate::{prelude::Real, utils::Sampling};
+use crate::{
+    prelude::Real,
+    utils::{EwmaAcc, Histogram, Sampling},
+};
+use std::collections::VecDeque;
 pub type DynTracker<T> = Box<dyn Tracker<T>>;
 pub type TrackerList<T> = Vec<(usize, DynTracker<T>)>;
 
@@ -141,7 +145,7 @@ $(impl Tracker<Real> for $ty
  };
 }
 
-sampling_tracker!(Vec<Real>);
+sampling_tracker!(Vec<Real>, Histogram, EwmaAcc);
 
 /////////////////////////////////////////////////////////////////////////////
 // Wrappers
@@ -175,3 +179,77 @@ where
         (&mut self.0)(value)
     }
 }
+
+/// Moving average of the last window tracked values.
+///
+/// Each tracked value is pushed to a ring buffer and the mean of the buffer is
+/// forwarded to the inner tracker and stored in the smoothed series. During
+/// the warm-up period, before window values are accumulated, the mean is
+/// computed from the values seen so far, i.e., the first element of the series
+/// is the first tracked value itself.
+#[derive(Clone, Debug)]
+pub struct SmoothingTracker<T> {
+    inner: T,
+    window: usize,
+    buffer: VecDeque<Real>,
+    series: Vec<Real>,
+}
+
+impl<T> SmoothingTracker<T> {
+    /// Wrap inner tracker with a moving average of the given window size.
+    pub fn new(inner: T, window: usize) -> Self {
+        assert!(window > 0, "window must be positive");
+        SmoothingTracker {
+            inner,
+            window,
+            buffer: VecDeque::with_capacity(window),
+            series: vec![],
+        }
+    }
+
+    /// Smoothed values computed so far.
+    pub fn smoothed_series(&self) -> &[Real] {
+        &self.series
+    }
+
+    /// Reference to the wrapped tracker.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Return the wrapped tracker.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Tracker<Real>> Tracker<Real> for SmoothingTracker<T> {
+    fn track(&mut self, value: &Real) {
+        if self.buffer.len() == self.window {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(*value);
+        let mean = self.buffer.iter().sum::<Real>() / self.buffer.len() as Real;
+        self.series.push(mean);
+        self.inner.track(&mean);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoothing_tracker_uses_partial_averages_during_warm_up() {
+        let mut tracker = SmoothingTracker::new(Vec::<Real>::new(), 3);
+        for x in [3.0, 6.0, 9.0, 0.0, 3.0] {
+            tracker.track(&x);
+        }
+        assert_eq!(tracker.smoothed_series(), &[3.0, 4.5, 6.0, 5.0, 4.0]);
+        assert_eq!(tracker.inner(), &vec![3.0, 4.5, 6.0, 5.0, 4.0]);
+    }
+}
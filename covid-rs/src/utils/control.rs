@@ -0,0 +1,106 @@
+use std::ops::{Add, Mul, Sub, Div};
+
+use getset::{CopyGetters, Getters, Setters};
+
+/// A simple implementation of a PID (Proportional, Integral, Derivative)
+/// controller.
+///
+/// This controller is used to callibrate parameters during the simulation
+/// initialization. The feedback is kp * e + ki * ∫e dt + kd * de/dt, where e
+/// is the error passed to feedback().
+#[derive(Debug, Clone, Copy, Default, Getters, Setters, CopyGetters)]
+#[getset(get_copy = "pub", set = "pub")]
+pub struct PID<N: Copy> {
+    kp: N,
+    ki: N,
+    kd: N,
+    error: N,
+    acc: N,
+}
+
+impl<
+        N: Add<Output = N>
+            + Sub<Output = N>
+            + Mul<Output = N>
+            + Div<Output = N>
+            + Default
+            + Clone
+            + Copy,
+    > PID<N>
+{
+    /// Create a new controller from PID coefficients.
+    pub fn new(kp: N, ki: N, kd: N) -> Self {
+        let zero: N = Default::default();
+        PID {
+            kp,
+            ki,
+            kd,
+            error: zero,
+            acc: zero,
+        }
+    }
+
+    /// Add measurement and return the corresponding feedback. This function
+    /// updates the internal state tracking the error term and the cumulative
+    /// error term.
+    pub fn feedback(&mut self, error: N, dt: N) -> N {
+        let diff = (error - self.error) / dt;
+        self.error = error;
+        let acc = self.acc + error * dt;
+        self.acc = acc;
+
+        return self.kp * error + self.kd * diff + self.ki * acc;
+    }
+
+    /// Reset the error and cumulative error terms, keeping the gains.
+    pub fn reset(&mut self) -> &mut Self {
+        self.error = Default::default();
+        self.acc = Default::default();
+        return self;
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Real;
+    use assert_approx_eq::assert_approx_eq;
+
+    /// Drive the first order system dx/dt = u - x to the setpoint and return
+    /// the final state.
+    fn control_first_order(pid: &mut PID<Real>, setpoint: Real, n: usize) -> Real {
+        let dt = 0.1;
+        let mut x = 0.0;
+        for _ in 0..n {
+            let u = pid.feedback(setpoint - x, dt);
+            x += (u - x) * dt;
+        }
+        return x;
+    }
+
+    #[test]
+    fn pid_converges_first_order_system_to_setpoint() {
+        let mut pid = PID::new(2.0, 1.0, 0.0);
+        assert_approx_eq!(control_first_order(&mut pid, 1.0, 500), 1.0, 1e-3);
+        assert_approx_eq!(pid.error(), 0.0, 1e-3);
+        assert_approx_eq!(pid.acc(), 1.0, 1e-3);
+
+        // Proportional control alone leaves a steady state error
+        let mut pid = PID::new(2.0, 0.0, 0.0);
+        assert_approx_eq!(control_first_order(&mut pid, 1.0, 500), 2.0 / 3.0, 1e-3);
+    }
+
+    #[test]
+    fn pid_reset_keeps_gains() {
+        let mut pid = PID::new(1.0, 0.5, 0.1);
+        pid.feedback(2.0, 1.0);
+        pid.reset();
+        assert_eq!((pid.error(), pid.acc()), (0.0, 0.0));
+        assert_eq!((pid.kp(), pid.ki(), pid.kd()), (1.0, 0.5, 0.1));
+        assert_eq!(pid.feedback(1.0, 1.0), 1.0 + 0.5 + 0.1);
+    }
+}
@@ -0,0 +1,303 @@
+//! Analysis of epidemic curves, usually extracted from a simulation as a
+//! sequence of daily values.
+use crate::{
+    prelude::{Real, NAN},
+    utils::standard_normal,
+};
+use rand::Rng;
+
+/// Estimate the effective reproduction number from an incidence curve using
+/// the renewal equation.
+///
+/// The generation interval is given as a (possibly unnormalized) distribution
+/// in which generation_interval[0] is the weight of infections occurring one
+/// day after the infector was infected. The estimate for day t is the ratio
+/// between incidence at t and the incidence convolved with the generation
+/// interval. Near the start of the curve, the generation interval is
+/// truncated to the first t days and the remaining weights are renormalized.
+///
+/// Days with no past infectious pressure (including the first day) are NaN.
+pub fn renewal_rt(incidence: &[Real], generation_interval: &[Real]) -> Vec<Real> {
+    let mut rt = Vec::with_capacity(incidence.len());
+    for t in 0..incidence.len() {
+        let window = t.min(generation_interval.len());
+        let mut pressure = 0.0;
+        let mut norm = 0.0;
+        for s in 1..=window {
+            let w = generation_interval[s - 1];
+            pressure += w * incidence[t - s];
+            norm += w;
+        }
+        if pressure > 0.0 {
+            rt.push(incidence[t] * norm / pressure);
+        } else {
+            rt.push(NAN);
+        }
+    }
+    return rt;
+}
+
+/// Incidence-weighted mean day of infection, i.e., the "center of mass" of
+/// the epidemic curve. Days are counted from zero.
+///
+/// Return NaN if there are no infections.
+pub fn mean_infection_time(incidence: &[Real]) -> Real {
+    let total: Real = incidence.iter().sum();
+    if total <= 0.0 {
+        return NAN;
+    }
+    let moment: Real = incidence
+        .iter()
+        .enumerate()
+        .map(|(t, x)| t as Real * x)
+        .sum();
+    return moment / total;
+}
+
+/// Area under a curve sampled daily, computed with the trapezoidal rule.
+///
+/// Applied to the prevalence of some compartment, this is the total
+/// person-time spent in that compartment. Curves with less than two points
+/// have zero area.
+pub fn auc(xs: &[Real]) -> Real {
+    return xs.windows(2).map(|w| 0.5 * (w[0] + w[1])).sum();
+}
+
+/// Simulate the observed curve of reported cases from the true daily
+/// incidence.
+///
+/// Each case is reported with probability reporting_fraction after a random
+/// delay drawn from a log-normal distribution with the given mean and
+/// coefficient of variation, rounded to whole days. This is equivalent to
+/// convolving the incidence with the delay distribution and applying binomial
+/// under-reporting. Cases reported after the last day are lost, as in
+/// real-time data.
+pub fn observe_with_delay<R: Rng>(
+    incidence: &[usize],
+    mean_delay: Real,
+    cv: Real,
+    reporting_fraction: Real,
+    rng: &mut R,
+) -> Vec<Real> {
+    let sigma = (1.0 + cv * cv).ln().sqrt();
+    let mu = mean_delay.ln() - 0.5 * sigma * sigma;
+    let mut observed = vec![0.0; incidence.len()];
+
+    for (t, &cases) in incidence.iter().enumerate() {
+        for _ in 0..cases {
+            if !rng.gen_bool(reporting_fraction) {
+                continue;
+            }
+            let delay = if mean_delay > 0.0 {
+                let z = standard_normal(rng);
+                (mu + sigma * z).exp().round() as usize
+            } else {
+                0
+            };
+            if let Some(x) = observed.get_mut(t + delay) {
+                *x += 1.0;
+            }
+        }
+    }
+    return observed;
+}
+
+/// Estimate the true daily incidence from a curve of reported cases.
+///
+/// This inverts the reporting process of observe_with_delay(): cases are
+/// reported with probability reporting_fraction after a delay of d days with
+/// probability delay_dist[d] (the distribution does not need to be
+/// normalized). The estimate uses Richardson-Lucy deconvolution, which keeps
+/// the result non-negative and corrects for cases that were not reported yet
+/// at the end of the curve. Estimates for the last few days are based on few
+/// reports and should be taken with a grain of salt.
+pub fn deconvolve_reporting(
+    observed: &[Real],
+    delay_dist: &[Real],
+    reporting_fraction: Real,
+) -> Vec<Real> {
+    let n_iter = 200;
+    let n = observed.len();
+    let norm: Real = delay_dist.iter().sum();
+    let kernel: Vec<Real> = delay_dist
+        .iter()
+        .map(|p| p * reporting_fraction / norm)
+        .collect();
+    if n == 0 || !(norm > 0.0 && reporting_fraction > 0.0) {
+        return vec![NAN; n];
+    }
+
+    // Fraction of the infections of each day that are reported before the end
+    // of the curve.
+    let visible: Vec<Real> = (0..n)
+        .map(|t| kernel.iter().take(n - t).sum())
+        .collect();
+    let total: Real = observed.iter().sum();
+    let mut estimate = vec![total / visible.iter().sum::<Real>(); n];
+    let mut predicted = vec![0.0; n];
+
+    for _ in 0..n_iter {
+        for t in 0..n {
+            predicted[t] = (0..kernel.len().min(t + 1))
+                .map(|d| kernel[d] * estimate[t - d])
+                .sum();
+        }
+        for t in 0..n {
+            if visible[t] <= 0.0 {
+                continue;
+            }
+            let mut ratio = 0.0;
+            for (d, k) in kernel.iter().enumerate().take(n - t) {
+                if predicted[t + d] > 0.0 {
+                    ratio += k * observed[t + d] / predicted[t + d];
+                }
+            }
+            estimate[t] *= ratio / visible[t];
+        }
+    }
+    return estimate;
+}
+
+/// Shift curves so that their peaks (first maximum) sit at a common index,
+/// padding with zeros at the start and at the end so all curves have the
+/// same length.
+pub fn align_by_peak(curves: &[Vec<Real>]) -> Vec<Vec<Real>> {
+    let peaks: Vec<usize> = curves
+        .iter()
+        .map(|c| (0..c.len()).fold(0, |k, i| if c[i] > c[k] { i } else { k }))
+        .collect();
+    let target = peaks.iter().cloned().max().unwrap_or(0);
+
+    let mut aligned: Vec<Vec<Real>> = curves
+        .iter()
+        .zip(peaks.iter())
+        .map(|(c, &peak)| {
+            let mut new = vec![0.0; target - peak];
+            new.extend_from_slice(c);
+            new
+        })
+        .collect();
+    let n = aligned.iter().map(|c| c.len()).max().unwrap_or(0);
+    for c in aligned.iter_mut() {
+        c.resize(n, 0.0);
+    }
+    return aligned;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn renewal_rt_recovers_constant_rt() {
+        let gi = [0.1, 0.3, 0.3, 0.2, 0.1];
+        let r = 1.3;
+        let mut incidence = vec![10.0];
+        for t in 1..40 {
+            let mut acc = 0.0;
+            for s in 1..=t.min(gi.len()) {
+                acc += gi[s - 1] * incidence[t - s];
+            }
+            incidence.push(r * acc);
+        }
+
+        let rt = renewal_rt(&incidence, &gi);
+        assert_eq!(rt.len(), incidence.len());
+        assert!(rt[0].is_nan());
+        for &x in &rt[gi.len()..] {
+            assert_approx_eq!(x, r, 1e-9);
+        }
+
+        // Early values use a truncated and renormalized kernel
+        for t in 1..gi.len() {
+            let norm: Real = gi[..t].iter().sum();
+            assert!(rt[t].is_finite());
+            assert_approx_eq!(rt[t], r * norm, 1e-9);
+        }
+
+        // Unnormalized distributions give the same result
+        let scaled: Vec<Real> = gi.iter().map(|w| 2.0 * w).collect();
+        let rt = renewal_rt(&incidence, &scaled);
+        assert!(rt[1..].iter().all(|x| x.is_finite()));
+        assert_approx_eq!(rt[gi.len()], r, 1e-9);
+        assert!(renewal_rt(&[0.0; 10], &gi).iter().all(|x| x.is_nan()));
+    }
+
+    #[test]
+    fn mean_infection_time_of_symmetric_and_skewed_curves() {
+        let symmetric = [0.0, 1.0, 4.0, 9.0, 4.0, 1.0, 0.0];
+        assert_approx_eq!(mean_infection_time(&symmetric), 3.0);
+
+        let skewed = [0.0, 4.0, 9.0, 6.0, 4.0, 3.0, 2.0, 1.0];
+        assert!(mean_infection_time(&skewed) > 2.0);
+        assert!(mean_infection_time(&[0.0; 5]).is_nan());
+    }
+
+    #[test]
+    fn auc_of_constant_and_triangular_curves() {
+        assert_approx_eq!(auc(&[10.0; 6]), 50.0);
+        assert_approx_eq!(auc(&[0.0, 1.0, 2.0, 1.0, 0.0]), 4.0);
+        assert_eq!(auc(&[1.0]), 0.0);
+    }
+
+    #[test]
+    fn observe_with_delay_without_delay_or_noise() {
+        let incidence = [0, 5, 10, 3];
+        let mut rng = crate::utils::seeded_rng(42u64);
+        let observed = observe_with_delay(&incidence, 0.0, 0.0, 1.0, &mut rng);
+        assert_eq!(observed, vec![0.0, 5.0, 10.0, 3.0]);
+
+        let observed = observe_with_delay(&incidence, 1.0, 0.0, 1.0, &mut rng);
+        assert_eq!(observed, vec![0.0, 0.0, 5.0, 10.0]);
+    }
+
+    #[test]
+    fn deconvolve_reporting_recovers_true_incidence() {
+        let delay = [0.1, 0.2, 0.4, 0.2, 0.1];
+        let fraction = 0.4;
+        let incidence: Vec<Real> = (0..60)
+            .map(|t| 100.0 * (-((t as Real - 25.0) / 8.0).powi(2)).exp())
+            .collect();
+        let observed: Vec<Real> = (0..60)
+            .map(|t| {
+                let acc: Real = (0..delay.len().min(t + 1))
+                    .map(|d| delay[d] * incidence[t - d])
+                    .sum();
+                fraction * acc
+            })
+            .collect();
+
+        let estimate = deconvolve_reporting(&observed, &delay, fraction);
+        assert_eq!(estimate.len(), incidence.len());
+        for t in 0..55 {
+            assert!(
+                (estimate[t] - incidence[t]).abs() < 5.0,
+                "day {}: {} vs {}",
+                t,
+                estimate[t],
+                incidence[t]
+            );
+        }
+        let total: Real = estimate.iter().sum();
+        assert_approx_eq!(total, incidence.iter().sum::<Real>(), 1.0);
+    }
+
+    #[test]
+    fn align_by_peak_of_offset_curves() {
+        let curve: Vec<Real> = (0..30)
+            .map(|t| (-((t as Real - 12.0) / 4.0).powi(2)).exp())
+            .collect();
+        let mut offset = vec![0.0; 10];
+        offset.extend_from_slice(&curve[..20]);
+
+        let aligned = align_by_peak(&[offset, curve.clone()]);
+        assert_eq!(aligned[0].len(), aligned[1].len());
+        assert_eq!(aligned[0][..30], aligned[1][..30]);
+        assert_eq!(aligned[0][22], 1.0);
+    }
+}
@@ -44,3 +44,45 @@ pub fn default_rng() -> SmallRng {
 pub fn seeded_rng(n: impl Into<u64>) -> SmallRng {
     SmallRng::seed_from_u64(n.into())
 }
+
+/// Sample from a Poisson distribution using Knuth's algorithm. This is only
+/// efficient for small values of lambda.
+pub fn poisson<R: Rng>(lambda: Real, rng: &mut R) -> usize {
+    let threshold = (-lambda).exp();
+    let mut k = 0;
+    let mut p: Real = rng.gen();
+    while p > threshold {
+        k += 1;
+        p *= rng.gen::<Real>();
+    }
+    return k;
+}
+
+/// Sample from a Gamma distribution with given shape and scale using the
+/// Marsaglia-Tsang method.
+pub fn gamma<R: Rng>(shape: Real, scale: Real, rng: &mut R) -> Real {
+    if shape < 1.0 {
+        let u: Real = rng.gen();
+        return gamma(shape + 1.0, scale, rng) * u.powf(1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: Real = rng.gen();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v * scale;
+        }
+    }
+}
+
+/// Sample from a standard normal distribution using the Box-Muller transform.
+pub fn standard_normal<R: Rng>(rng: &mut R) -> Real {
+    let u: Real = 1.0 - rng.gen::<Real>();
+    let v: Real = rng.gen();
+    return (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+}
@@ -1,6 +1,8 @@
+pub mod control;
+pub mod curve;
 mod functions;
-mod pid;
 mod ma;
 mod stats;
 mod ascii_plot;
-pub use self::{functions::*, pid::PID, stats::*, ma::*, ascii_plot::*};
+mod rng;
+pub use self::{functions::*, control::PID, stats::*, ma::*, ascii_plot::*, rng::*};
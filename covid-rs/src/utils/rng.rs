@@ -0,0 +1,122 @@
+use rand::{Error, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// A xoshiro256++ random number generator with a serializable state.
+///
+/// This is the algorithm behind SmallRng on 64-bit platforms and both
+/// generators produce the same stream for the same seed. SmallRng does not
+/// expose its state, however, so this type is used by simulations that must be
+/// saved and restored. See Simulation::checkpoint().
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimRng {
+    s: [u64; 4],
+}
+
+impl SeedableRng for SimRng {
+    type Seed = [u8; 32];
+
+    /// Create generator from seed. The all-zeros seed, which is invalid for
+    /// xoshiro, is mapped to a fixed non-zero state, as in SmallRng.
+    fn from_seed(seed: [u8; 32]) -> Self {
+        if seed.iter().all(|&x| x == 0) {
+            return Self::from_seed(splitmix_seed(0));
+        }
+        let mut s = [0; 4];
+        for (x, chunk) in s.iter_mut().zip(seed.chunks_exact(8)) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            *x = u64::from_le_bytes(bytes);
+        }
+        return SimRng { s };
+    }
+}
+
+/// Expand a u64 into a xoshiro seed using SplitMix64.
+fn splitmix_seed(mut state: u64) -> [u8; 32] {
+    const PHI: u64 = 0x9e3779b97f4a7c15;
+    let mut seed = [0; 32];
+    for chunk in seed.chunks_mut(8) {
+        state = state.wrapping_add(PHI);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z = z ^ (z >> 31);
+        chunk.copy_from_slice(&z.to_le_bytes());
+    }
+    return seed;
+}
+
+impl RngCore for SimRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        // The lowest bits have some linear dependencies, so we use the upper
+        // bits instead.
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        return result;
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut chunks = dest.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+        let rest = chunks.into_remainder();
+        let n = rest.len();
+        if n > 4 {
+            rest.copy_from_slice(&self.next_u64().to_le_bytes()[..n]);
+        } else if n > 0 {
+            rest.copy_from_slice(&self.next_u32().to_le_bytes()[..n]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        return Ok(());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Tests
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::seeded_rng;
+    use rand::{rngs::SmallRng, Rng};
+
+    #[test]
+    fn sim_rng_matches_small_rng_and_round_trips() {
+        let mut rng = SimRng::seed_from_u64(42);
+        let mut small = seeded_rng(42u64);
+        for _ in 0..100 {
+            assert_eq!(rng.gen::<u64>(), small.gen::<u64>());
+            assert_eq!(rng.gen_range(0..7), small.gen_range(0..7));
+        }
+        let (mut a, mut b) = ([0u8; 13], [0u8; 13]);
+        rng.fill_bytes(&mut a);
+        small.fill_bytes(&mut b);
+        assert_eq!(a, b);
+
+        let data = serde_json::to_string(&rng).unwrap();
+        let mut restored: SimRng = serde_json::from_str(&data).unwrap();
+        assert_eq!(restored.gen::<f64>(), rng.gen::<f64>());
+
+        let mut zero = SimRng::from_seed([0; 32]);
+        let mut small = SmallRng::from_seed([0; 32]);
+        assert_eq!(zero.gen::<u64>(), small.gen::<u64>());
+    }
+}
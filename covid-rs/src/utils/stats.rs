@@ -63,7 +63,9 @@ pub trait Sampling {
     /// Return the skewness (or normalized third moment)
     fn skew(&self) -> Real;
 
-    /// Return the kurtosis (or normalized fourth moment)
+    /// Return the kurtosis (or normalized fourth moment). This is Pearson's
+    /// kurtosis, not the excess kurtosis, i.e., it is 3.0 for a normal
+    /// distribution.
     fn kurt(&self) -> Real;
 
     /// Return the standard deviation
@@ -81,6 +83,11 @@ pub trait Sampling {
         NAN
     }
 
+    /// Return the median, or NaN if the accumulator does not track it.
+    fn median(&self) -> Real {
+        NAN
+    }
+
     /// Return a simple stats struct holding all statistics as fields.
     fn stats(&self) -> Stats {
         Stats {
@@ -90,6 +97,7 @@ pub trait Sampling {
             kurt: self.kurt(),
             min: self.min(),
             max: self.max(),
+            median: self.median(),
             size: self.sample_size(),
         }
     }
@@ -123,7 +131,10 @@ impl Sampling for Vec<Real> {
         return self.iter().fold(-INF, |acc, x| acc.max(*x));
     }
     fn stats(&self) -> Stats {
-        MinMaxAcc::<KurtAcc>::from_data(self.iter().cloned()).stats()
+        Stats {
+            median: self.median(),
+            ..MinMaxAcc::<KurtAcc>::from_data(self.iter().cloned()).stats()
+        }
     }
     fn last_sample(&self) -> Real {
         match self.last() {
@@ -131,6 +142,20 @@ impl Sampling for Vec<Real> {
             _ => NAN,
         }
     }
+    fn median(&self) -> Real {
+        // NaN propagates, like in mean()
+        if self.iter().any(|x| x.is_nan()) {
+            return NAN;
+        }
+        let mut data = self.clone();
+        data.sort_by(|a, b| a.total_cmp(b));
+        let n = data.len();
+        return match n {
+            0 => NAN,
+            _ if n % 2 == 1 => data[n / 2],
+            _ => (data[n / 2 - 1] + data[n / 2]) / 2.0,
+        };
+    }
 }
 
 impl Sampling for Vec<(Real, usize)> {
@@ -147,7 +172,7 @@ impl Sampling for Vec<(Real, usize)> {
         self.iter().map(|(x, n)| x * (*n as Real)).sum()
     }
     fn var(&self) -> Real {
-        StdAcc::from_counts(self.iter().cloned()).skew()
+        StdAcc::from_counts(self.iter().cloned()).var()
     }
     fn skew(&self) -> Real {
         KurtAcc::from_counts(self.iter().cloned()).skew()
@@ -175,6 +200,14 @@ impl Sampling for Vec<(Real, usize)> {
     }
 }
 
+/// Accumulators that can be combined, e.g., to reduce partial results computed
+/// from different chunks of a dataset in parallel.
+pub trait Merge {
+    /// Merge two accumulators. The result is equivalent to an accumulator
+    /// that received all observations of self followed by those of other.
+    fn merge(&self, other: &Self) -> Self;
+}
+
 /// Accumulate totals and mean. Other values are estimated from a normalized
 /// Gaussian distribution
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
@@ -210,40 +243,12 @@ impl Sampling for MeanAcc {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Default)]
-pub struct StdAcc {
-    m0: Real,
-    m1: Real,
-    m2: Real,
-}
-
-impl Sampling for StdAcc {
-    fn add_many(&mut self, x: Real, n: usize) {
-        let n = n as Real;
-        self.m0 += n;
-        self.m1 += x * n;
-        self.m2 += x * x * n;
-    }
-
-    fn sample_size(&self) -> usize {
-        self.m0 as usize
-    }
-
-    fn total(&self) -> Real {
-        self.m1
-    }
-
-    fn var(&self) -> Real {
-        let mean = self.m1 / self.m0;
-        self.m2 / self.m0 - mean * mean
-    }
-
-    fn skew(&self) -> Real {
-        0.0
-    }
-
-    fn kurt(&self) -> Real {
-        todo!()
+impl Merge for MeanAcc {
+    fn merge(&self, other: &Self) -> Self {
+        MeanAcc {
+            m0: self.m0 + other.m0,
+            m1: self.m1 + other.m1,
+        }
     }
 }
 
@@ -276,19 +281,45 @@ impl Sampling for KurtAcc {
         self.m2 / self.m0 - mean * mean
     }
     fn skew(&self) -> Real {
-        let mu = self.mean();
-        let std = self.std();
-        return (self.m3 / self.m0 - 3.0 * mu * std * std - mu * mu * mu) / (std * std * std);
+        skew_from_moments([self.m0, self.m1, self.m2, self.m3])
     }
     fn kurt(&self) -> Real {
-        let n = self.m0;
-        let (m, b, c, d) = (self.m1 / n, self.m2 / n, self.m3 / n, self.m4 / n);
-        let m2 = m * m;
-        let m4 = m2 * m2;
-        return (d - 4. * m * c + 6. * m2 * b - 3. * m4) / ((b - m2) * (b - m2));
+        kurt_from_moments([self.m0, self.m1, self.m2, self.m3, self.m4])
+    }
+}
+
+impl Merge for KurtAcc {
+    fn merge(&self, other: &Self) -> Self {
+        KurtAcc {
+            m0: self.m0 + other.m0,
+            m1: self.m1 + other.m1,
+            m2: self.m2 + other.m2,
+            m3: self.m3 + other.m3,
+            m4: self.m4 + other.m4,
+        }
     }
 }
 
+/// Accumulator for the variance and higher moments. It is the same as KurtAcc,
+/// which tracks raw moments up to the fourth order.
+pub type StdAcc = KurtAcc;
+
+/// Skewness from the raw moments [n, sum(x), sum(x^2), sum(x^3)].
+fn skew_from_moments([m0, m1, m2, m3]: [Real; 4]) -> Real {
+    let mu = m1 / m0;
+    let var = m2 / m0 - mu * mu;
+    let std = var.sqrt();
+    return (m3 / m0 - 3.0 * mu * var - mu * mu * mu) / (std * std * std);
+}
+
+/// Pearson (non-excess) kurtosis from the raw moments [n, sum(x), ..., sum(x^4)].
+fn kurt_from_moments([n, m1, m2, m3, m4]: [Real; 5]) -> Real {
+    let (m, b, c, d) = (m1 / n, m2 / n, m3 / n, m4 / n);
+    let m2 = m * m;
+    let m4 = m2 * m2;
+    return (d - 4. * m * c + 6. * m2 * b - 3. * m4) / ((b - m2) * (b - m2));
+}
+
 /// A simple accumulator of point statistics.
 ///
 /// It stores the latest value computed from number of samples, moments
@@ -325,20 +356,22 @@ impl<S: Sampling + Default> MinMaxAcc<S> {
         }
         return acc;
     }
+}
 
-    // /// Merge two accumulators
-    // pub fn merge(&self, other: &Self) -> Self {
-    //     Accumulator {
-    //         n: self.n + other.n,
-    //         m1: self.m1 + other.m1,
-    //         m2: self.m2 + other.m2,
-    //         m3: self.m3 + other.m3,
-    //         m4: self.m4 + other.m4,
-    //         min: self.min + other.min,
-    //         max: self.max + other.max,
-    //         last: self.last + other.last,
-    //     }
-    // }
+impl<S: Merge> Merge for MinMaxAcc<S> {
+    /// The last sample is taken from other, unless it is empty.
+    fn merge(&self, other: &Self) -> Self {
+        MinMaxAcc {
+            acc: self.acc.merge(&other.acc),
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            last: if other.last.is_nan() {
+                self.last
+            } else {
+                other.last
+            },
+        }
+    }
 }
 
 impl<S: Sampling> Sampling for MinMaxAcc<S> {
@@ -403,6 +436,7 @@ pub struct Stats {
     pub kurt: Real,
     pub min: Real,
     pub max: Real,
+    pub median: Real,
     pub size: usize,
 }
 
@@ -425,9 +459,437 @@ macro_rules! AccImpl {
         )*
     };
 }
-AccImpl!(MeanAcc, StdAcc, KurtAcc);
+AccImpl!(MeanAcc, KurtAcc);
 pub type Accumulator = MinMaxAcc<KurtAcc>;
 
+/// Streaming estimate of a single quantile using the P² algorithm of Jain
+/// and Chlamtac (1985).
+///
+/// It uses constant memory, storing only five markers that track the minimum,
+/// maximum, the desired quantile and two intermediate quantiles. Estimates
+/// are exact for up to five observations.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct P2Quantile {
+    p: Real,
+    count: usize,
+    total: Real,
+    heights: [Real; 5],
+    positions: [Real; 5],
+    desired: [Real; 5],
+}
+
+impl P2Quantile {
+    /// Create estimator for the quantile p, in the 0-1 range.
+    pub fn new(p: Real) -> Self {
+        let p = p.clamp(0.0, 1.0);
+        P2Quantile {
+            p,
+            count: 0,
+            total: 0.0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+        }
+    }
+
+    /// Current estimate of the quantile, or NaN if there are no observations.
+    pub fn quantile(&self) -> Real {
+        match self.count {
+            0 => NAN,
+            n if n < 5 => {
+                let mut data = self.heights[..n].to_vec();
+                data.sort_by(|a, b| a.total_cmp(b));
+                let pos = self.p * (n - 1) as Real;
+                let (i, frac) = (pos.floor() as usize, pos.fract());
+                match data.get(i + 1) {
+                    Some(next) => data[i] + frac * (next - data[i]),
+                    None => data[i],
+                }
+            }
+            _ => self.heights[2],
+        }
+    }
+}
+
+/// P2Quantile only tracks the number of observations, their sum and the
+/// extreme values exactly. Higher moments are not available and return NaN.
+/// NaN observations cannot be ranked and are ignored.
+impl Sampling for P2Quantile {
+    fn add(&mut self, x: Real) {
+        if x.is_nan() {
+            return;
+        }
+        self.total += x;
+        let (q, n) = (&mut self.heights, &mut self.positions);
+        if self.count < 5 {
+            q[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                q.sort_by(|a, b| a.total_cmp(b));
+            }
+            return;
+        }
+        self.count += 1;
+
+        // Find cell of x, updating extreme markers if necessary
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (1..4).take_while(|&i| x >= q[i]).count()
+        };
+        for pos in n.iter_mut().skip(k + 1) {
+            *pos += 1.0;
+        }
+        let p = self.p;
+        let increments = [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0];
+        for (d, inc) in self.desired.iter_mut().zip(increments.iter()) {
+            *d += inc;
+        }
+
+        // Adjust heights of the inner markers
+        for i in 1..4 {
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    fn add_many(&mut self, x: Real, n: usize) {
+        for _ in 0..n {
+            self.add(x);
+        }
+    }
+
+    fn sample_size(&self) -> usize {
+        self.count
+    }
+
+    fn total(&self) -> Real {
+        self.total
+    }
+
+    fn min(&self) -> Real {
+        let n = self.count.min(5);
+        self.heights[..n].iter().fold(INF, |acc, x| acc.min(*x))
+    }
+
+    fn max(&self) -> Real {
+        let n = self.count.min(5);
+        self.heights[..n].iter().fold(-INF, |acc, x| acc.max(*x))
+    }
+
+    fn var(&self) -> Real {
+        NAN
+    }
+
+    fn skew(&self) -> Real {
+        NAN
+    }
+
+    fn kurt(&self) -> Real {
+        NAN
+    }
+
+    fn median(&self) -> Real {
+        if self.p == 0.5 {
+            return self.quantile();
+        }
+        return NAN;
+    }
+}
+
+/// Track the 5%, 25%, 50%, 75% and 95% quantiles of a sample simultaneously
+/// with P2Quantile estimators, together with its moments.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quantiles {
+    quantiles: [P2Quantile; 5],
+    acc: Accumulator,
+}
+
+impl Quantiles {
+    /// Probabilities of the tracked quantiles.
+    pub const PROBABILITIES: [Real; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Estimates of all tracked quantiles, in the order of PROBABILITIES.
+    pub fn values(&self) -> [Real; 5] {
+        self.quantiles.map(|q| q.quantile())
+    }
+
+    pub fn p05(&self) -> Real {
+        self.quantiles[0].quantile()
+    }
+
+    pub fn p25(&self) -> Real {
+        self.quantiles[1].quantile()
+    }
+
+    pub fn p50(&self) -> Real {
+        self.quantiles[2].quantile()
+    }
+
+    pub fn p75(&self) -> Real {
+        self.quantiles[3].quantile()
+    }
+
+    pub fn p95(&self) -> Real {
+        self.quantiles[4].quantile()
+    }
+}
+
+impl Default for Quantiles {
+    fn default() -> Self {
+        Quantiles {
+            quantiles: Self::PROBABILITIES.map(P2Quantile::new),
+            acc: Accumulator::new(),
+        }
+    }
+}
+
+impl Sampling for Quantiles {
+    fn add_many(&mut self, x: Real, n: usize) {
+        for q in self.quantiles.iter_mut() {
+            q.add_many(x, n);
+        }
+        self.acc.add_many(x, n);
+    }
+    fn sample_size(&self) -> usize {
+        self.acc.sample_size()
+    }
+    fn total(&self) -> Real {
+        self.acc.total()
+    }
+    fn min(&self) -> Real {
+        self.acc.min()
+    }
+    fn max(&self) -> Real {
+        self.acc.max()
+    }
+    fn var(&self) -> Real {
+        self.acc.var()
+    }
+    fn skew(&self) -> Real {
+        self.acc.skew()
+    }
+    fn kurt(&self) -> Real {
+        self.acc.kurt()
+    }
+    fn last_sample(&self) -> Real {
+        self.acc.last_sample()
+    }
+    fn median(&self) -> Real {
+        self.p50()
+    }
+}
+
+/// Exponentially weighted moving average.
+///
+/// Each new observation x updates the mean as mean = alpha * x + (1 - alpha) *
+/// mean, so larger values of alpha track the latest observations more closely.
+/// The first observation initializes the mean. The variance is the
+/// exponentially weighted variance computed with the same weights. Higher
+/// moments are not tracked and return NaN, while total() and sample_size()
+/// refer to all observations without weights.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EwmaAcc {
+    alpha: Real,
+    count: usize,
+    total: Real,
+    mean: Real,
+    var: Real,
+    last: Real,
+}
+
+impl EwmaAcc {
+    /// Create accumulator with the given decay, in the (0, 1] range.
+    pub fn new(alpha: Real) -> Self {
+        assert!(alpha > 0.0 && alpha <= 1.0, "alpha must be in (0, 1]");
+        EwmaAcc {
+            alpha,
+            count: 0,
+            total: 0.0,
+            mean: NAN,
+            var: NAN,
+            last: NAN,
+        }
+    }
+
+    /// Decay factor.
+    pub fn alpha(&self) -> Real {
+        self.alpha
+    }
+}
+
+impl Sampling for EwmaAcc {
+    fn add(&mut self, x: Real) {
+        if self.count == 0 {
+            self.mean = x;
+            self.var = 0.0;
+        } else {
+            let diff = x - self.mean;
+            let incr = self.alpha * diff;
+            self.mean += incr;
+            self.var = (1.0 - self.alpha) * (self.var + diff * incr);
+        }
+        self.count += 1;
+        self.total += x;
+        self.last = x;
+    }
+    fn add_many(&mut self, x: Real, n: usize) {
+        for _ in 0..n {
+            self.add(x);
+        }
+    }
+    fn sample_size(&self) -> usize {
+        self.count
+    }
+    fn total(&self) -> Real {
+        self.total
+    }
+    fn mean(&self) -> Real {
+        self.mean
+    }
+    fn var(&self) -> Real {
+        self.var
+    }
+    fn skew(&self) -> Real {
+        NAN
+    }
+    fn kurt(&self) -> Real {
+        NAN
+    }
+    fn last_sample(&self) -> Real {
+        self.last
+    }
+}
+
+/// Count observations in bins with configurable edges.
+///
+/// Bins are left-closed and right-open, so a value equal to an edge is
+/// counted in the bin that starts at that edge. Values below the first edge
+/// go to an open underflow bin and values equal to or above the last edge go
+/// to an open overflow bin. Moments and extreme values are tracked exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    edges: Vec<Real>,
+    counts: Vec<usize>,
+    underflow: usize,
+    overflow: usize,
+    acc: Accumulator,
+}
+
+impl Histogram {
+    /// Create histogram from a strictly increasing list of bin edges. N edges
+    /// define N - 1 bins plus the underflow and overflow bins.
+    pub fn new(edges: Vec<Real>) -> Self {
+        assert!(!edges.is_empty(), "histogram needs at least one edge");
+        assert!(
+            edges.windows(2).all(|w| w[0] < w[1]),
+            "edges must be strictly increasing"
+        );
+        Histogram {
+            counts: vec![0; edges.len() - 1],
+            edges,
+            underflow: 0,
+            overflow: 0,
+            acc: Accumulator::new(),
+        }
+    }
+
+    /// Create histogram with n bins of equal width in the [low, high) range.
+    pub fn uniform(low: Real, high: Real, n: usize) -> Self {
+        let width = (high - low) / n as Real;
+        return Self::new((0..=n).map(|i| low + width * i as Real).collect());
+    }
+
+    /// Bin edges.
+    pub fn edges(&self) -> &[Real] {
+        &self.edges
+    }
+
+    /// Counts of the inner bins, without underflow and overflow.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// Number of observations below the first edge.
+    pub fn underflow(&self) -> usize {
+        self.underflow
+    }
+
+    /// Number of observations equal to or above the last edge.
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+
+    /// List of (low, high, count) for all bins, starting with the underflow
+    /// bin (-inf, first edge) and ending with the overflow bin [last edge, inf).
+    pub fn bins(&self) -> Vec<(Real, Real, usize)> {
+        let mut bins = Vec::with_capacity(self.counts.len() + 2);
+        bins.push((-INF, self.edges[0], self.underflow));
+        for (i, &count) in self.counts.iter().enumerate() {
+            bins.push((self.edges[i], self.edges[i + 1], count));
+        }
+        bins.push((self.edges[self.edges.len() - 1], INF, self.overflow));
+        return bins;
+    }
+}
+
+impl Sampling for Histogram {
+    fn add_many(&mut self, x: Real, n: usize) {
+        // Number of edges <= x. NaN values are counted as underflow.
+        match self.edges.partition_point(|&e| e <= x) {
+            0 => self.underflow += n,
+            i if i == self.edges.len() => self.overflow += n,
+            i => self.counts[i - 1] += n,
+        }
+        self.acc.add_many(x, n);
+    }
+    fn sample_size(&self) -> usize {
+        self.acc.sample_size()
+    }
+    fn total(&self) -> Real {
+        self.acc.total()
+    }
+    fn min(&self) -> Real {
+        self.acc.min()
+    }
+    fn max(&self) -> Real {
+        self.acc.max()
+    }
+    fn var(&self) -> Real {
+        self.acc.var()
+    }
+    fn skew(&self) -> Real {
+        self.acc.skew()
+    }
+    fn kurt(&self) -> Real {
+        self.acc.kurt()
+    }
+    fn last_sample(&self) -> Real {
+        self.acc.last_sample()
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Tests
 ///////////////////////////////////////////////////////////////////////////////
@@ -435,7 +897,9 @@ pub type Accumulator = MinMaxAcc<KurtAcc>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{trackers::Tracker, utils::seeded_rng};
     use assert_approx_eq::assert_approx_eq;
+    use rand::Rng;
 
     #[test]
     fn simple_stats() {
@@ -447,6 +911,152 @@ mod tests {
         assert_approx_eq!(st.mean, 2.0, 0.001);
         assert_approx_eq!(st.std, 1.4142, 0.001);
         assert_approx_eq!(st.skew, 0.0, 0.001);
-        assert_approx_eq!(st.kurt, 3.40, 0.001);
+        assert_approx_eq!(st.kurt, 1.7, 0.001);
+    }
+
+    #[test]
+    fn p2_quantile_estimates_uniform_quantiles() {
+        let mut quantiles: Vec<_> = [0.05, 0.5, 0.9]
+            .iter()
+            .map(|&p| P2Quantile::new(p))
+            .collect();
+        assert!(quantiles[0].quantile().is_nan());
+        for i in 0..10_000 {
+            // Low discrepancy sequence in [0, 1)
+            let x = (i as Real * 0.618_033_988_75).fract();
+            for q in quantiles.iter_mut() {
+                q.add(x);
+            }
+        }
+        assert_approx_eq!(quantiles[0].quantile(), 0.05, 0.01);
+        assert_approx_eq!(quantiles[1].quantile(), 0.5, 0.01);
+        assert_approx_eq!(quantiles[2].quantile(), 0.9, 0.01);
+
+        let mut small = P2Quantile::new(0.5);
+        for &x in [4.0, 1.0, 3.0, 2.0].iter() {
+            small.add(x);
+        }
+        assert_approx_eq!(small.quantile(), 2.5);
+    }
+
+    #[test]
+    fn quantiles_do_not_panic_on_nan() {
+        let mut median = P2Quantile::new(0.5);
+        for &x in [4.0, NAN, 1.0, 3.0, 2.0, 5.0, NAN].iter() {
+            median.add(x);
+        }
+        assert_eq!(median.sample_size(), 5);
+        assert_eq!(median.quantile(), 3.0);
+        assert!(vec![1.0, NAN, 2.0].median().is_nan());
+    }
+
+    #[test]
+    fn quantiles_track_median_of_sample() {
+        let rng = &mut seeded_rng(42u64);
+        let mut data: Vec<Real> = vec![];
+        let mut quantiles = Quantiles::new();
+        let mut median = P2Quantile::new(0.5);
+        for _ in 0..5_000 {
+            let x: Real = rng.gen::<Real>().powi(2);
+            data.add(x);
+            quantiles.add(x);
+            median.add(x);
+        }
+        assert_eq!(quantiles.sample_size(), data.sample_size());
+        assert_approx_eq!(quantiles.mean(), data.mean());
+        assert_approx_eq!(quantiles.median(), data.median(), 0.01);
+        assert_approx_eq!(median.median(), data.median(), 0.01);
+        assert_eq!(median.max(), data.max());
+        assert!(quantiles.p05() < quantiles.p25());
+        assert!(quantiles.p75() < quantiles.p95());
+        assert_approx_eq!(quantiles.p95(), 0.95 * 0.95, 0.02);
+
+        let stats = quantiles.stats();
+        assert_eq!(stats.median, quantiles.p50());
+        assert_eq!(vec![3.0, 1.0, 2.0, 10.0].median(), 2.5);
+        assert!(P2Quantile::new(0.9).median().is_nan());
+    }
+
+    #[test]
+    fn weighted_stats_match_flat_sample() {
+        let counts: Vec<(Real, usize)> = vec![(1.0, 2), (3.0, 2)];
+        let flat: Vec<Real> = vec![1.0, 1.0, 3.0, 3.0];
+        assert_approx_eq!(counts.var(), flat.var());
+        assert_approx_eq!(counts.var(), 1.0);
+        assert_approx_eq!(counts.std(), flat.std());
+        assert_approx_eq!(counts.skew(), flat.skew());
+        assert_approx_eq!(counts.kurt(), flat.kurt());
+    }
+
+    #[test]
+    fn std_acc_tracks_higher_moments() {
+        let data = [0.0, 1.0, 1.0, 2.0, 5.0];
+        let acc = StdAcc::from_seq(data.iter().cloned());
+        assert_approx_eq!(acc.var(), 2.96);
+        assert_approx_eq!(acc.skew(), 1.01795, 1e-4);
+        assert_approx_eq!(acc.kurt(), 2.65194, 1e-4);
+    }
+
+    #[test]
+    fn merged_halves_match_whole_sequence() {
+        let data: Vec<Real> = (0..100).map(|i| ((i * 37) % 17) as Real).collect();
+        let (left, right) = data.split_at(40);
+        let whole = Accumulator::from_data(data.iter().cloned());
+        let merged = Accumulator::from_data(left.iter().cloned())
+            .merge(&Accumulator::from_data(right.iter().cloned()));
+        assert_eq!(merged.sample_size(), whole.sample_size());
+        assert_approx_eq!(merged.mean(), whole.mean());
+        assert_approx_eq!(merged.var(), whole.var());
+        assert_approx_eq!(merged.skew(), whole.skew());
+        assert_approx_eq!(merged.kurt(), whole.kurt());
+        assert_eq!(merged.min(), whole.min());
+        assert_eq!(merged.max(), whole.max());
+        assert_eq!(merged.last_sample(), whole.last_sample());
+        assert_eq!(merged.merge(&Accumulator::new()), merged);
+
+        let std =
+            StdAcc::from_seq(left.iter().cloned()).merge(&StdAcc::from_seq(right.iter().cloned()));
+        assert_approx_eq!(std.kurt(), whole.kurt());
+        let mean = MeanAcc::from_seq(left.iter().cloned())
+            .merge(&MeanAcc::from_seq(right.iter().cloned()));
+        assert_approx_eq!(mean.mean(), whole.mean());
+    }
+
+    #[test]
+    fn histogram_counts_left_closed_bins() {
+        let mut hist = Histogram::uniform(0.0, 3.0, 3);
+        assert_eq!(hist.edges(), &[0.0, 1.0, 2.0, 3.0]);
+        hist.add_sequence(vec![-0.5, 0.0, 0.5, 1.0, 2.5, 3.0, 7.0].into_iter());
+        hist.add_many(1.5, 2);
+        assert_eq!(
+            hist.bins(),
+            vec![
+                (-INF, 0.0, 1),
+                (0.0, 1.0, 2),
+                (1.0, 2.0, 3),
+                (2.0, 3.0, 1),
+                (3.0, INF, 2),
+            ]
+        );
+        assert_eq!(hist.sample_size(), 9);
+        assert_eq!(hist.max(), 7.0);
+        assert_eq!(hist.last_sample(), 1.5);
+
+        Tracker::track(&mut hist, &2.0);
+        assert_eq!(hist.counts(), &[2, 3, 2]);
+    }
+
+    #[test]
+    fn ewma_matches_hand_computed_average() {
+        let mut acc = EwmaAcc::new(0.5);
+        assert!(acc.mean().is_nan());
+        acc.add_sequence(vec![4.0, 8.0, 2.0].into_iter());
+        // 4 -> 0.5 * 8 + 0.5 * 4 = 6 -> 0.5 * 2 + 0.5 * 6 = 4
+        assert_approx_eq!(acc.mean(), 4.0);
+        // 0 -> 0.5 * (0 + 4 * 2) = 4 -> 0.5 * (4 + 4 * 2) = 6
+        assert_approx_eq!(acc.var(), 6.0);
+        assert_eq!(acc.sample_size(), 3);
+        assert_eq!(acc.total(), 14.0);
+        assert_eq!(acc.last_sample(), 2.0);
     }
 }
@@ -0,0 +1,29 @@
+use std::{env, fs, process::Command};
+
+const TINY_CONFIG: &str = r#"
+pop_size = 200
+initial_infections = 5
+num_iter = 5
+verbose = false
+"#;
+
+#[test]
+fn sim_runs_directory_of_configs() {
+    let dir = env::temp_dir().join(format!("covid-batch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("conf_a.toml"), TINY_CONFIG).unwrap();
+    fs::write(dir.join("conf_b.toml"), TINY_CONFIG).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_sim"))
+        .arg(&dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    for name in &["a", "b"] {
+        let path = dir.join(format!("epicurve_{}.csv", name));
+        let data = fs::read_to_string(&path).unwrap();
+        assert!(data.starts_with("S,E,I,C,H,A,R,D,cases"));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
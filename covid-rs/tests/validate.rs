@@ -0,0 +1,72 @@
+use covid::{
+    models::{SEAIRm, SEICHARm, SEIRm},
+    params::{EpiParamsFull, EpiParamsLocal},
+    prelude::*,
+    sim::{RandomUpdate, Simulation},
+    utils::seeded_rng,
+};
+use std::fmt::Debug;
+
+const POP_SIZE: usize = 5000;
+const N_RUNS: u64 = 8;
+const TOLERANCE: Real = 0.05;
+
+/// Mean (attack rate, death ratio) over several runs of model M.
+fn run_model<M>(params: &EpiParamsFull<Real>) -> (Real, Real)
+where
+    M: EpiModel + RandomUpdate<EpiParamsLocal> + Debug + Default,
+    M::Clinical: Default,
+{
+    let (mut attack, mut deaths) = (0.0, 0.0);
+    for seed in 0..N_RUNS {
+        let population = vec![M::default(); POP_SIZE];
+        let mut sim = Simulation::new_simple(EpiParamsLocal::new(params), population, 5.0, 0.1);
+        sim.seed(seed);
+        sim.contaminate_at_random(20, &mut seeded_rng(seed));
+        sim.run(200);
+        attack += sim.population().attack_ratio();
+        deaths += sim.population().n_dead() as Real / POP_SIZE as Real;
+    }
+    return (attack / N_RUNS as Real, deaths / N_RUNS as Real);
+}
+
+/// Run each model with the same epidemiological parameters and check that
+/// final attack rates and death tolls are mutually consistent.
+///
+/// Params should disable asymptomatic and severe cases, so that SEAIR and
+/// SEICHAR collapse to SEIR. SIR is not included: agents are updated before
+/// contacts in each step, which shortens the effective infectious period of
+/// newly infected agents when there is no exposed state.
+fn model_consistency(params: &EpiParamsFull<Real>) {
+    let reference = run_model::<SEIRm>(params);
+    let results = [
+        ("SEAIR", run_model::<SEAIRm>(params)),
+        ("SEICHAR", run_model::<SEICHARm>(params)),
+    ];
+
+    assert!(reference.0 > 0.3, "SEIR epidemic did not take off");
+    for (name, (attack, deaths)) in results.iter() {
+        assert!(
+            (attack - reference.0).abs() < TOLERANCE,
+            "{} attack rate {} differs from SEIR {}",
+            name,
+            attack,
+            reference.0
+        );
+        assert!(
+            (deaths - reference.1).abs() < TOLERANCE,
+            "{} death ratio {} differs from SEIR {}",
+            name,
+            deaths,
+            reference.1
+        );
+    }
+}
+
+#[test]
+fn seichar_reduced_to_seir_is_consistent() {
+    let mut params: EpiParamsFull<Real> = Default::default();
+    params.epidemic.set_prob_asymptomatic(0.0);
+    params.clinical.set_prob_severe(0.0).set_prob_critical(0.0);
+    model_consistency(&params);
+}